@@ -0,0 +1,66 @@
+// The `logging` module of every board crate, included with `#[path]` so
+// that `$crate` and the `defmt` feature are the ones of the board crate.
+// It is not part of this crate.
+
+//! Logging over RTT.
+//!
+//! By default, [`info!`](crate::info), [`warn!`](crate::warn) and
+//! [`error!`](crate::error) print formatted text with `rtt-target`. With the
+//! `defmt` feature they log with `defmt` instead: the format strings stay on
+//! the host and only the arguments are sent, which saves flash and time spent
+//! formatting.
+//! The `defmt` profile of `Embed.toml` decodes the output:
+//!
+//! ```text
+//! cargo embed defmt --example <example> --features defmt --release
+//! ```
+//!
+//! The same format string is passed to both, so only `{}` and `{:?}` can be
+//! used. With `defmt`, the arguments must implement `defmt::Format`. The
+//! readings, failures and other types of the common crate do. Values which
+//! only implement `Debug`, like most driver errors, can be wrapped in
+//! [`Dbg`].
+
+pub use driver_examples_common::log::Dbg;
+
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+pub use defmt;
+
+/// Stands in for `defmt` without the `defmt` feature, so that the macros
+/// expand to the same path for both: every level prints formatted text.
+#[cfg(not(feature = "defmt"))]
+#[doc(hidden)]
+pub mod defmt {
+    pub use rtt_target::{rprintln as info, rprintln as warn, rprintln as error};
+}
+
+/// Set up RTT. Call it once at the start of the program, before logging.
+pub fn init() {
+    #[cfg(not(feature = "defmt"))]
+    rtt_target::rtt_init_print!();
+}
+
+/// Log an informational message.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::logging::defmt::info!($($arg)*)
+    };
+}
+
+/// Log a warning, e.g. about a failed reading which will be retried.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::logging::defmt::warn!($($arg)*)
+    };
+}
+
+/// Log an error.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::logging::defmt::error!($($arg)*)
+    };
+}
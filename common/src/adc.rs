@@ -0,0 +1,27 @@
+//! Presentation of ADC readings.
+
+/// Make a raw ADS1x15 reading smaller for reading ease.
+///
+/// The full 16-bit range is reduced to [-1024, 1023] so that it fits in a
+/// few columns of a display.
+pub const fn scale_reading(raw: i16) -> i16 {
+    raw >> 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_scale() {
+        assert_eq!(1023, scale_reading(i16::MAX));
+        assert_eq!(-1024, scale_reading(i16::MIN));
+    }
+
+    #[test]
+    fn small_values() {
+        assert_eq!(0, scale_reading(31));
+        assert_eq!(1, scale_reading(32));
+        assert_eq!(-1, scale_reading(-1));
+    }
+}
@@ -0,0 +1,131 @@
+//! I2C bus shared between the display and the devices, and SPI bus shared
+//! between devices with their own chip select pins.
+
+use core::{cell::RefCell, convert::Infallible};
+use critical_section::Mutex;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal_bus::i2c::CriticalSectionDevice;
+use embedded_hal_bus::spi::{self, NoDelay};
+
+/// Handle to an [`I2cBus`] which can be passed to any driver.
+///
+/// It implements `embedded_hal::i2c::I2c`.
+pub type I2cDevice<'a, I2C> = CriticalSectionDevice<'a, I2C>;
+
+/// I2C bus shared between several drivers.
+///
+/// Every driver gets its own [`I2cDevice`] handle from
+/// [`acquire_i2c`](I2cBus::acquire_i2c). Each transaction runs inside a
+/// critical section so the handles can also be used from interrupt
+/// handlers.
+///
+/// ```
+/// # use driver_examples_common::I2cBus;
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) {
+/// let bus = I2cBus::new(i2c);
+/// let sensor_i2c = bus.acquire_i2c();
+/// let display_i2c = bus.acquire_i2c();
+/// # }
+/// ```
+pub struct I2cBus<I2C> {
+    bus: Mutex<RefCell<I2C>>,
+}
+
+impl<I2C> I2cBus<I2C> {
+    /// Create a new bus from an I2C peripheral.
+    pub const fn new(i2c: I2C) -> Self {
+        I2cBus {
+            bus: Mutex::new(RefCell::new(i2c)),
+        }
+    }
+
+    /// Get a new handle to the bus.
+    pub fn acquire_i2c(&self) -> I2cDevice<'_, I2C> {
+        CriticalSectionDevice::new(&self.bus)
+    }
+
+    /// Access the I2C peripheral directly, e.g. to configure it.
+    ///
+    /// `f` runs inside a critical section.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut I2C) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.bus.borrow_ref_mut(cs)))
+    }
+
+    /// Destroy the bus and return the I2C peripheral.
+    pub fn release(self) -> I2C {
+        self.bus.into_inner().into_inner()
+    }
+}
+
+/// Handle to an [`SpiBus`] with its own chip select pin, which can be passed
+/// to any driver.
+///
+/// It implements `embedded_hal::spi::SpiDevice`.
+pub type SpiDevice<'a, SPI, CS> = spi::CriticalSectionDevice<'a, SPI, CS, NoDelay>;
+
+/// Chip select "pin" for the devices whose driver drives the chip select
+/// pin itself, like the embedded-hal 0.2 ones. Setting it does nothing.
+///
+/// ```
+/// # use driver_examples_common::{bus::NoChipSelect, Compat, SpiBus};
+/// # fn example<SPI: embedded_hal::spi::SpiBus>(spi: SPI) {
+/// let bus = SpiBus::new(spi);
+/// // Implements embedded_hal_02::blocking::spi::Write<u8>.
+/// let spi = Compat::new(bus.acquire_spi(NoChipSelect).unwrap());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NoChipSelect;
+
+impl ErrorType for NoChipSelect {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoChipSelect {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// SPI bus shared between several devices which use the same SPI mode.
+///
+/// Every device gets its own [`SpiDevice`] handle driving its chip select
+/// pin from [`acquire_spi`](SpiBus::acquire_spi). As with the [`I2cBus`],
+/// each transaction runs inside a critical section.
+///
+/// ```
+/// # use driver_examples_common::SpiBus;
+/// # fn example<SPI, CS>(spi: SPI, cs0: CS, cs1: CS)
+/// # where SPI: embedded_hal::spi::SpiBus, CS: embedded_hal::digital::OutputPin {
+/// let bus = SpiBus::new(spi);
+/// let left = bus.acquire_spi(cs0).unwrap();
+/// let right = bus.acquire_spi(cs1).unwrap();
+/// # }
+/// ```
+pub struct SpiBus<SPI> {
+    bus: Mutex<RefCell<SPI>>,
+}
+
+impl<SPI> SpiBus<SPI> {
+    /// Create a new bus from an SPI peripheral.
+    pub const fn new(spi: SPI) -> Self {
+        SpiBus {
+            bus: Mutex::new(RefCell::new(spi)),
+        }
+    }
+
+    /// Get a new handle to the bus for the device selected by
+    /// `chip_select`.
+    ///
+    /// The chip select pin is set high (inactive) right away.
+    pub fn acquire_spi<CS: OutputPin>(
+        &self,
+        chip_select: CS,
+    ) -> Result<SpiDevice<'_, SPI, CS>, CS::Error> {
+        spi::CriticalSectionDevice::new_no_delay(&self.bus, chip_select)
+    }
+}
@@ -0,0 +1,88 @@
+//! Monotonic time from a free-running hardware counter.
+//!
+//! The telemetry samples carry the time they were taken at. The boards have
+//! a counter which runs all the time, like the cycle counter of the Cortex-M3
+//! and M4 cores or an RTC, but it wraps around after a while (59 seconds for
+//! the cycle counter at 72 MHz). [`Clock`] counts the wraps, so it only needs
+//! to be read more often than that:
+//!
+//! ```
+//! # use driver_examples_common::clock::Clock;
+//! fn cycle_count() -> u32 {
+//!     // e.g. cortex_m::peripheral::DWT::cycle_count()
+//! #   0
+//! }
+//!
+//! let mut clock = Clock::new(72_000_000, cycle_count);
+//! let timestamp_ms = clock.now_ms();
+//! ```
+
+/// Time since a counter was started, robust to its wrap-arounds.
+#[derive(Debug)]
+pub struct Clock {
+    read: fn() -> u32,
+    hz: u64,
+    mask: u32,
+    last: u32,
+    ticks: u64,
+}
+
+impl Clock {
+    /// Create a clock for a 32-bit counter running at `hz`.
+    pub fn new(hz: u32, read: fn() -> u32) -> Self {
+        Self::with_width(hz, 32, read)
+    }
+
+    /// Create a clock for a counter of `bits` bits running at `hz`, e.g. the
+    /// 24-bit RTC of the nRF51.
+    pub fn with_width(hz: u32, bits: u32, read: fn() -> u32) -> Self {
+        let mask = u32::MAX >> (32 - bits);
+        Clock {
+            read,
+            hz: u64::from(hz),
+            mask,
+            last: read() & mask,
+            ticks: 0,
+        }
+    }
+
+    /// Milliseconds since the clock was created.
+    ///
+    /// Must be called more often than the counter wraps around. Wraps around
+    /// itself after 49 days.
+    pub fn now_ms(&mut self) -> u32 {
+        let now = (self.read)() & self.mask;
+        self.ticks += u64::from(now.wrapping_sub(self.last) & self.mask);
+        self.last = now;
+        (self.ticks * 1000 / self.hz) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn counts_wrap_arounds() {
+        static COUNTER: AtomicU32 = AtomicU32::new(u32::MAX - 999);
+        let mut clock = Clock::new(1_000_000, || COUNTER.load(Ordering::Relaxed));
+
+        COUNTER.store(1_000, Ordering::Relaxed);
+        assert_eq!(2, clock.now_ms());
+        COUNTER.store(4_000_000_000, Ordering::Relaxed);
+        assert_eq!(4_000_001, clock.now_ms());
+        COUNTER.store(3_000_000, Ordering::Relaxed);
+        assert_eq!(4_297_968, clock.now_ms());
+    }
+
+    #[test]
+    fn narrow_counter() {
+        static COUNTER: AtomicU32 = AtomicU32::new(0x00ff_ffff);
+        let mut clock = Clock::with_width(32_768, 24, || COUNTER.load(Ordering::Relaxed));
+
+        // The unused bits are ignored and the counter wraps at 24 bits.
+        COUNTER.store(0xff00_7fff, Ordering::Relaxed);
+        assert_eq!(1_000, clock.now_ms());
+    }
+}
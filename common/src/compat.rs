@@ -0,0 +1,374 @@
+//! Adapter between the embedded-hal 0.2 and 1.0 traits.
+//!
+//! The examples and most drivers only use the embedded-hal 1.0 traits. The
+//! board modules wrap the peripherals of HALs which have not been ported
+//! yet in a [`Compat`] so that nothing outside of them notices the
+//! difference.
+//!
+//! It also works the other way round for the drivers which have no
+//! embedded-hal 1.0 release yet: an I2C or SPI device wrapped in a
+//! [`Compat`] implements the blocking embedded-hal 0.2 I2C or SPI traits.
+
+use core::{fmt::Debug, marker::PhantomData};
+use embedded_hal::{delay::DelayNs, digital, i2c, spi};
+use embedded_hal_02::blocking::{
+    delay::{DelayMs, DelayUs},
+    i2c as i2c_02, spi as spi_02,
+};
+use embedded_hal_02::digital::v2 as digital_02;
+use embedded_hal_02::spi as spi_mode_02;
+
+/// Peripheral implementing the embedded-hal 0.2 traits wrapped so that it
+/// implements their embedded-hal 1.0 counterparts.
+///
+/// Supported are blocking I2C, blocking SPI (as `SpiBus`), output pins
+/// and delays. Input pins are wrapped in an [`InputCompat`] instead.
+///
+/// `K` classifies the I2C errors of the HAL, see [`ErrorKinds`].
+#[derive(Debug)]
+pub struct Compat<T, K = Unclassified>(T, PhantomData<K>);
+
+impl<T> Compat<T> {
+    /// Wrap a peripheral whose errors are all reported as "other".
+    pub const fn new(inner: T) -> Self {
+        Compat(inner, PhantomData)
+    }
+}
+
+impl<T, K> Compat<T, K> {
+    /// Wrap a peripheral whose errors are classified by `K`.
+    pub const fn with_error_kinds(inner: T) -> Self {
+        Compat(inner, PhantomData)
+    }
+
+    /// Access the wrapped peripheral.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Destroy the adapter and return the wrapped peripheral.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Classification of the I2C errors of an embedded-hal 0.2 HAL.
+///
+/// Their errors carry no information about their kind, so the board
+/// modules implement this on a type of their own for the errors of their
+/// HAL and wrap the peripheral with [`Compat::with_error_kinds`]:
+///
+/// ```
+/// # use driver_examples_common::compat::ErrorKinds;
+/// # use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+/// # #[derive(Debug)]
+/// # enum HalError { Nack, Bus }
+/// #[derive(Debug)]
+/// pub struct HalErrorKinds;
+///
+/// impl ErrorKinds<HalError> for HalErrorKinds {
+///     fn i2c(error: &HalError) -> ErrorKind {
+///         match error {
+///             HalError::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+///             HalError::Bus => ErrorKind::Bus,
+///         }
+///     }
+/// }
+/// ```
+pub trait ErrorKinds<E> {
+    /// Kind of an I2C error.
+    fn i2c(error: &E) -> i2c::ErrorKind;
+}
+
+/// Reports every error as "other".
+#[derive(Debug)]
+pub struct Unclassified;
+
+impl<E> ErrorKinds<E> for Unclassified {
+    fn i2c(_error: &E) -> i2c::ErrorKind {
+        i2c::ErrorKind::Other
+    }
+}
+
+/// Error of a wrapped peripheral.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatError<E> {
+    /// Error reported by a wrapped SPI peripheral or pin. Its kind is
+    /// always "other".
+    Hal(E),
+    /// Error reported by a wrapped I2C peripheral and its kind.
+    I2c(E, i2c::ErrorKind),
+    /// The I2C operations cannot be done in one transaction. The HAL can
+    /// only do a single read, a single write or a write followed by a read
+    /// without releasing the bus in between.
+    UnsupportedTransaction,
+}
+
+impl<E: Debug> i2c::Error for CompatError<E> {
+    fn kind(&self) -> i2c::ErrorKind {
+        match self {
+            CompatError::I2c(_, kind) => *kind,
+            CompatError::Hal(_) | CompatError::UnsupportedTransaction => i2c::ErrorKind::Other,
+        }
+    }
+}
+
+impl<E: Debug> spi::Error for CompatError<E> {
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+impl<E: Debug> digital::Error for CompatError<E> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl<T, K> Compat<T, K> {
+    fn i2c_error<E>(error: E) -> CompatError<E>
+    where
+        K: ErrorKinds<E>,
+    {
+        let kind = K::i2c(&error);
+        CompatError::I2c(error, kind)
+    }
+}
+
+impl<T, K, E> i2c::ErrorType for Compat<T, K>
+where
+    T: i2c_02::Write<Error = E> + i2c_02::Read<Error = E> + i2c_02::WriteRead<Error = E>,
+    E: Debug,
+{
+    type Error = CompatError<E>;
+}
+
+impl<T, K, E> i2c::I2c for Compat<T, K>
+where
+    T: i2c_02::Write<Error = E> + i2c_02::Read<Error = E> + i2c_02::WriteRead<Error = E>,
+    K: ErrorKinds<E>,
+    E: Debug,
+{
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, read).map_err(Self::i2c_error)
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, write).map_err(Self::i2c_error)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0
+            .write_read(address, write, read)
+            .map_err(Self::i2c_error)
+    }
+
+    /// A write followed by a read is done with a repeated start. Any other
+    /// sequence of more than one operation fails with
+    /// [`CompatError::UnsupportedTransaction`] without touching the bus.
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match operations {
+            [] => Ok(()),
+            [i2c::Operation::Read(read)] => self.read(address, read),
+            [i2c::Operation::Write(write)] => self.write(address, write),
+            [i2c::Operation::Write(write), i2c::Operation::Read(read)] => {
+                self.write_read(address, write, read)
+            }
+            _ => Err(CompatError::UnsupportedTransaction),
+        }
+    }
+}
+
+impl<T: i2c::I2c, K> i2c_02::Write for Compat<T, K> {
+    type Error = T::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+}
+
+impl<T: i2c::I2c, K> i2c_02::Read for Compat<T, K> {
+    type Error = T::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, buffer)
+    }
+}
+
+impl<T: i2c::I2c, K> i2c_02::WriteRead for Compat<T, K> {
+    type Error = T::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0.write_read(address, bytes, buffer)
+    }
+}
+
+impl<T: spi::SpiDevice, K> spi_02::Write<u8> for Compat<T, K> {
+    type Error = T::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(words)
+    }
+}
+
+impl<T: spi::SpiDevice, K> spi_02::Transfer<u8> for Compat<T, K> {
+    type Error = T::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.0.transfer_in_place(words)?;
+        Ok(words)
+    }
+}
+
+impl<T, K, E> spi::ErrorType for Compat<T, K>
+where
+    T: spi_02::Transfer<u8, Error = E> + spi_02::Write<u8, Error = E>,
+    E: Debug,
+{
+    type Error = CompatError<E>;
+}
+
+impl<T, K, E> spi::SpiBus for Compat<T, K>
+where
+    T: spi_02::Transfer<u8, Error = E> + spi_02::Write<u8, Error = E>,
+    E: Debug,
+{
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0);
+        self.transfer_in_place(words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(words).map_err(CompatError::Hal)
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            let mut word = [write.get(i).copied().unwrap_or(0)];
+            self.0.transfer(&mut word).map_err(CompatError::Hal)?;
+            if let Some(read) = read.get_mut(i) {
+                *read = word[0];
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.transfer(words).map(|_| ()).map_err(CompatError::Hal)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Blocking 0.2 transfers are complete when they return.
+        Ok(())
+    }
+}
+
+impl<T, K, E> digital::ErrorType for Compat<T, K>
+where
+    T: digital_02::OutputPin<Error = E>,
+    E: Debug,
+{
+    type Error = CompatError<E>;
+}
+
+impl<T, K, E> digital::OutputPin for Compat<T, K>
+where
+    T: digital_02::OutputPin<Error = E>,
+    E: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(CompatError::Hal)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(CompatError::Hal)
+    }
+}
+
+/// Input pin implementing the embedded-hal 0.2 `InputPin` trait wrapped so
+/// that it implements the embedded-hal 1.0 one.
+///
+/// This is separate from [`Compat`] because a pin could implement both
+/// the input and the output traits.
+#[derive(Debug)]
+pub struct InputCompat<T>(T);
+
+impl<T> InputCompat<T> {
+    /// Wrap an input pin.
+    pub const fn new(inner: T) -> Self {
+        InputCompat(inner)
+    }
+
+    /// Destroy the adapter and return the wrapped pin.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, E> digital::ErrorType for InputCompat<T>
+where
+    T: digital_02::InputPin<Error = E>,
+    E: Debug,
+{
+    type Error = CompatError<E>;
+}
+
+impl<T, E> digital::InputPin for InputCompat<T>
+where
+    T: digital_02::InputPin<Error = E>,
+    E: Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_high().map_err(CompatError::Hal)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_low().map_err(CompatError::Hal)
+    }
+}
+
+impl<T, K> DelayNs for Compat<T, K>
+where
+    T: DelayUs<u32> + DelayMs<u32>,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us(ns.div_ceil(1_000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+/// Convert an SPI mode as exported by the drivers to the type expected by
+/// embedded-hal 0.2 HALs.
+pub fn spi_mode(mode: spi::Mode) -> spi_mode_02::Mode {
+    spi_mode_02::Mode {
+        polarity: match mode.polarity {
+            spi::Polarity::IdleLow => spi_mode_02::Polarity::IdleLow,
+            spi::Polarity::IdleHigh => spi_mode_02::Polarity::IdleHigh,
+        },
+        phase: match mode.phase {
+            spi::Phase::CaptureOnFirstTransition => spi_mode_02::Phase::CaptureOnFirstTransition,
+            spi::Phase::CaptureOnSecondTransition => spi_mode_02::Phase::CaptureOnSecondTransition,
+        },
+    }
+}
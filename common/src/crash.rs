@@ -0,0 +1,155 @@
+//! Panic report shown on the display.
+//!
+//! When a program panics on a board which only has a display attached, the
+//! screen freezes with the last values and nothing tells that something went
+//! wrong. The panic handlers of the board crates build a [`Report`] from the
+//! panic and show it on a [`TextPanel`](crate::TextPanel) instead:
+//!
+//! ```text
+//! PANIC
+//! ---------------------
+//! called `Result::unwra
+//! p()` on an `Err` valu
+//! e: I2C(Timeout)
+//! lm75-display-bp.rs:48
+//! ```
+
+use crate::display::{DisplayError, WriteOnlyDataCommand};
+use crate::reading::Truncating;
+use crate::text_panel::{Row, TextPanel, COLUMNS, ROWS};
+use core::fmt::{self, Display, Write};
+use core::panic::PanicInfo;
+use ssd1306::prelude::DisplaySize;
+
+/// Text of a panic message. Longer messages are cut off.
+pub type Message = heapless::String<{ (ROWS - 1) * COLUMNS }>;
+
+/// Message, file and line of a panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Panic message, cut off to what fits on the display.
+    pub message: Message,
+    /// File where the panic happened.
+    pub file: Row,
+    /// Line where the panic happened.
+    pub line: u32,
+}
+
+impl Report {
+    /// Create a report from the information passed to the panic handler.
+    pub fn new(info: &PanicInfo) -> Self {
+        match info.location() {
+            Some(location) => Report::from_parts(info.message(), location.file(), location.line()),
+            None => Report::from_parts(info.message(), "", 0),
+        }
+    }
+
+    /// Create a report from a message and a location.
+    ///
+    /// Only the file name is kept from the path of the file.
+    pub fn from_parts(message: impl Display, file: &str, line: u32) -> Self {
+        let mut text = Message::new();
+        let _ = write!(Truncating(&mut text), "{}", message);
+        let mut name = Row::new();
+        let _ = Truncating(&mut name).write_str(file.rsplit(['/', '\\']).next().unwrap_or(file));
+        Report {
+            message: text,
+            file: name,
+            line,
+        }
+    }
+
+    /// Rows of the panel: the message wrapped over all rows but the last
+    /// one, which holds the file and line.
+    pub fn rows(&self) -> [Row; ROWS] {
+        let mut rows: [Row; ROWS] = Default::default();
+        let mut chars = self.message.chars();
+        for row in rows.iter_mut().take(ROWS - 1) {
+            for c in chars.by_ref().take(COLUMNS) {
+                let _ = row.push(if c == '\n' { ' ' } else { c });
+            }
+        }
+        let _ = write!(
+            Truncating(&mut rows[ROWS - 1]),
+            "{}:{}",
+            self.file,
+            self.line
+        );
+        rows
+    }
+
+    /// Show the report on the panel and flush it.
+    pub fn show<DI, SIZE>(&self, panel: &mut TextPanel<DI, SIZE>) -> Result<bool, DisplayError>
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: DisplaySize,
+    {
+        panel.page("PANIC");
+        for (i, row) in self.rows().iter().enumerate() {
+            panel.set_row(i, row);
+        }
+        panel.flush()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "panicked at {}:{}: {}",
+            self.file, self.line, self.message
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Report {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "panicked at {=str}:{=u32}: {=str}",
+            self.file.as_str(),
+            self.line,
+            self.message.as_str()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_the_message_and_keeps_the_file_name() {
+        let report = Report::from_parts(
+            "called `Result::unwrap()` on an `Err` value: I2C(Timeout)",
+            "examples/lm75-display-bp.rs",
+            48,
+        );
+        assert_eq!(
+            report.rows(),
+            [
+                "called `Result::unwra",
+                "p()` on an `Err` valu",
+                "e: I2C(Timeout)",
+                "lm75-display-bp.rs:48",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_messages_are_cut_off() {
+        let report = Report::from_parts(format_args!("{:0>100}", 1), "src\\main.rs", 7);
+        assert_eq!(report.message.len(), (ROWS - 1) * COLUMNS);
+        assert_eq!(report.rows()[ROWS - 2].len(), COLUMNS);
+        assert_eq!(report.rows()[ROWS - 1], "main.rs:7");
+    }
+
+    #[test]
+    fn prints_like_the_default_handler() {
+        let report = Report::from_parts("boom", "src/board.rs", 12);
+        let mut text = heapless::String::<64>::new();
+        write!(text, "{}", report).unwrap();
+        assert_eq!(text, "panicked at board.rs:12: boom");
+    }
+}
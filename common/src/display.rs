@@ -0,0 +1,57 @@
+//! SSD1306 OLED display as used on all boards.
+//!
+//! Code which only uses the types in this module and [`TextPanel`] compiles
+//! unchanged for every board. For example:
+//!
+//! ```
+//! use core::fmt::Write;
+//! use driver_examples_common::{Panel, WriteOnlyDataCommand};
+//!
+//! fn show_temperature<DI: WriteOnlyDataCommand>(panel: &mut Panel<DI>, celsius: f32) {
+//!     panel.page("Temperature");
+//!     write!(panel.row(0), "{:.1}ºC", celsius).unwrap();
+//!     panel.flush().unwrap();
+//! }
+//! ```
+
+use crate::TextPanel;
+#[cfg(feature = "async")]
+pub use display_interface::AsyncWriteOnlyDataCommand;
+pub use display_interface::{DisplayError, WriteOnlyDataCommand};
+use ssd1306::{mode::BufferedGraphicsMode, prelude::*, Ssd1306};
+#[cfg(feature = "async")]
+use ssd1306::{mode::BufferedGraphicsModeAsync, Ssd1306Async};
+
+/// 128x64 SSD1306 display in buffered graphics mode.
+pub type Display<DI> = Ssd1306<DI, DisplaySize128x64, BufferedGraphicsMode<DisplaySize128x64>>;
+
+/// Text panel on a [`Display`].
+pub type Panel<DI> = TextPanel<DI, DisplaySize128x64>;
+
+/// Create a display on the given interface.
+///
+/// The display is not initialized. Call `init()` on it or on the
+/// [`TextPanel`] wrapping it before using it.
+pub fn new_display<DI: WriteOnlyDataCommand>(interface: DI) -> Display<DI> {
+    Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+        .into_buffered_graphics_mode()
+}
+
+/// 128x64 SSD1306 display in async buffered graphics mode.
+#[cfg(feature = "async")]
+pub type AsyncDisplay<DI> =
+    Ssd1306Async<DI, DisplaySize128x64, BufferedGraphicsModeAsync<DisplaySize128x64>>;
+
+/// Async text panel on an [`AsyncDisplay`].
+#[cfg(feature = "async")]
+pub type AsyncPanel<DI> = crate::text_panel::AsyncTextPanel<DI, DisplaySize128x64>;
+
+/// Create an async display on the given interface.
+///
+/// The display is not initialized. Call `init().await` on it or on the
+/// [`AsyncTextPanel`](crate::AsyncTextPanel) wrapping it before using it.
+#[cfg(feature = "async")]
+pub fn new_async_display<DI: AsyncWriteOnlyDataCommand>(interface: DI) -> AsyncDisplay<DI> {
+    Ssd1306Async::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+        .into_buffered_graphics_mode()
+}
@@ -0,0 +1,83 @@
+//! Compensation of CCS811 gas readings for the ambient conditions.
+//!
+//! The CCS811 algorithm is more accurate when it knows the current
+//! temperature and humidity. Passing them to the device requires the
+//! `embedded-ccs811` feature.
+
+#[cfg(feature = "embedded-ccs811")]
+use embedded_ccs811::Ccs811AppMode;
+
+/// Decides when to measure the environment and pass it to a CCS811.
+///
+/// Temperature and humidity change slowly so it is enough to measure them
+/// every few gas readings.
+#[derive(Debug)]
+pub struct Compensation {
+    period: u32,
+    readings: u32,
+}
+
+impl Compensation {
+    /// Compensate every `period` readings. A period of 1 compensates after
+    /// every reading.
+    pub const fn new(period: u32) -> Self {
+        Compensation {
+            period,
+            readings: 0,
+        }
+    }
+
+    /// Count a reading and return whether the environment should be
+    /// measured again.
+    pub fn is_due(&mut self) -> bool {
+        self.readings += 1;
+        if self.readings >= self.period {
+            self.readings = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Count a reading and, if it is due, measure the environment and pass
+    /// it to the sensor.
+    ///
+    /// `measure` returns the temperature in ºC and the relative humidity in
+    /// percent. Returns whether the sensor was updated.
+    #[cfg(feature = "embedded-ccs811")]
+    pub fn update<S, F>(&mut self, ccs811: &mut S, measure: F) -> Result<bool, S::Error>
+    where
+        S: Ccs811AppMode,
+        F: FnOnce() -> (f32, f32),
+    {
+        if !self.is_due() {
+            return Ok(false);
+        }
+        let (temperature, humidity) = measure();
+        ccs811.set_environment(humidity, temperature)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_reading() {
+        let mut compensation = Compensation::new(1);
+        assert!(compensation.is_due());
+        assert!(compensation.is_due());
+    }
+
+    #[test]
+    fn every_eleventh_reading() {
+        let mut compensation = Compensation::new(11);
+        for _ in 0..2 {
+            for _ in 0..10 {
+                assert!(!compensation.is_due());
+            }
+            assert!(compensation.is_due());
+        }
+    }
+}
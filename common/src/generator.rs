@@ -0,0 +1,984 @@
+//! Function generator controlled with SCPI-style commands per serial.
+//!
+//! Every line is one command. The names can be written in full or
+//! shortened to their first letters, e.g. `FREQuency` or `SWEep`, and letter
+//! case does not matter:
+//!
+//! ```text
+//! FREQ 1000              Output 1 kHz, also `FREQ 1k`, `FREQ 1.5kHz` or `FREQ 2MHz`
+//! FUNC SQU               Waveform: SINusoid, TRIangle or SQUare
+//! PHAS 90                Phase in degrees
+//! AMPL 50                Amplitude in percent, set with a digital potentiometer
+//! SWEEP 100 10000 2s     Sweep from 100 Hz to 10 kHz in 2 s, over and over
+//! SWEEP 20 20k 5s LOG    Logarithmic sweep, `LIN` is the default
+//! SWEEP OFF              Go back to the frequency set with `FREQ`
+//! FREQ?                  Show a setting, also `FUNC?`, `PHAS?`...
+//! *IDN?                  Show the name of the generator
+//! ```
+//!
+//! [`CommandLine`] reads the commands, [`Generator`] keeps the settings and
+//! runs the sweeps one timer tick at a time and `Output` sets them on an
+//! AD9833 without glitches, alternating between its two frequency and two
+//! phase registers. Driving the device requires the `ad983x` feature.
+
+use crate::display::WriteOnlyDataCommand;
+use crate::synth::Waveform;
+#[cfg(feature = "ad983x")]
+use crate::synth::{frequency_word, get_opposite, phase_word};
+use crate::text_panel::TextPanel;
+#[cfg(feature = "ad983x")]
+use ad983x::{Ad983x, Error, FrequencyRegister, PhaseRegister};
+use core::fmt::{self, Write};
+#[cfg(feature = "ad983x")]
+use embedded_hal::spi::SpiDevice;
+use ssd1306::prelude::DisplaySize;
+
+/// Highest frequency which can be set: half the master clock of the usual
+/// AD9833 modules.
+pub const MAX_FREQUENCY_HZ: f64 = 12_500_000.0;
+
+/// Longest sweep: one hour.
+pub const MAX_SWEEP_MS: u32 = 3_600_000;
+
+const FREQUENCY_UNITS: [(&str, f64); 4] = [("HZ", 1.0), ("K", 1e3), ("KHZ", 1e3), ("MHZ", 1e6)];
+const TIME_UNITS_MS: [(&str, f64); 2] = [("S", 1e3), ("MS", 1.0)];
+const PERCENT: [(&str, f64); 1] = [("%", 1.0)];
+
+/// Full name of every command and the length of its short form.
+const HEADERS: [(&str, usize, Query); 6] = [
+    ("*IDN", 4, Query::Identity),
+    ("FREQUENCY", 4, Query::Frequency),
+    ("FUNCTION", 4, Query::Function),
+    ("PHASE", 4, Query::Phase),
+    ("AMPLITUDE", 4, Query::Amplitude),
+    ("SWEEP", 3, Query::Sweep),
+];
+
+/// How the frequency goes from the start to the end of a sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Spacing {
+    /// The same number of Hz every millisecond.
+    Linear,
+    /// The same number of octaves every millisecond, which sounds even.
+    Logarithmic,
+}
+
+/// Frequency sweep, repeated until another frequency is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sweep {
+    /// Frequency at the beginning in Hz.
+    pub start_hz: f64,
+    /// Frequency at the end in Hz. It can be lower than the start.
+    pub stop_hz: f64,
+    /// Duration of one sweep in ms.
+    pub duration_ms: u32,
+    /// Spacing of the frequencies.
+    pub spacing: Spacing,
+}
+
+impl Sweep {
+    /// Frequency after a fraction of the sweep, from 0.0 to 1.0.
+    pub fn frequency_at(&self, progress: f64) -> f64 {
+        match self.spacing {
+            Spacing::Linear => self.start_hz + (self.stop_hz - self.start_hz) * progress,
+            Spacing::Logarithmic => {
+                self.start_hz * libm::pow(self.stop_hz / self.start_hz, progress)
+            }
+        }
+    }
+}
+
+/// Setting asked for with a query like `FREQ?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Query {
+    /// Name of the generator, `*IDN?`.
+    Identity,
+    /// Frequency in Hz.
+    Frequency,
+    /// Waveform.
+    Function,
+    /// Phase in degrees.
+    Phase,
+    /// Amplitude in percent.
+    Amplitude,
+    /// Running sweep or `OFF`.
+    Sweep,
+}
+
+/// What a line received per serial asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Output this frequency in Hz, stopping any sweep.
+    Frequency(f64),
+    /// Change the waveform.
+    Function(Waveform),
+    /// Shift the phase by this many degrees, from 0 to 360.
+    Phase(f64),
+    /// Set the amplitude in percent.
+    Amplitude(u8),
+    /// Start a sweep.
+    Sweep(Sweep),
+    /// Stop the sweep and go back to the frequency.
+    SweepOff,
+    /// Show a setting.
+    Query(Query),
+}
+
+/// Written back in the short form of the commands, e.g. `FREQ 1500` or
+/// `SWE 100 10000 2500MS LOG`.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Frequency(hz) => {
+                write!(
+                    f,
+                    "{} {}",
+                    Query::Frequency.header(),
+                    Decimal::trimmed(*hz, 3)
+                )
+            }
+            Command::Function(waveform) => {
+                write!(f, "{} {}", Query::Function.header(), keyword(*waveform))
+            }
+            Command::Phase(degrees) => {
+                write!(
+                    f,
+                    "{} {}",
+                    Query::Phase.header(),
+                    Decimal::trimmed(*degrees, 3)
+                )
+            }
+            Command::Amplitude(percent) => write!(f, "{} {}", Query::Amplitude.header(), percent),
+            Command::Sweep(sweep) => write!(
+                f,
+                "{} {} {} {}MS {}",
+                Query::Sweep.header(),
+                Decimal::trimmed(sweep.start_hz, 3),
+                Decimal::trimmed(sweep.stop_hz, 3),
+                sweep.duration_ms,
+                spacing_keyword(sweep.spacing)
+            ),
+            Command::SweepOff => write!(f, "{} OFF", Query::Sweep.header()),
+            Command::Query(query) => write!(f, "{}?", query.header()),
+        }
+    }
+}
+
+impl Query {
+    /// Short form of the command setting or showing this.
+    fn header(&self) -> &'static str {
+        HEADERS
+            .iter()
+            .find(|(_, _, query)| query == self)
+            .map_or("", |(long, short, _)| &long[..*short])
+    }
+}
+
+/// Short form of the keyword for a waveform.
+fn keyword(waveform: Waveform) -> &'static str {
+    match waveform {
+        Waveform::Sine => "SIN",
+        Waveform::Triangle => "TRI",
+        Waveform::Square => "SQU",
+    }
+}
+
+/// Short form of the keyword for a sweep spacing.
+fn spacing_keyword(spacing: Spacing) -> &'static str {
+    match spacing {
+        Spacing::Linear => "LIN",
+        Spacing::Logarithmic => "LOG",
+    }
+}
+
+/// Error in a command line, reported with its SCPI error number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommandError {
+    /// The command does not exist.
+    UndefinedHeader,
+    /// The command needs more parameters.
+    MissingParameter,
+    /// The command has more parameters than it takes.
+    ParameterNotAllowed,
+    /// A parameter is not a number, a unit or a choice of the command.
+    IllegalValue,
+    /// A parameter is beyond what the generator can do.
+    OutOfRange,
+    /// The line does not fit in the buffer.
+    TooMuchData,
+}
+
+impl CommandError {
+    /// Error number defined by SCPI.
+    pub fn code(&self) -> i16 {
+        match self {
+            CommandError::UndefinedHeader => -113,
+            CommandError::MissingParameter => -109,
+            CommandError::ParameterNotAllowed => -108,
+            CommandError::IllegalValue => -224,
+            CommandError::OutOfRange => -222,
+            CommandError::TooMuchData => -223,
+        }
+    }
+}
+
+/// Formatted like an entry of the SCPI error queue, e.g.
+/// `-113,"Undefined header"`.
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            CommandError::UndefinedHeader => "Undefined header",
+            CommandError::MissingParameter => "Missing parameter",
+            CommandError::ParameterNotAllowed => "Parameter not allowed",
+            CommandError::IllegalValue => "Illegal parameter value",
+            CommandError::OutOfRange => "Data out of range",
+            CommandError::TooMuchData => "Too much data",
+        };
+        write!(f, "{},\"{}\"", self.code(), description)
+    }
+}
+
+/// Collects the bytes received per serial into command lines.
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    line: heapless::String<40>,
+    overflow: bool,
+}
+
+impl CommandLine {
+    /// Add a received byte. Returns the command once a line is complete.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Command, CommandError>> {
+        if byte != b'\r' && byte != b'\n' {
+            if !byte.is_ascii() || self.line.push(byte as char).is_err() {
+                self.overflow = true;
+            }
+            return None;
+        }
+        let line = core::mem::take(&mut self.line);
+        if core::mem::take(&mut self.overflow) {
+            return Some(Err(CommandError::TooMuchData));
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        Some(parse_command(line))
+    }
+}
+
+fn parse_command(line: &str) -> Result<Command, CommandError> {
+    let (header, rest) = line
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((line, ""));
+    let mut parameters = heapless::Vec::<&str, 4>::new();
+    for parameter in rest
+        .split(|c: char| c == ',' || c.is_ascii_whitespace())
+        .filter(|parameter| !parameter.is_empty())
+    {
+        parameters
+            .push(parameter)
+            .map_err(|_| CommandError::ParameterNotAllowed)?;
+    }
+    let (name, is_query) = match header.strip_suffix('?') {
+        Some(name) => (name, true),
+        None => (header, false),
+    };
+    let setting = HEADERS
+        .iter()
+        .find(|(long, short, _)| is_keyword(name, long, *short))
+        .map(|(_, _, setting)| *setting)
+        .ok_or(CommandError::UndefinedHeader)?;
+    if is_query {
+        return match parameters[..] {
+            [] => Ok(Command::Query(setting)),
+            _ => Err(CommandError::ParameterNotAllowed),
+        };
+    }
+    match (setting, &parameters[..]) {
+        (Query::Identity, _) => Err(CommandError::UndefinedHeader),
+        (_, []) => Err(CommandError::MissingParameter),
+        (Query::Frequency, [value]) => parse_frequency(value).map(Command::Frequency),
+        (Query::Function, [value]) => parse_waveform(value).map(Command::Function),
+        (Query::Phase, [value]) => {
+            let degrees = libm::fmod(parse_quantity(value, &[])?, 360.0);
+            Ok(Command::Phase(if degrees < 0.0 {
+                degrees + 360.0
+            } else {
+                degrees
+            }))
+        }
+        (Query::Amplitude, [value]) => {
+            let percent = parse_quantity(value, &PERCENT)?;
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(CommandError::OutOfRange);
+            }
+            Ok(Command::Amplitude(libm::round(percent) as u8))
+        }
+        (Query::Sweep, [value]) if is_keyword(value, "OFF", 3) => Ok(Command::SweepOff),
+        (Query::Sweep, [_] | [_, _]) => Err(CommandError::MissingParameter),
+        (Query::Sweep, [start, stop, duration]) => {
+            parse_sweep(start, stop, duration, Spacing::Linear).map(Command::Sweep)
+        }
+        (Query::Sweep, [start, stop, duration, spacing]) => {
+            let spacing = if is_keyword(spacing, "LINEAR", 3) {
+                Spacing::Linear
+            } else if is_keyword(spacing, "LOGARITHMIC", 3) {
+                Spacing::Logarithmic
+            } else {
+                return Err(CommandError::IllegalValue);
+            };
+            parse_sweep(start, stop, duration, spacing).map(Command::Sweep)
+        }
+        _ => Err(CommandError::ParameterNotAllowed),
+    }
+}
+
+/// Whether a word is the long or the short form of a keyword.
+fn is_keyword(word: &str, long: &str, short: usize) -> bool {
+    word.eq_ignore_ascii_case(long) || word.eq_ignore_ascii_case(&long[..short])
+}
+
+/// Parse a number with an optional unit, returned in the unit with factor 1.
+fn parse_quantity(text: &str, units: &[(&str, f64)]) -> Result<f64, CommandError> {
+    // Units do not start with an `e`, which is the exponent of the number.
+    let split = text
+        .find(|c: char| (c.is_ascii_alphabetic() && !matches!(c, 'e' | 'E')) || c == '%')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let factor = if unit.is_empty() {
+        1.0
+    } else {
+        units
+            .iter()
+            .find(|(name, _)| unit.eq_ignore_ascii_case(name))
+            .map(|(_, factor)| *factor)
+            .ok_or(CommandError::IllegalValue)?
+    };
+    match parse_number(number) {
+        Some(value) if value.is_finite() => Ok(value * factor),
+        _ => Err(CommandError::IllegalValue),
+    }
+}
+
+/// Parse a decimal number like `-1.5` or `20e3`.
+///
+/// `str::parse` brings along tables which alone take a sixth of the flash
+/// of a Blue Pill, and the commands never need its exact rounding.
+fn parse_number(text: &str) -> Option<f64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let mut value = 0.0;
+    for digit in integer.bytes().chain(fraction.bytes()) {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10.0 + f64::from(digit - b'0');
+    }
+    // The command line is short, so the fraction has only a few digits.
+    let exponent = exponent.saturating_sub(fraction.len() as i32);
+    let scale = libm::pow(10.0, f64::from(exponent.unsigned_abs()));
+    let value = if exponent < 0 {
+        value / scale
+    } else {
+        value * scale
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn parse_frequency(text: &str) -> Result<f64, CommandError> {
+    let frequency = parse_quantity(text, &FREQUENCY_UNITS)?;
+    if (0.0..=MAX_FREQUENCY_HZ).contains(&frequency) {
+        Ok(frequency)
+    } else {
+        Err(CommandError::OutOfRange)
+    }
+}
+
+fn parse_waveform(text: &str) -> Result<Waveform, CommandError> {
+    if is_keyword(text, "SINUSOID", 3) {
+        Ok(Waveform::Sine)
+    } else if is_keyword(text, "TRIANGLE", 3) {
+        Ok(Waveform::Triangle)
+    } else if is_keyword(text, "SQUARE", 3) {
+        Ok(Waveform::Square)
+    } else {
+        Err(CommandError::IllegalValue)
+    }
+}
+
+fn parse_sweep(
+    start: &str,
+    stop: &str,
+    duration: &str,
+    spacing: Spacing,
+) -> Result<Sweep, CommandError> {
+    let start_hz = parse_frequency(start)?;
+    let stop_hz = parse_frequency(stop)?;
+    // Seconds unless given in ms.
+    let duration_ms = parse_quantity(duration, &TIME_UNITS_MS)?;
+    let duration_ms = if duration.ends_with(|c: char| c.is_ascii_alphabetic()) {
+        duration_ms
+    } else {
+        duration_ms * 1e3
+    };
+    if !(1.0..=f64::from(MAX_SWEEP_MS)).contains(&duration_ms)
+        || (spacing == Spacing::Logarithmic && (start_hz == 0.0 || stop_hz == 0.0))
+    {
+        return Err(CommandError::OutOfRange);
+    }
+    Ok(Sweep {
+        start_hz,
+        stop_hz,
+        duration_ms: duration_ms as u32,
+        spacing,
+    })
+}
+
+/// Everything the generator outputs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Settings {
+    /// Frequency in Hz when not sweeping.
+    pub frequency_hz: f64,
+    /// Waveform.
+    pub waveform: Waveform,
+    /// Phase in degrees, from 0 to 360.
+    pub phase_deg: f64,
+    /// Amplitude in percent.
+    pub amplitude_percent: u8,
+    /// Running sweep.
+    pub sweep: Option<Sweep>,
+}
+
+impl Default for Settings {
+    /// A 1 kHz sine wave at full amplitude.
+    fn default() -> Self {
+        Settings {
+            frequency_hz: 1_000.0,
+            waveform: Waveform::Sine,
+            phase_deg: 0.0,
+            amplitude_percent: 100,
+            sweep: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Write the answer to a query, without the line end.
+    pub fn answer(&self, query: Query, out: &mut impl Write) -> fmt::Result {
+        match query {
+            Query::Identity => write!(
+                out,
+                "driver-examples,AD9833 function generator,0,{}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            Query::Frequency => write!(out, "{}", Decimal::trimmed(self.frequency_hz, 3)),
+            Query::Function => out.write_str(keyword(self.waveform)),
+            Query::Phase => write!(out, "{}", Decimal::trimmed(self.phase_deg, 3)),
+            Query::Amplitude => write!(out, "{}", self.amplitude_percent),
+            Query::Sweep => match self.sweep {
+                Some(sweep) => write!(
+                    out,
+                    "{},{},{},{}",
+                    Decimal::trimmed(sweep.start_hz, 3),
+                    Decimal::trimmed(sweep.stop_hz, 3),
+                    Decimal::trimmed(f64::from(sweep.duration_ms) / 1e3, 3),
+                    spacing_keyword(sweep.spacing)
+                ),
+                None => out.write_str("OFF"),
+            },
+        }
+    }
+
+    /// Write the settings on a page of the panel. They are shown on the
+    /// next flush.
+    pub fn show<DI, SIZE>(&self, panel: &mut TextPanel<DI, SIZE>)
+    where
+        DI: WriteOnlyDataCommand,
+        SIZE: DisplaySize,
+    {
+        let waveform = match self.waveform {
+            Waveform::Sine => "Sine",
+            Waveform::Triangle => "Triangle",
+            Waveform::Square => "Square",
+        };
+        panel.page("Function generator");
+        let _ = write!(
+            panel.row(0),
+            "{:<8} {:>3}% {:>4}deg",
+            waveform,
+            self.amplitude_percent,
+            libm::round(self.phase_deg) as u16
+        );
+        match self.sweep {
+            Some(sweep) => {
+                let _ = write!(panel.row(1), "From {}", Hertz(sweep.start_hz));
+                let _ = write!(panel.row(2), "To {}", Hertz(sweep.stop_hz));
+                let spacing = match sweep.spacing {
+                    Spacing::Linear => "Lin",
+                    Spacing::Logarithmic => "Log",
+                };
+                let _ = write!(panel.row(3), "{} sweep {} ms", spacing, sweep.duration_ms);
+            }
+            None => {
+                let _ = write!(panel.row(1), "{}", Hertz(self.frequency_hz));
+            }
+        }
+    }
+}
+
+/// Frequency shown with the unit which suits it best.
+struct Hertz(f64);
+
+impl fmt::Display for Hertz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 >= 1e6 {
+            write!(f, "{} MHz", Decimal::new(self.0 / 1e6, 3))
+        } else if self.0 >= 1e3 {
+            write!(f, "{} kHz", Decimal::new(self.0 / 1e3, 3))
+        } else {
+            write!(f, "{} Hz", Decimal::new(self.0, 1))
+        }
+    }
+}
+
+/// Non-negative number written with a fixed number of decimals.
+///
+/// The `core` float formatting takes around 10 kB of flash, which does not
+/// fit on the Blue Pill together with the rest of the generator.
+struct Decimal {
+    value: f64,
+    decimals: u32,
+    trim: bool,
+}
+
+impl Decimal {
+    /// Write all the decimals, like `{:.decimals$}`.
+    fn new(value: f64, decimals: u32) -> Self {
+        Decimal {
+            value,
+            decimals,
+            trim: false,
+        }
+    }
+
+    /// Leave out trailing zeros in the decimals and the point if there are
+    /// no decimals left.
+    fn trimmed(value: f64, decimals: u32) -> Self {
+        Decimal {
+            value,
+            decimals,
+            trim: true,
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10_u64.pow(self.decimals);
+        let scaled = libm::round(self.value * scale as f64) as u64;
+        write!(f, "{}", scaled / scale)?;
+        let mut fraction = scaled % scale;
+        let mut decimals = self.decimals;
+        while self.trim && decimals > 0 && fraction.is_multiple_of(10) {
+            fraction /= 10;
+            decimals -= 1;
+        }
+        if decimals > 0 {
+            write!(f, ".{:0width$}", fraction, width = decimals as usize)?;
+        }
+        Ok(())
+    }
+}
+
+/// Position of a 256 step digital potentiometer, used as voltage divider
+/// at the output, for an amplitude in percent.
+pub fn amplitude_position(percent: u8) -> u8 {
+    (u16::from(percent.min(100)) * 255 / 100) as u8
+}
+
+/// Keeps the settings and runs the sweeps one timer tick at a time, so
+/// that the main loop can read the serial port in between.
+#[derive(Debug)]
+pub struct Generator {
+    settings: Settings,
+    tick_hz: u32,
+    sweep_ticks: u32,
+    elapsed: u32,
+    changed: bool,
+}
+
+impl Generator {
+    /// Create a generator with the given settings for a timer running at
+    /// `tick_hz`.
+    pub fn new(settings: Settings, tick_hz: u32) -> Self {
+        let mut generator = Generator {
+            settings,
+            tick_hz,
+            sweep_ticks: 1,
+            elapsed: 0,
+            changed: true,
+        };
+        if let Some(sweep) = settings.sweep {
+            generator.start(sweep);
+        }
+        generator
+    }
+
+    /// Current settings.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Change the settings. Queries do nothing.
+    ///
+    /// A new frequency or sweep takes effect on the next tick. The waveform,
+    /// phase and amplitude need to be set on the devices.
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Frequency(frequency_hz) => {
+                self.settings.frequency_hz = frequency_hz;
+                self.settings.sweep = None;
+                self.changed = true;
+            }
+            Command::Function(waveform) => self.settings.waveform = waveform,
+            Command::Phase(degrees) => self.settings.phase_deg = degrees,
+            Command::Amplitude(percent) => self.settings.amplitude_percent = percent,
+            Command::Sweep(sweep) => self.start(sweep),
+            Command::SweepOff => {
+                self.changed |= self.settings.sweep.take().is_some();
+            }
+            Command::Query(_) => {}
+        }
+    }
+
+    /// Advance one tick. Returns the frequency to output when it changes,
+    /// which is on every tick while sweeping.
+    pub fn tick(&mut self) -> Option<f64> {
+        match self.settings.sweep {
+            Some(sweep) => {
+                // The last tick of a sweep outputs the stop frequency.
+                let last = self.sweep_ticks.saturating_sub(1).max(1);
+                let progress = f64::from(self.elapsed) / f64::from(last);
+                self.elapsed = (self.elapsed + 1) % self.sweep_ticks;
+                Some(sweep.frequency_at(progress))
+            }
+            None => core::mem::take(&mut self.changed).then_some(self.settings.frequency_hz),
+        }
+    }
+
+    fn start(&mut self, sweep: Sweep) {
+        let ticks = u64::from(sweep.duration_ms) * u64::from(self.tick_hz) / 1_000;
+        self.sweep_ticks = ticks.clamp(1, u64::from(u32::MAX)) as u32;
+        self.elapsed = 0;
+        self.settings.sweep = Some(sweep);
+    }
+}
+
+/// Sets the frequency and the phase of an AD9833 without glitches.
+///
+/// A new value is written to the register which is not in use and then
+/// the output is switched to it.
+#[cfg(feature = "ad983x")]
+#[derive(Debug)]
+pub struct Output {
+    frequency: FrequencyRegister,
+    phase: PhaseRegister,
+}
+
+#[cfg(feature = "ad983x")]
+impl Default for Output {
+    fn default() -> Self {
+        Output {
+            frequency: FrequencyRegister::F0,
+            phase: PhaseRegister::P0,
+        }
+    }
+}
+
+#[cfg(feature = "ad983x")]
+impl Output {
+    /// Output a frequency in Hz.
+    pub fn set_frequency<SPI, IC, E>(
+        &mut self,
+        synth: &mut Ad983x<SPI, IC>,
+        frequency_hz: f64,
+    ) -> Result<(), Error<E>>
+    where
+        SPI: SpiDevice<u8, Error = E>,
+    {
+        let opposite = get_opposite(self.frequency);
+        synth.set_frequency(opposite, frequency_word(frequency_hz))?;
+        synth.select_frequency(opposite)?;
+        self.frequency = opposite;
+        Ok(())
+    }
+
+    /// Shift the phase of the output by some degrees, from 0 to 360.
+    pub fn set_phase<SPI, IC, E>(
+        &mut self,
+        synth: &mut Ad983x<SPI, IC>,
+        degrees: f64,
+    ) -> Result<(), Error<E>>
+    where
+        SPI: SpiDevice<u8, Error = E>,
+    {
+        let opposite = match self.phase {
+            PhaseRegister::P0 => PhaseRegister::P1,
+            PhaseRegister::P1 => PhaseRegister::P0,
+        };
+        synth.set_phase(opposite, phase_word(degrees))?;
+        synth.select_phase(opposite)?;
+        self.phase = opposite;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_lines(text: &[u8]) -> heapless::Vec<Result<Command, CommandError>, 16> {
+        let mut line = CommandLine::default();
+        let mut commands = heapless::Vec::new();
+        for byte in text {
+            if let Some(command) = line.push(*byte) {
+                commands.push(command).unwrap();
+            }
+        }
+        commands
+    }
+
+    #[test]
+    fn parses_commands() {
+        let commands = parse_lines(
+            b"FREQ 1000\r\nfreq 1.5kHz\nFREQUENCY 2MHZ\nFREQ 1e3\nfunc squ\nFUNCTION TRIANGLE\n\
+              PHAS 90\nPHAS -90\nAMPL 50\nampl 25%\nSWEEP OFF\nFREQ?\n*idn?\n",
+        );
+        assert_eq!(
+            &[
+                Ok(Command::Frequency(1_000.0)),
+                Ok(Command::Frequency(1_500.0)),
+                Ok(Command::Frequency(2_000_000.0)),
+                Ok(Command::Frequency(1_000.0)),
+                Ok(Command::Function(Waveform::Square)),
+                Ok(Command::Function(Waveform::Triangle)),
+                Ok(Command::Phase(90.0)),
+                Ok(Command::Phase(270.0)),
+                Ok(Command::Amplitude(50)),
+                Ok(Command::Amplitude(25)),
+                Ok(Command::SweepOff),
+                Ok(Command::Query(Query::Frequency)),
+                Ok(Command::Query(Query::Identity)),
+            ],
+            &commands[..]
+        );
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(Some(1_000.0), parse_number("1000"));
+        assert_eq!(Some(-1.5), parse_number("-1.5"));
+        assert_eq!(Some(0.25), parse_number("+.25"));
+        assert_eq!(Some(20_000.0), parse_number("20e3"));
+        assert_eq!(Some(12.0), parse_number("1.2E1"));
+        assert_eq!(Some(0.05), parse_number("5e-2"));
+        for invalid in ["", "-", ".", "e3", "1e", "1.2.3", "1,5", "--1"] {
+            assert_eq!(None, parse_number(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_sweeps() {
+        let commands = parse_lines(b"SWEEP 100 10000 2s\nswe 20,20k,500ms,log\nSWEEP 1k 100 3\n");
+        assert_eq!(
+            &[
+                Ok(Command::Sweep(Sweep {
+                    start_hz: 100.0,
+                    stop_hz: 10_000.0,
+                    duration_ms: 2_000,
+                    spacing: Spacing::Linear,
+                })),
+                Ok(Command::Sweep(Sweep {
+                    start_hz: 20.0,
+                    stop_hz: 20_000.0,
+                    duration_ms: 500,
+                    spacing: Spacing::Logarithmic,
+                })),
+                Ok(Command::Sweep(Sweep {
+                    start_hz: 1_000.0,
+                    stop_hz: 100.0,
+                    duration_ms: 3_000,
+                    spacing: Spacing::Linear,
+                })),
+            ],
+            &commands[..]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        let commands = parse_lines(
+            b"VOLT 1\nFREQ\nFREQ 1 2\nFREQ abc\nFREQ 1 GHz\nFREQ 13MHz\nFUNC SAW\nAMPL 120\n\
+              SWEEP 100 1k\nSWEEP 0 1k 1s LOG\nSWEEP 1 2 3 SQRT\nFREQ? 1\n*IDN\n",
+        );
+        assert_eq!(
+            &[
+                Err(CommandError::UndefinedHeader),
+                Err(CommandError::MissingParameter),
+                Err(CommandError::ParameterNotAllowed),
+                Err(CommandError::IllegalValue),
+                Err(CommandError::ParameterNotAllowed),
+                Err(CommandError::OutOfRange),
+                Err(CommandError::IllegalValue),
+                Err(CommandError::OutOfRange),
+                Err(CommandError::MissingParameter),
+                Err(CommandError::OutOfRange),
+                Err(CommandError::IllegalValue),
+                Err(CommandError::ParameterNotAllowed),
+                Err(CommandError::UndefinedHeader),
+            ],
+            &commands[..]
+        );
+        let commands = parse_lines(b"SWEEP 100 10000 2s LOG and some more text\nFREQ 1\n");
+        assert_eq!(
+            &[Err(CommandError::TooMuchData), Ok(Command::Frequency(1.0))],
+            &commands[..]
+        );
+    }
+
+    #[test]
+    fn shows_errors_as_scpi() {
+        let mut text = heapless::String::<32>::new();
+        write!(text, "{}", CommandError::UndefinedHeader).unwrap();
+        assert_eq!("-113,\"Undefined header\"", text);
+    }
+
+    #[test]
+    fn answers_queries() {
+        let mut settings = Settings::default();
+        let answer = |settings: &Settings, query| {
+            let mut text = heapless::String::<64>::new();
+            settings.answer(query, &mut text).unwrap();
+            text
+        };
+        assert_eq!("1000", answer(&settings, Query::Frequency));
+        assert_eq!("SIN", answer(&settings, Query::Function));
+        assert_eq!("100", answer(&settings, Query::Amplitude));
+        assert_eq!("OFF", answer(&settings, Query::Sweep));
+        settings.sweep = Some(Sweep {
+            start_hz: 100.0,
+            stop_hz: 10_000.0,
+            duration_ms: 2_500,
+            spacing: Spacing::Logarithmic,
+        });
+        assert_eq!("100,10000,2.5,LOG", answer(&settings, Query::Sweep));
+        assert!(answer(&settings, Query::Identity).starts_with("driver-examples,AD9833"));
+    }
+
+    #[test]
+    fn shows_commands_in_short_form() {
+        let commands = parse_lines(
+            b"FREQ 1.5kHz
+FUNC TRIANGLE
+PHAS -90
+SWEEP 20 20k 5s LOG
+FREQ?
+",
+        );
+        let mut text = heapless::String::<128>::new();
+        for command in commands {
+            write!(text, "{};", command.unwrap()).unwrap();
+        }
+        assert_eq!(
+            "FREQ 1500;FUNC TRI;PHAS 270;SWE 20 20000 5000MS LOG;FREQ?;",
+            text
+        );
+    }
+
+    #[test]
+    fn writes_decimals_without_float_formatting() {
+        let mut text = heapless::String::<64>::new();
+        write!(
+            text,
+            "{} {} {} {}",
+            Hertz(12_500_000.0),
+            Hertz(1_234.567_8),
+            Hertz(0.25),
+            Decimal::trimmed(2.5, 3)
+        )
+        .unwrap();
+        assert_eq!("12.500 MHz 1.235 kHz 0.3 Hz 2.5", text);
+    }
+
+    #[test]
+    fn sweeps_linearly_and_logarithmically() {
+        let mut sweep = Sweep {
+            start_hz: 100.0,
+            stop_hz: 10_000.0,
+            duration_ms: 2_000,
+            spacing: Spacing::Linear,
+        };
+        assert_eq!(100.0, sweep.frequency_at(0.0));
+        assert_eq!(5_050.0, sweep.frequency_at(0.5));
+        sweep.spacing = Spacing::Logarithmic;
+        assert!((sweep.frequency_at(0.5) - 1_000.0).abs() < 1e-6);
+        assert!((sweep.frequency_at(1.0) - 10_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reports_frequency_changes() {
+        let mut generator = Generator::new(Settings::default(), 1_000);
+        assert_eq!(Some(1_000.0), generator.tick());
+        assert_eq!(None, generator.tick());
+        generator.apply(Command::Amplitude(50));
+        assert_eq!(None, generator.tick());
+
+        generator.apply(Command::Sweep(Sweep {
+            start_hz: 100.0,
+            stop_hz: 400.0,
+            duration_ms: 4,
+            spacing: Spacing::Linear,
+        }));
+        let frequencies: [_; 5] = core::array::from_fn(|_| generator.tick());
+        assert_eq!(
+            [
+                Some(100.0),
+                Some(200.0),
+                Some(300.0),
+                Some(400.0),
+                Some(100.0)
+            ],
+            frequencies
+        );
+
+        generator.apply(Command::SweepOff);
+        assert_eq!(Some(1_000.0), generator.tick());
+        assert_eq!(None, generator.tick());
+        generator.apply(Command::Frequency(440.0));
+        assert_eq!(Some(440.0), generator.tick());
+        assert_eq!(50, generator.settings().amplitude_percent);
+    }
+
+    #[test]
+    fn scales_amplitude_to_wiper_position() {
+        assert_eq!(0, amplitude_position(0));
+        assert_eq!(127, amplitude_position(50));
+        assert_eq!(255, amplitude_position(100));
+    }
+}
@@ -0,0 +1,217 @@
+//! Heartbeat LED which shows the status of the program.
+//!
+//! Instead of toggling the LED in the main loop, which delays the
+//! measurements, a timer interrupt calls [`Heartbeat::tick`] at
+//! [`TICK_HZ`] and sets the LED to what it returns. The main loop feeds the
+//! heartbeat with its [`Status`] on every iteration, like a watchdog. If it
+//! stops doing so, e.g. because a transfer hangs, the LED stops blinking.
+//!
+//! ```
+//! # use driver_examples_common::heartbeat::{Heartbeat, Status, TICK_HZ};
+//! # use driver_examples_common::reading::Reading;
+//! # fn example(reading: Reading<f32>) {
+//! let mut heartbeat = Heartbeat::new(2 * TICK_HZ);
+//! // In the main loop:
+//! heartbeat.feed(Status::of(&reading));
+//! // In the timer interrupt:
+//! let led_on = heartbeat.tick();
+//! # }
+//! ```
+
+use crate::reading::{Failure, Reading};
+
+/// Rate at which [`Heartbeat::tick`] must be called.
+pub const TICK_HZ: u32 = 10;
+
+/// Number of ticks after which the patterns repeat (4 s).
+pub const PATTERN_TICKS: u32 = 4 * TICK_HZ;
+
+/// S, O and S in Morse code with one tick per unit: three short blinks,
+/// three long blinks and three short blinks.
+const SOS: u64 = 0b10101 | 0b111_0111_0111 << 8 | 0b10101 << 22;
+
+/// Variants of the driver errors which mean that the bus failed, rather
+/// than the sensor.
+const BUS_ERROR_VARIANTS: [&str; 5] = ["I2C", "I2c", "SPI", "Spi", "Comm"];
+
+/// Status shown by the heartbeat LED, from best to worst.
+///
+/// Combine the status of several readings with `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Status {
+    /// Everything works. The LED blinks once per second.
+    Healthy,
+    /// A sensor reported an error. The LED blinks twice every two seconds.
+    SensorError,
+    /// A transfer on the bus failed. The LED blinks three times every two
+    /// seconds.
+    BusError,
+    /// The program panicked. The LED blinks SOS and the heartbeat does not
+    /// need to be fed anymore.
+    ///
+    /// Only panic handlers which drive the LED themselves can show it, like
+    /// the one of the `panic-display` feature of the board crates. With the
+    /// default panic handlers, the LED stays as it was when panicking.
+    Panic,
+}
+
+impl Status {
+    /// Status for a reading done through a [`Monitor`](crate::Monitor).
+    pub fn of<T>(reading: &Reading<T>) -> Self {
+        match reading {
+            Ok(_) => Status::Healthy,
+            Err(failure) => Status::of_failure(failure),
+        }
+    }
+
+    /// Status for a failed reading. Errors of the bus are told apart from
+    /// other errors by the name of the driver error variant.
+    pub fn of_failure(failure: &Failure) -> Self {
+        if BUS_ERROR_VARIANTS.contains(&failure.variant()) {
+            Status::BusError
+        } else {
+            Status::SensorError
+        }
+    }
+
+    /// Ticks of a pattern period during which the LED is lit, one bit per
+    /// tick starting at the least significant bit.
+    pub fn pattern(self) -> u64 {
+        match self {
+            Status::Healthy => repeat(1, TICK_HZ),
+            Status::SensorError => repeat(0b101, 2 * TICK_HZ),
+            Status::BusError => repeat(0b10101, 2 * TICK_HZ),
+            Status::Panic => SOS,
+        }
+    }
+}
+
+/// Repeat a pattern lasting `period` ticks over a whole pattern period.
+fn repeat(pattern: u64, period: u32) -> u64 {
+    (0..PATTERN_TICKS / period).fold(0, |repeated, i| repeated | pattern << (i * period))
+}
+
+/// State of the heartbeat LED.
+#[derive(Debug)]
+pub struct Heartbeat {
+    status: Status,
+    tick: u32,
+    since_fed: u32,
+    timeout: u32,
+}
+
+impl Heartbeat {
+    /// Create a heartbeat which stops if it is not fed within `timeout`
+    /// ticks. It starts as healthy and just fed.
+    pub const fn new(timeout: u32) -> Self {
+        Heartbeat {
+            status: Status::Healthy,
+            tick: 0,
+            since_fed: 0,
+            timeout,
+        }
+    }
+
+    /// Current status.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Whether the heartbeat has not been fed in time.
+    ///
+    /// A heartbeat showing a panic never starves.
+    pub fn is_starved(&self) -> bool {
+        self.status != Status::Panic && self.since_fed > self.timeout
+    }
+
+    /// Tell the heartbeat that the program is alive and its status.
+    ///
+    /// Once the status is [`Status::Panic`] it does not change anymore.
+    pub fn feed(&mut self, status: Status) {
+        if self.status != Status::Panic {
+            self.status = status;
+        }
+        self.since_fed = 0;
+    }
+
+    /// Advance by one tick and return whether the LED should be lit.
+    pub fn tick(&mut self) -> bool {
+        let tick = self.tick;
+        self.tick = (self.tick + 1) % PATTERN_TICKS;
+        self.since_fed = self.since_fed.saturating_add(1);
+        !self.is_starved() && self.status.pattern() & (1 << tick) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(heartbeat: &mut Heartbeat, status: Status) -> u64 {
+        (0..PATTERN_TICKS).fold(0, |lit, tick| {
+            heartbeat.feed(status);
+            lit | u64::from(heartbeat.tick()) << tick
+        })
+    }
+
+    #[test]
+    fn blinks_the_pattern_of_the_status() {
+        let mut heartbeat = Heartbeat::new(TICK_HZ);
+        for status in [Status::Healthy, Status::SensorError, Status::BusError] {
+            assert_eq!(period(&mut heartbeat, status), status.pattern());
+        }
+    }
+
+    #[test]
+    fn patterns_fit_in_a_period() {
+        for status in [
+            Status::Healthy,
+            Status::SensorError,
+            Status::BusError,
+            Status::Panic,
+        ] {
+            assert_eq!(status.pattern() >> PATTERN_TICKS, 0);
+        }
+    }
+
+    #[test]
+    fn stops_when_not_fed() {
+        let mut heartbeat = Heartbeat::new(3);
+        assert!((0..3).any(|_| heartbeat.tick()));
+        assert!(!heartbeat.is_starved());
+        for _ in 0..2 * PATTERN_TICKS {
+            assert!(!heartbeat.tick());
+        }
+        assert!(heartbeat.is_starved());
+
+        heartbeat.feed(Status::Healthy);
+        assert!(!heartbeat.is_starved());
+    }
+
+    #[test]
+    fn panic_is_final_and_never_starves() {
+        let mut heartbeat = Heartbeat::new(1);
+        heartbeat.feed(Status::Panic);
+        heartbeat.feed(Status::Healthy);
+        assert_eq!(heartbeat.status(), Status::Panic);
+        let lit = (0..10 * PATTERN_TICKS).filter(|_| heartbeat.tick()).count();
+        assert_eq!(lit, 10 * SOS.count_ones() as usize);
+    }
+
+    #[test]
+    fn tells_bus_errors_apart() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        enum Error {
+            I2C(()),
+            InvalidInputData,
+        }
+        let bus = Failure::new("LM75", &Error::I2C(()), 1);
+        assert_eq!(Status::of_failure(&bus), Status::BusError);
+        let sensor = Failure::new("LM75", &Error::InvalidInputData, 1);
+        assert_eq!(Status::of_failure(&sensor), Status::SensorError);
+        assert_eq!(Status::of(&Ok::<_, Failure>(())), Status::Healthy);
+        assert_eq!(Status::BusError.max(Status::SensorError), Status::BusError);
+    }
+}
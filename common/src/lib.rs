@@ -0,0 +1,36 @@
+//! Code shared by the examples for all boards.
+//!
+//! This crate does not depend on any particular board or HAL so that
+//! anything built on top of it works the same everywhere.
+#![no_std]
+
+pub mod adc;
+pub mod bus;
+pub use crate::bus::{I2cBus, I2cDevice, SpiBus, SpiDevice};
+pub mod clock;
+pub mod compat;
+pub use crate::compat::{Compat, InputCompat};
+pub mod crash;
+pub mod display;
+#[cfg(feature = "async")]
+pub use crate::display::{new_async_display, AsyncDisplay, AsyncPanel, AsyncWriteOnlyDataCommand};
+pub use crate::display::{new_display, Display, DisplayError, Panel, WriteOnlyDataCommand};
+pub mod environment;
+pub mod generator;
+pub mod heartbeat;
+pub mod log;
+pub mod midi;
+pub mod mux;
+pub mod pwm;
+pub mod reading;
+pub mod rtttl;
+pub mod scan;
+pub mod sensor;
+pub use crate::reading::{Failure, Monitor, Reading};
+pub mod synth;
+pub mod telemetry;
+pub mod text_panel;
+#[cfg(feature = "async")]
+pub use crate::text_panel::AsyncTextPanel;
+pub use crate::text_panel::TextPanel;
+pub mod watchdog;
@@ -0,0 +1,38 @@
+//! Helpers to log values either as formatted text or with `defmt`.
+//!
+//! The board crates log through macros which pass the same format string
+//! to `rtt-target` or to `defmt`, depending on their `defmt` feature. The
+//! types of this crate implement `defmt::Format` when the `defmt` feature
+//! is enabled. Values which only implement `Debug`, like most driver errors,
+//! can be wrapped in [`Dbg`] and logged with `{}`:
+//!
+//! ```
+//! # use driver_examples_common::log::Dbg;
+//! let error: Result<(), _> = Err("Timeout");
+//! assert_eq!(format!("{}", Dbg(&error)), "Err(\"Timeout\")");
+//! ```
+
+use core::fmt::{self, Debug};
+
+/// Logs the `Debug` representation of the value.
+#[derive(Clone, Copy)]
+pub struct Dbg<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Debug + ?Sized> fmt::Display for Dbg<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl<T: Debug + ?Sized> Debug for Dbg<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T: Debug + ?Sized> defmt::Format for Dbg<'_, T> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{}", defmt::Debug2Format(self.0))
+    }
+}
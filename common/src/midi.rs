@@ -0,0 +1,846 @@
+//! Standard MIDI File (SMF) parser and melody extraction.
+//!
+//! [`Smf::parse`] reads format 0 and 1 files straight from memory, e.g. from
+//! `include_bytes!`, without copying them. Files stored in a flash memory
+//! like the W25 are read into a buffer with [`load`].
+//!
+//! A [`Melody`] walks one track of the file and returns when the note to play
+//! changes, honoring the tempo changes of the file. When several notes sound
+//! at once, the highest one is played, which is usually the melody:
+//!
+//! ```
+//! # use driver_examples_common::midi::{Melody, Smf};
+//! # let song = driver_examples_common::synth::ODE_TO_JOY;
+//! let smf = Smf::parse(song).unwrap();
+//! // Track 0 of format 1 files usually only has the tempo.
+//! for step in Melody::new(&smf, 1, 1_000).unwrap() {
+//!     // Wait `step.ticks` ticks of a 1 kHz timer, then play `step.note`.
+//! #   let _ = step;
+//! }
+//! ```
+//!
+//! Instruments with several voices play a [`Performance`] instead, which
+//! returns every note on and off event with the same timing.
+
+use core::fmt;
+
+/// Tempo until the first tempo change: 120 beats per minute.
+pub const DEFAULT_TEMPO_US: u32 = 500_000;
+
+/// Error parsing a MIDI file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MidiError {
+    /// The data does not start with an `MThd` header.
+    NotSmf,
+    /// Format 2 files, which contain independent sequences, are not
+    /// supported.
+    UnsupportedFormat(u16),
+    /// The file ends in the middle of a chunk or an event.
+    Truncated,
+    /// An event is malformed, e.g. data without a status byte.
+    InvalidEvent,
+    /// There is no track with this index.
+    NoTrack(usize),
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiError::NotSmf => f.write_str("not a MIDI file"),
+            MidiError::UnsupportedFormat(format) => write!(f, "unsupported format {}", format),
+            MidiError::Truncated => f.write_str("truncated file"),
+            MidiError::InvalidEvent => f.write_str("invalid event"),
+            MidiError::NoTrack(index) => write!(f, "no track {}", index),
+        }
+    }
+}
+
+/// Time unit of the delta times of the events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Division {
+    /// Ticks per quarter note. The length of a quarter note is set by the
+    /// tempo.
+    TicksPerQuarter(u16),
+    /// Ticks per frame of a SMPTE time code, independent of the tempo.
+    Smpte {
+        /// Frames per second: 24, 25, 29 (for 29.97) or 30.
+        frames_per_second: u8,
+        /// Ticks per frame.
+        ticks_per_frame: u8,
+    },
+}
+
+/// Standard MIDI File of format 0 (one track) or 1 (several tracks played
+/// together).
+#[derive(Debug, Clone, Copy)]
+pub struct Smf<'a> {
+    format: u16,
+    track_count: u16,
+    division: Division,
+    chunks: &'a [u8],
+}
+
+impl<'a> Smf<'a> {
+    /// Parse a file and check all its events.
+    pub fn parse(data: &'a [u8]) -> Result<Self, MidiError> {
+        let Chunk {
+            kind,
+            contents: header,
+            rest: chunks,
+        } = chunk(data).map_err(|_| MidiError::NotSmf)?;
+        if kind != *b"MThd" || header.len() < 6 {
+            return Err(MidiError::NotSmf);
+        }
+        let format = u16::from_be_bytes([header[0], header[1]]);
+        if format > 1 {
+            return Err(MidiError::UnsupportedFormat(format));
+        }
+        let division = match [header[4], header[5]] {
+            [frames, ticks_per_frame] if frames & 0x80 != 0 => Division::Smpte {
+                frames_per_second: frames.wrapping_neg(),
+                ticks_per_frame,
+            },
+            ticks => Division::TicksPerQuarter(u16::from_be_bytes(ticks)),
+        };
+        let smf = Smf {
+            format,
+            track_count: u16::from_be_bytes([header[2], header[3]]),
+            division,
+            chunks,
+        };
+        for index in 0..usize::from(smf.track_count) {
+            let mut events = Events::new(smf.track_data(index)?);
+            while events.next_event()?.is_some() {}
+        }
+        Ok(smf)
+    }
+
+    /// Format of the file, 0 or 1.
+    pub fn format(&self) -> u16 {
+        self.format
+    }
+
+    /// Number of tracks.
+    pub fn track_count(&self) -> usize {
+        usize::from(self.track_count)
+    }
+
+    /// Time unit of the events.
+    pub fn division(&self) -> Division {
+        self.division
+    }
+
+    /// Events of a track.
+    pub fn track(&self, index: usize) -> Result<Events<'a>, MidiError> {
+        if index >= self.track_count() {
+            return Err(MidiError::NoTrack(index));
+        }
+        self.track_data(index).map(Events::new)
+    }
+
+    fn track_data(&self, index: usize) -> Result<&'a [u8], MidiError> {
+        // Chunks of other types than `MTrk` must be skipped.
+        let mut rest = self.chunks;
+        let mut found = 0;
+        while !rest.is_empty() {
+            let chunk = chunk(rest)?;
+            if chunk.kind == *b"MTrk" {
+                if found == index {
+                    return Ok(chunk.contents);
+                }
+                found += 1;
+            }
+            rest = chunk.rest;
+        }
+        Err(MidiError::NoTrack(index))
+    }
+}
+
+/// Chunk of a MIDI file.
+struct Chunk<'a> {
+    kind: [u8; 4],
+    contents: &'a [u8],
+    /// Data after the chunk.
+    rest: &'a [u8],
+}
+
+/// Split a chunk off the data.
+fn chunk(data: &[u8]) -> Result<Chunk<'_>, MidiError> {
+    let header = data.get(..8).ok_or(MidiError::Truncated)?;
+    let kind = [header[0], header[1], header[2], header[3]];
+    let len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let rest = &data[8..];
+    if rest.len() < len {
+        return Err(MidiError::Truncated);
+    }
+    let (contents, rest) = rest.split_at(len);
+    Ok(Chunk {
+        kind,
+        contents,
+        rest,
+    })
+}
+
+/// What happens in an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A key is pressed.
+    NoteOn {
+        /// MIDI channel, 0-15.
+        channel: u8,
+        /// Note number, 69 is A4 (440 Hz).
+        key: u8,
+        /// How hard the key is pressed, 1-127.
+        velocity: u8,
+    },
+    /// A key is released. Note on events with velocity 0 are returned as
+    /// note off.
+    NoteOff {
+        /// MIDI channel, 0-15.
+        channel: u8,
+        /// Note number.
+        key: u8,
+    },
+    /// New tempo in microseconds per quarter note.
+    Tempo(u32),
+    /// End of the track.
+    EndOfTrack,
+    /// Any other event, e.g. a controller change or a track name.
+    Other,
+}
+
+/// Event of a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// Ticks since the previous event of the track.
+    pub delta: u32,
+    /// What happens.
+    pub kind: EventKind,
+}
+
+/// Iterator over the events of a track.
+///
+/// The tracks are checked when the file is parsed, so it simply ends at the
+/// end of the track.
+#[derive(Debug, Clone)]
+pub struct Events<'a> {
+    data: &'a [u8],
+    running_status: Option<u8>,
+}
+
+impl<'a> Events<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Events {
+            data,
+            running_status: None,
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, MidiError> {
+        let (&byte, rest) = self.data.split_first().ok_or(MidiError::Truncated)?;
+        self.data = rest;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], MidiError> {
+        if self.data.len() < len {
+            return Err(MidiError::Truncated);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// Variable-length quantity of up to 4 bytes.
+    fn variable(&mut self) -> Result<u32, MidiError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.byte()?;
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(MidiError::InvalidEvent)
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>, MidiError> {
+        if self.data.is_empty() {
+            return Ok(None);
+        }
+        let delta = self.variable()?;
+        let mut status = self.byte()?;
+        let mut first = None;
+        if status < 0x80 {
+            // Running status: the byte is data of the previous status.
+            first = Some(status);
+            status = self.running_status.ok_or(MidiError::InvalidEvent)?;
+        }
+        let kind = match status {
+            0xFF => {
+                let kind = self.byte()?;
+                let len = self.variable()? as usize;
+                let data = self.bytes(len)?;
+                match (kind, data) {
+                    (0x51, &[a, b, c]) => EventKind::Tempo(u32::from_be_bytes([0, a, b, c])),
+                    (0x2F, _) => EventKind::EndOfTrack,
+                    _ => EventKind::Other,
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len = self.variable()? as usize;
+                self.bytes(len)?;
+                self.running_status = None;
+                EventKind::Other
+            }
+            0xF1..=0xFE => return Err(MidiError::InvalidEvent),
+            _ => {
+                self.running_status = Some(status);
+                let first = match first {
+                    Some(byte) => byte,
+                    None => self.byte()?,
+                };
+                // Program changes and channel pressure have a single data byte.
+                let second = match status & 0xF0 {
+                    0xC0 | 0xD0 => 0,
+                    _ => self.byte()?,
+                };
+                if first > 0x7F || second > 0x7F {
+                    return Err(MidiError::InvalidEvent);
+                }
+                let channel = status & 0x0F;
+                match (status & 0xF0, second) {
+                    (0x90, 0) | (0x80, _) => EventKind::NoteOff {
+                        channel,
+                        key: first,
+                    },
+                    (0x90, velocity) => EventKind::NoteOn {
+                        channel,
+                        key: first,
+                        velocity,
+                    },
+                    _ => EventKind::Other,
+                }
+            }
+        };
+        if kind == EventKind::EndOfTrack {
+            self.data = &[];
+        }
+        Ok(Some(Event { delta, kind }))
+    }
+}
+
+impl Iterator for Events<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().ok().flatten()
+    }
+}
+
+/// Change of the note to play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// Timer ticks to wait since the previous step.
+    pub ticks: u32,
+    /// Note to play from then on, or `None` for silence.
+    pub note: Option<u8>,
+}
+
+/// Events of a track with their absolute time.
+#[derive(Debug)]
+struct Timeline<'a> {
+    events: Events<'a>,
+    next: Option<(u64, EventKind)>,
+}
+
+impl<'a> Timeline<'a> {
+    fn new(mut events: Events<'a>) -> Self {
+        let next = events
+            .next()
+            .map(|event| (u64::from(event.delta), event.kind));
+        Timeline { events, next }
+    }
+
+    fn pop(&mut self) -> Option<(u64, EventKind)> {
+        let current = self.next.take()?;
+        self.next = self
+            .events
+            .next()
+            .map(|event| (current.0 + u64::from(event.delta), event.kind));
+        Some(current)
+    }
+}
+
+/// Events of a track in order, together with the tempo changes, and the
+/// time elapsed in ticks of a timer.
+#[derive(Debug)]
+struct Sequencer<'a> {
+    tempo_track: Option<Timeline<'a>>,
+    notes: Timeline<'a>,
+    /// Microseconds per tick are `tempo / ticks_per_unit`.
+    ticks_per_unit: u64,
+    smpte: bool,
+    tempo: u64,
+    tick_hz: u64,
+    tick: u64,
+    /// Elapsed time in units of `1 / ticks_per_unit` microseconds.
+    time: u64,
+    emitted: u64,
+}
+
+impl<'a> Sequencer<'a> {
+    fn new(smf: &Smf<'a>, track: usize, tick_hz: u32) -> Result<Self, MidiError> {
+        let notes = Timeline::new(smf.track(track)?);
+        let tempo_track = if smf.format() == 1 && track != 0 {
+            Some(Timeline::new(smf.track(0)?))
+        } else {
+            None
+        };
+        let (ticks_per_unit, smpte) = match smf.division() {
+            Division::TicksPerQuarter(ticks) => (u64::from(ticks.max(1)), false),
+            Division::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            } => (
+                u64::from(frames_per_second) * u64::from(ticks_per_frame).max(1),
+                true,
+            ),
+        };
+        Ok(Sequencer {
+            tempo_track,
+            notes,
+            ticks_per_unit,
+            smpte,
+            tempo: u64::from(DEFAULT_TEMPO_US),
+            tick_hz: u64::from(tick_hz),
+            tick: 0,
+            time: 0,
+            emitted: 0,
+        })
+    }
+
+    /// Next event of either track, tempo changes first. The time advances
+    /// to the event and tempo changes are applied.
+    fn pop(&mut self) -> Option<(u64, EventKind)> {
+        let notes_at = self.notes.next.map(|(tick, _)| tick);
+        let (tick, kind) = match &mut self.tempo_track {
+            Some(tempo) if tempo.next.is_some_and(|(tick, _)| Some(tick) <= notes_at) => {
+                match tempo.pop()? {
+                    (tick, EventKind::Tempo(us)) => (tick, EventKind::Tempo(us)),
+                    (tick, _) => (tick, EventKind::Other),
+                }
+            }
+            _ => self.notes.pop()?,
+        };
+        let per_tick = if self.smpte { 1_000_000 } else { self.tempo };
+        self.time += (tick - self.tick) * per_tick;
+        self.tick = tick;
+        if let EventKind::Tempo(us) = kind {
+            self.tempo = u64::from(us);
+        }
+        Some((tick, kind))
+    }
+
+    fn next_tick(&self) -> Option<u64> {
+        let tempo = self.tempo_track.as_ref().and_then(|track| track.next);
+        [tempo, self.notes.next]
+            .into_iter()
+            .flatten()
+            .map(|(tick, _)| tick)
+            .min()
+    }
+
+    /// Timer ticks elapsed since the previous call.
+    fn elapsed(&mut self) -> u32 {
+        let microseconds = self.time / self.ticks_per_unit;
+        let total = microseconds * self.tick_hz / 1_000_000;
+        let ticks = (total - self.emitted) as u32;
+        self.emitted = total;
+        ticks
+    }
+}
+
+/// Monophonic melody of a track, timed for a timer ticking at a fixed rate.
+///
+/// It returns a [`Step`] whenever the highest note held in the track
+/// changes, and a last step with silence at the end of the track. The tempo
+/// changes are taken from the track itself and, in format 1 files, from the
+/// first track, which holds them by convention.
+#[derive(Debug)]
+pub struct Melody<'a> {
+    sequencer: Sequencer<'a>,
+    held: u128,
+    playing: Option<u8>,
+    ended: bool,
+}
+
+impl<'a> Melody<'a> {
+    /// Play the track with the given index, with steps measured in ticks of
+    /// a timer running at `tick_hz`.
+    pub fn new(smf: &Smf<'a>, track: usize, tick_hz: u32) -> Result<Self, MidiError> {
+        Ok(Melody {
+            sequencer: Sequencer::new(smf, track, tick_hz)?,
+            held: 0,
+            playing: None,
+            ended: false,
+        })
+    }
+
+    fn step(&mut self, note: Option<u8>) -> Step {
+        Step {
+            ticks: self.sequencer.elapsed(),
+            note,
+        }
+    }
+}
+
+impl Iterator for Melody<'_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ended {
+            return None;
+        }
+        while let Some((tick, kind)) = self.sequencer.pop() {
+            match kind {
+                EventKind::NoteOn { key, .. } => self.held |= 1 << key,
+                EventKind::NoteOff { key, .. } => self.held &= !(1 << key),
+                _ => {}
+            }
+            // Apply all the events at the same time before choosing the note.
+            if self.sequencer.next_tick() == Some(tick) {
+                continue;
+            }
+            let highest = (self.held != 0).then(|| 127 - self.held.leading_zeros() as u8);
+            if highest != self.playing {
+                self.playing = highest;
+                return Some(self.step(highest));
+            }
+        }
+        self.ended = true;
+        // Wait until the end of the track unless it ends with the last note.
+        let last = self.step(None);
+        (last.ticks > 0 || self.playing.take().is_some()).then_some(last)
+    }
+}
+
+/// Note pressed or released, timed like a [`Step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cue {
+    /// Timer ticks to wait since the previous cue.
+    pub ticks: u32,
+    /// [`EventKind::NoteOn`] or [`EventKind::NoteOff`], or
+    /// [`EventKind::EndOfTrack`] for the last cue.
+    pub kind: EventKind,
+}
+
+/// Every note of a track, for instruments which play several notes at once.
+///
+/// Like a [`Melody`], it follows the tempo changes, but it returns a [`Cue`]
+/// for every note on and off event with its velocity and channel. If the
+/// track goes on after its last note, a last cue with
+/// [`EventKind::EndOfTrack`] waits until its end.
+#[derive(Debug)]
+pub struct Performance<'a> {
+    sequencer: Sequencer<'a>,
+    ended: bool,
+}
+
+impl<'a> Performance<'a> {
+    /// Play the track with the given index, with cues measured in ticks of
+    /// a timer running at `tick_hz`.
+    pub fn new(smf: &Smf<'a>, track: usize, tick_hz: u32) -> Result<Self, MidiError> {
+        Ok(Performance {
+            sequencer: Sequencer::new(smf, track, tick_hz)?,
+            ended: false,
+        })
+    }
+}
+
+impl Iterator for Performance<'_> {
+    type Item = Cue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ended {
+            return None;
+        }
+        while let Some((_, kind)) = self.sequencer.pop() {
+            if let EventKind::NoteOn { .. } | EventKind::NoteOff { .. } = kind {
+                let ticks = self.sequencer.elapsed();
+                return Some(Cue { ticks, kind });
+            }
+        }
+        self.ended = true;
+        let ticks = self.sequencer.elapsed();
+        (ticks > 0).then_some(Cue {
+            ticks,
+            kind: EventKind::EndOfTrack,
+        })
+    }
+}
+
+/// Error loading a MIDI file from a memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError<E> {
+    /// Reading the memory failed.
+    Read(E),
+    /// The file does not fit in the buffer.
+    TooLong,
+    /// The file is not valid.
+    Midi(MidiError),
+}
+
+impl<E> From<MidiError> for LoadError<E> {
+    fn from(error: MidiError) -> Self {
+        LoadError::Midi(error)
+    }
+}
+
+/// Load a MIDI file stored in a memory, e.g. a W25 flash, into `buffer`
+/// and parse it.
+///
+/// `read(offset, bytes)` must fill `bytes` with the contents of the file
+/// from `offset` on. The length of the file is found out from its chunks,
+/// so it does not need to be stored anywhere:
+///
+/// ```ignore
+/// let mut buffer = [0; 4096];
+/// let smf = midi::load(&mut buffer, |offset, bytes| flash.read_data(ADDRESS + offset, bytes))?;
+/// ```
+pub fn load<'b, E>(
+    buffer: &'b mut [u8],
+    mut read: impl FnMut(u32, &mut [u8]) -> Result<(), E>,
+) -> Result<Smf<'b>, LoadError<E>> {
+    let mut len = 0;
+    let mut tracks = None;
+    let mut found = 0;
+    while tracks != Some(found) {
+        let header = buffer.get_mut(len..len + 8).ok_or(LoadError::TooLong)?;
+        read(len as u32, header).map_err(LoadError::Read)?;
+        let kind = [header[0], header[1], header[2], header[3]];
+        let chunk_len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        match (tracks, &kind) {
+            (None, b"MThd") => {}
+            (None, _) => return Err(MidiError::NotSmf.into()),
+            (Some(_), b"MTrk") => found += 1,
+            (Some(_), _) => {}
+        }
+        let start = len + 8;
+        let contents = buffer
+            .get_mut(start..start + chunk_len)
+            .ok_or(LoadError::TooLong)?;
+        read(start as u32, contents).map_err(LoadError::Read)?;
+        if tracks.is_none() {
+            let count = contents.get(2..4).ok_or(MidiError::NotSmf)?;
+            tracks = Some(u16::from_be_bytes([count[0], count[1]]));
+        }
+        len = start + chunk_len;
+    }
+    Ok(Smf::parse(&buffer[..len])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synth::{ODE_TO_JOY, ODE_TO_JOY_DUET};
+
+    /// Format 0 file at 96 ticks per quarter note with a C major chord, a
+    /// tempo change and a melody written with running status.
+    const CHORDS: &[u8] = &[
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0, 96, //
+        b'M', b'T', b'r', b'k', 0, 0, 0, 37, //
+        0x00, 0x90, 60, 100, // C4 on
+        0x00, 64, 100, // E4 on, running status
+        0x00, 67, 100, // G4 on
+        0x60, 67, 0, // G4 off after a quarter note (500 ms)
+        0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 60 BPM
+        0x60, 0x80, 64, 64, // E4 off after a quarter note (1 s)
+        0x30, 0xF0, 0x02, 0x01, 0xF7, // SysEx after an eighth note
+        0x00, 0x80, 60, 0, // C4 off, the SysEx cancels the running status
+        0x00, 0xFF, 0x2F, 0x00,
+    ];
+
+    fn steps(data: &[u8], track: usize) -> heapless::Vec<Step, 128> {
+        let smf = Smf::parse(data).unwrap();
+        Melody::new(&smf, track, 1_000).unwrap().collect()
+    }
+
+    #[test]
+    fn reads_header() {
+        let smf = Smf::parse(ODE_TO_JOY).unwrap();
+        assert_eq!(1, smf.format());
+        assert_eq!(2, smf.track_count());
+        assert_eq!(Division::TicksPerQuarter(480), smf.division());
+        assert_eq!(Err(MidiError::NoTrack(2)), smf.track(2).map(|_| ()));
+    }
+
+    #[test]
+    fn reads_events() {
+        let smf = Smf::parse(ODE_TO_JOY).unwrap();
+        let mut tempo = smf.track(0).unwrap().map(|event| event.kind);
+        assert_eq!(Some(EventKind::Other), tempo.next());
+        assert_eq!(Some(EventKind::Other), tempo.next());
+        assert_eq!(Some(EventKind::Tempo(500_000)), tempo.next());
+        assert_eq!(Some(EventKind::EndOfTrack), tempo.next());
+        assert_eq!(None, tempo.next());
+        let first_note = smf.track(1).unwrap().nth(2).unwrap();
+        assert_eq!(
+            Event {
+                delta: 0,
+                kind: EventKind::NoteOn {
+                    channel: 0,
+                    key: 76,
+                    velocity: 100
+                }
+            },
+            first_note
+        );
+    }
+
+    #[test]
+    fn plays_highest_note_with_tempo_changes() {
+        let expected = [
+            Step {
+                ticks: 0,
+                note: Some(67),
+            },
+            Step {
+                ticks: 500,
+                note: Some(64),
+            },
+            Step {
+                ticks: 1_000,
+                note: Some(60),
+            },
+            Step {
+                ticks: 500,
+                note: None,
+            },
+        ];
+        assert_eq!(&expected, &steps(CHORDS, 0)[..]);
+    }
+
+    #[test]
+    fn performs_every_note() {
+        let smf = Smf::parse(CHORDS).unwrap();
+        let cues: heapless::Vec<Cue, 8> = Performance::new(&smf, 0, 1_000).unwrap().collect();
+        let on = |ticks, key| Cue {
+            ticks,
+            kind: EventKind::NoteOn {
+                channel: 0,
+                key,
+                velocity: 100,
+            },
+        };
+        let off = |ticks, key| Cue {
+            ticks,
+            kind: EventKind::NoteOff { channel: 0, key },
+        };
+        assert_eq!(
+            &[
+                on(0, 60),
+                on(0, 64),
+                on(0, 67),
+                off(500, 67),
+                off(1_000, 64),
+                off(500, 60)
+            ],
+            &cues[..]
+        );
+    }
+
+    #[test]
+    fn performs_both_voices_of_duet() {
+        let smf = Smf::parse(ODE_TO_JOY_DUET).unwrap();
+        let mut notes = [0; 2];
+        let mut total = 0;
+        for cue in Performance::new(&smf, 1, 1_000).unwrap() {
+            total += cue.ticks;
+            if let EventKind::NoteOn { channel, .. } = cue.kind {
+                notes[usize::from(channel)] += 1;
+            }
+        }
+        assert_eq!([62, 32], notes);
+        assert_eq!(32_900, total);
+    }
+
+    #[test]
+    fn takes_tempo_from_first_track() {
+        // 96 ticks per quarter at the 120 BPM of track 0 is 100 ms.
+        let steps = steps(ODE_TO_JOY, 1);
+        let first: [(u32, Option<u8>); 4] =
+            core::array::from_fn(|i| (steps[i].ticks, steps[i].note));
+        assert_eq!(
+            [(0, Some(76)), (400, None), (100, Some(76)), (400, None)],
+            first
+        );
+        // As long as the 329 steps of 100 ms of the old note table.
+        let total: u32 = steps.iter().map(|step| step.ticks).sum();
+        assert_eq!(32_900, total);
+        assert_eq!(None, steps.last().unwrap().note);
+    }
+
+    #[test]
+    fn smpte_division_ignores_tempo() {
+        let mut data = heapless::Vec::<u8, 64>::from_slice(CHORDS).unwrap();
+        // 25 frames per second and 40 ticks per frame: 1 ms per tick.
+        data[12] = (-25i8) as u8;
+        data[13] = 40;
+        let ticks: heapless::Vec<u32, 4> = steps(&data, 0).iter().map(|step| step.ticks).collect();
+        assert_eq!(&[0, 96, 96, 48], &ticks[..]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(Err(MidiError::NotSmf), Smf::parse(b"RIFF").map(|_| ()));
+        let mut format_2 = heapless::Vec::<u8, 64>::from_slice(CHORDS).unwrap();
+        format_2[9] = 2;
+        assert_eq!(
+            Err(MidiError::UnsupportedFormat(2)),
+            Smf::parse(&format_2).map(|_| ())
+        );
+        assert_eq!(
+            Err(MidiError::Truncated),
+            Smf::parse(&CHORDS[..CHORDS.len() - 1]).map(|_| ())
+        );
+        let mut no_status = heapless::Vec::<u8, 64>::from_slice(CHORDS).unwrap();
+        no_status[23] = 60;
+        assert_eq!(
+            Err(MidiError::InvalidEvent),
+            Smf::parse(&no_status).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn loads_file_from_memory() {
+        let mut memory = [0xFF; 1024];
+        memory[..ODE_TO_JOY.len()].copy_from_slice(ODE_TO_JOY);
+        let read = |offset: u32, bytes: &mut [u8]| -> Result<(), ()> {
+            let start = offset as usize;
+            bytes.copy_from_slice(memory.get(start..start + bytes.len()).ok_or(())?);
+            Ok(())
+        };
+
+        let mut buffer = [0; 1024];
+        let smf = load(&mut buffer, read).unwrap();
+        assert_eq!(2, smf.track_count());
+        // The rest of the buffer is untouched, so nothing else was read.
+        let (file, rest) = buffer.split_at(ODE_TO_JOY.len());
+        assert_eq!(ODE_TO_JOY, file);
+        assert!(rest.iter().all(|byte| *byte == 0));
+
+        let mut small = [0; 64];
+        assert_eq!(Err(LoadError::TooLong), load(&mut small, read).map(|_| ()));
+        let mut buffer = [0; 1024];
+        assert_eq!(
+            Err(LoadError::Midi(MidiError::NotSmf)),
+            load(&mut buffer, |_, bytes: &mut [u8]| -> Result<(), ()> {
+                bytes.fill(0xFF);
+                Ok(())
+            })
+            .map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Devices behind an I2C multiplexer like the TCA9548A.
+//!
+//! A multiplexer makes it possible to use several devices with the same
+//! address, e.g. one VEML6040 per channel. [`Channels`] scans every
+//! channel, lets the caller build a driver for the device found on it and
+//! keeps the drivers indexed by channel:
+//!
+//! ```
+//! # use driver_examples_common::mux::Channels;
+//! # fn example<I2C: embedded_hal::i2c::I2c>(channels: [I2C; 8], upstream: &[u8]) {
+//! let mut sensors = Channels::bind(channels, upstream, |_channel, i2c, found| {
+//!     (found.address == 0x10).then(|| i2c)
+//! });
+//! while let Some((channel, sensor)) = sensors.poll() {
+//!     // read the sensor on the channel
+//! #   break;
+//! }
+//! # }
+//! ```
+//!
+//! The devices on the upstream bus, like the multiplexer itself or the
+//! display, answer on every channel as well. Their addresses are given to
+//! [`Channels::bind`] so that they are not taken for a device on the
+//! channel. Scan the upstream bus with all channels disabled to get them.
+
+use crate::scan::{scan, Found};
+use embedded_hal::i2c::I2c;
+
+/// Number of channels of the TCA9548A and PCA9548A.
+pub const CHANNELS: usize = 8;
+
+/// Driver for the device on each channel of a multiplexer, if any.
+#[derive(Debug)]
+pub struct Channels<D, const N: usize = CHANNELS> {
+    devices: [Option<D>; N],
+    next: usize,
+}
+
+impl<D, const N: usize> Channels<D, N> {
+    /// Scan every channel and build a driver for the device found on it.
+    ///
+    /// `bind` gets the channel number, the channel I2C and the first
+    /// device found on the channel whose address is not in `upstream`. It
+    /// returns the driver or `None` if the device is not supported.
+    /// Channels without devices are left empty.
+    pub fn bind<I2C, F>(channels: [I2C; N], upstream: &[u8], mut bind: F) -> Self
+    where
+        I2C: I2c,
+        F: FnMut(usize, I2C, Found) -> Option<D>,
+    {
+        let mut channel = 0;
+        let devices = channels.map(|mut i2c| {
+            let index = channel;
+            channel += 1;
+            let found = scan(&mut i2c).find(|found| !upstream.contains(&found.address))?;
+            bind(index, i2c, found)
+        });
+        Channels { devices, next: 0 }
+    }
+
+    /// Number of channels with a driver.
+    pub fn count(&self) -> usize {
+        self.devices
+            .iter()
+            .filter(|device| device.is_some())
+            .count()
+    }
+
+    /// Driver on the channel.
+    pub fn get(&self, channel: usize) -> Option<&D> {
+        self.devices.get(channel)?.as_ref()
+    }
+
+    /// Driver on the channel.
+    pub fn get_mut(&mut self, channel: usize) -> Option<&mut D> {
+        self.devices.get_mut(channel)?.as_mut()
+    }
+
+    /// Channels with a driver and their drivers, in channel order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &D)> {
+        self.devices
+            .iter()
+            .enumerate()
+            .filter_map(|(channel, device)| Some((channel, device.as_ref()?)))
+    }
+
+    /// Next channel with a driver, round-robin.
+    ///
+    /// Returns `None` only if no channel has a driver.
+    pub fn poll(&mut self) -> Option<(usize, &mut D)> {
+        let channel = (0..N)
+            .map(|offset| (self.next + offset) % N)
+            .find(|&channel| self.devices[channel].is_some())?;
+        self.next = (channel + 1) % N;
+        Some((channel, self.devices[channel].as_mut()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+    /// Channel where the devices at the given addresses answer.
+    struct Channel(&'static [u8]);
+
+    impl ErrorType for Channel {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Channel {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            if self.0.contains(&address) {
+                Ok(())
+            } else {
+                Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+            }
+        }
+    }
+
+    const UPSTREAM: &[u8] = &[0x3C, 0x70];
+
+    fn channels() -> Channels<u8, 4> {
+        let channels = [
+            Channel(&[0x10, 0x3C, 0x70]),
+            Channel(&[0x3C, 0x70]),
+            Channel(&[0x3C, 0x48, 0x70]),
+            Channel(&[0x10, 0x3C, 0x70]),
+        ];
+        Channels::bind(channels, UPSTREAM, |_, _, found| Some(found.address))
+    }
+
+    #[test]
+    fn binds_devices_not_upstream() {
+        let channels = channels();
+        assert_eq!(3, channels.count());
+        assert_eq!(Some(&0x10), channels.get(0));
+        assert_eq!(None, channels.get(1));
+        assert_eq!(Some(&0x48), channels.get(2));
+        assert_eq!(None, channels.get(4));
+        let bound: [_; 3] = core::array::from_fn(|i| channels.iter().nth(i).unwrap());
+        assert_eq!([(0, &0x10), (2, &0x48), (3, &0x10)], bound);
+    }
+
+    #[test]
+    fn skips_unsupported_devices() {
+        let channels: Channels<(), 2> = Channels::bind(
+            [Channel(&[0x10]), Channel(&[0x48])],
+            &[],
+            |channel, _, found| (found.address == 0x48).then(|| assert_eq!(1, channel)),
+        );
+        assert_eq!(1, channels.count());
+        assert!(channels.get(0).is_none());
+    }
+
+    #[test]
+    fn polls_round_robin() {
+        let mut channels = channels();
+        let polled: [_; 4] = core::array::from_fn(|_| channels.poll().unwrap().0);
+        assert_eq!([0, 2, 3, 0], polled);
+    }
+
+    #[test]
+    fn polls_nothing_without_devices() {
+        let mut channels: Channels<u8, 2> =
+            Channels::bind([Channel(&[0x70]), Channel(&[0x70])], &[0x70], |_, _, _| {
+                Some(0)
+            });
+        assert_eq!(0, channels.count());
+        assert!(channels.poll().is_none());
+    }
+}
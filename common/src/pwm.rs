@@ -0,0 +1,184 @@
+//! Animations for a PCA9685 PWM controller driving RGB LEDs and servos.
+
+/// Number of channels of a PCA9685.
+pub const CHANNEL_COUNT: usize = 16;
+
+/// RGB rainbow generator.
+///
+/// Each item is the red, green and blue intensity in the range [0-4080].
+#[derive(Debug)]
+pub struct Rainbow {
+    hue: u16,
+}
+
+impl Rainbow {
+    /// Maximum intensity of a color component.
+    pub const MAX: u16 = 4080;
+
+    /// Create a rainbow starting after the given hue in degrees.
+    pub fn new(hue: u16) -> Self {
+        Rainbow { hue }
+    }
+}
+
+impl Iterator for Rainbow {
+    type Item = (u16, u16, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // See HSV to RGB conversion: https://en.wikipedia.org/wiki/HSL_and_HSV
+        // To avoid floating point calculations and ensure smooth transitions
+        // the value range is limited to [0-4080] as 4080 = 60*68.
+        self.hue = (self.hue + 1) % 361;
+        match self.hue {
+            0..=59 => Some((4080, self.hue * 68, 0)),
+            60..=119 => Some(((120 - self.hue) * 68, 4080, 0)),
+            120..=179 => Some((0, 4080, (self.hue - 120) * 68)),
+            180..=239 => Some((0, (240 - self.hue) * 68, 4080)),
+            240..=299 => Some(((self.hue - 240) * 68, 0, 4080)),
+            300..=360 => Some((4080, 0, (360 - self.hue) * 68)),
+            _ => None,
+        }
+    }
+}
+
+/// Servo sweeping back and forth between [`Servo::MIN`] and [`Servo::MAX`].
+///
+/// Each item is the pulse length for the next step.
+#[derive(Debug)]
+pub struct Servo {
+    current: u16,
+    factor: i16,
+}
+
+impl Servo {
+    // You need to tweak these min/max values for your servos as these may vary.
+    // Be careful when doing this. Incorrect values can permanently damage your servos.
+    /// Minimum pulse length.
+    pub const MIN: u16 = 132;
+    /// Maximum pulse length.
+    pub const MAX: u16 = 608;
+
+    /// Create a servo starting at the given pulse length.
+    pub fn new(offset: u16) -> Self {
+        Servo {
+            current: offset,
+            factor: 2,
+        }
+    }
+}
+
+impl Iterator for Servo {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current >= Self::MAX {
+            self.factor = -2;
+        } else if self.current <= Self::MIN {
+            self.factor = 2;
+        }
+        self.current = ((self.current as i16) + self.factor) as u16;
+        Some(self.current)
+    }
+}
+
+/// Two RGB LEDs showing a rainbow and five servos moving simultaneously.
+///
+/// The first LED is connected to channels 0-2 and the second one, which has
+/// a common anode, to channels 3-5. The servos are connected to channels
+/// 10-14.
+#[derive(Debug)]
+pub struct RainbowServos {
+    rainbow: Rainbow,
+    servos: [Servo; 5],
+    values: [u16; CHANNEL_COUNT],
+}
+
+impl Default for RainbowServos {
+    fn default() -> Self {
+        RainbowServos {
+            rainbow: Rainbow::new(0),
+            servos: [
+                Servo::new(Servo::MIN),
+                Servo::new(Servo::MIN + 40),
+                Servo::new(Servo::MIN + 80),
+                Servo::new(Servo::MIN + 120),
+                Servo::new(Servo::MIN + 160),
+            ],
+            values: [0; CHANNEL_COUNT],
+        }
+    }
+}
+
+impl RainbowServos {
+    /// Advance one step and return the "off" values for all channels.
+    ///
+    /// All channels are meant to be switched on at 0.
+    pub fn next_values(&mut self) -> &[u16; CHANNEL_COUNT] {
+        if let Some((r, g, b)) = self.rainbow.next() {
+            const MAX: u16 = Rainbow::MAX >> 5;
+            let (r, g, b) = (r >> 5, g >> 5, b >> 5); // make LEDs less bright
+            self.values[0] = r;
+            self.values[1] = g;
+            self.values[2] = b;
+            self.values[3] = MAX - r;
+            self.values[4] = MAX - g;
+            self.values[5] = MAX - b;
+        }
+        for (i, servo) in self.servos.iter_mut().enumerate() {
+            if let Some(v) = servo.next() {
+                self.values[i + 10] = v;
+            }
+        }
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rainbow_goes_around_the_color_wheel() {
+        let mut rainbow = Rainbow::new(0);
+        assert_eq!(Some((4080, 68, 0)), rainbow.next());
+        let mut rainbow = Rainbow::new(119);
+        assert_eq!(Some((0, 4080, 0)), rainbow.next());
+        let mut rainbow = Rainbow::new(239);
+        assert_eq!(Some((0, 0, 4080)), rainbow.next());
+        let mut rainbow = Rainbow::new(360);
+        assert_eq!(Some((4080, 0, 0)), rainbow.next());
+    }
+
+    #[test]
+    fn rainbow_stays_in_range() {
+        for (r, g, b) in Rainbow::new(0).take(720) {
+            assert!(r <= Rainbow::MAX && g <= Rainbow::MAX && b <= Rainbow::MAX);
+        }
+    }
+
+    #[test]
+    fn servo_turns_around_at_the_limits() {
+        let mut servo = Servo::new(Servo::MAX - 2);
+        assert_eq!(Some(Servo::MAX), servo.next());
+        assert_eq!(Some(Servo::MAX - 2), servo.next());
+        let mut servo = Servo::new(Servo::MIN);
+        assert_eq!(Some(Servo::MIN + 2), servo.next());
+    }
+
+    #[test]
+    fn servo_stays_in_range() {
+        for v in Servo::new(Servo::MIN + 40).take(1000) {
+            assert!((Servo::MIN..=Servo::MAX).contains(&v));
+        }
+    }
+
+    #[test]
+    fn first_values() {
+        let mut animation = RainbowServos::default();
+        let values = animation.next_values();
+        assert_eq!(
+            &[127, 2, 0, 0, 125, 127, 0, 0, 0, 0, 134, 174, 214, 254, 294, 0],
+            values
+        );
+    }
+}
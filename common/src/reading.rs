@@ -0,0 +1,338 @@
+//! Sensor readings which can fail.
+//!
+//! Instead of showing a made-up value when a sensor cannot be read, read it
+//! through a [`Monitor`]. It retries a few times with increasing delays and
+//! returns a [`Reading`] which tells which device failed and why, so that
+//! the failure can be shown with
+//! [`TextPanel::show_reading`](crate::TextPanel::show_reading).
+//!
+//! ```
+//! # use driver_examples_common::reading::Monitor;
+//! # use embedded_hal::delay::DelayNs;
+//! # fn example(delay: &mut impl DelayNs, read_temperature: impl FnMut() -> Result<f32, ()>) {
+//! let mut monitor = Monitor::new("LM75");
+//! match monitor.read(delay, read_temperature) {
+//!     Ok(temperature) => { /* show the temperature */ }
+//!     Err(failure) => { /* show the failure */ }
+//! }
+//! # }
+//! ```
+
+use core::fmt::{self, Debug, Write};
+use embedded_hal::delay::DelayNs;
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+
+/// Result of reading a sensor through a [`Monitor`].
+pub type Reading<T> = Result<T, Failure>;
+
+/// Text of a driver error. Longer errors are cut off.
+pub type ErrorText = heapless::String<48>;
+
+/// Description of a failed reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// Name of the device which could not be read.
+    pub device: &'static str,
+    /// Debug representation of the driver error.
+    pub error: ErrorText,
+    /// Number of consecutive failed readings, including this one.
+    pub consecutive: u32,
+}
+
+impl Failure {
+    /// Create a failure from a driver error.
+    pub fn new<E: Debug>(device: &'static str, error: &E, consecutive: u32) -> Self {
+        let mut text = ErrorText::new();
+        let _ = write!(Truncating(&mut text), "{:?}", error);
+        Failure {
+            device,
+            error: text,
+            consecutive,
+        }
+    }
+
+    /// Name of the driver error variant, e.g. `I2C` for `I2C(Timeout)`.
+    pub fn variant(&self) -> &str {
+        let end = self.error.find(['(', '{', ' ']).unwrap_or(self.error.len());
+        &self.error[..end]
+    }
+
+    /// Contents of the driver error variant, e.g. `Timeout` for
+    /// `I2C(Timeout)`. Empty for variants without contents.
+    pub fn details(&self) -> &str {
+        let rest = self.error[self.variant().len()..].trim();
+        rest.strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(rest)
+    }
+}
+
+/// Shows e.g. `LM75: I2C(Timeout) (3 in a row)`.
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} ({} in a row)",
+            self.device, self.error, self.consecutive
+        )
+    }
+}
+
+/// Same as the `Display` implementation.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Failure {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "{=str}: {=str} ({=u32} in a row)",
+            self.device,
+            self.error.as_str(),
+            self.consecutive
+        )
+    }
+}
+
+/// Reads a sensor, retrying failed attempts with an increasing delay, and
+/// counts the consecutive failed readings.
+#[derive(Debug)]
+pub struct Monitor {
+    device: &'static str,
+    attempts: u32,
+    backoff_ms: u32,
+    consecutive_failures: u32,
+}
+
+impl Monitor {
+    /// Number of attempts for each reading by default.
+    pub const DEFAULT_ATTEMPTS: u32 = 3;
+    /// Delay before the first retry by default. It doubles with every retry.
+    pub const DEFAULT_BACKOFF_MS: u32 = 10;
+
+    /// Create a monitor for the device with the given name.
+    pub const fn new(device: &'static str) -> Self {
+        Self::with_retries(device, Self::DEFAULT_ATTEMPTS, Self::DEFAULT_BACKOFF_MS)
+    }
+
+    /// Create a monitor which tries every reading up to `attempts` times
+    /// and waits `backoff_ms` before the first retry.
+    pub const fn with_retries(device: &'static str, attempts: u32, backoff_ms: u32) -> Self {
+        Monitor {
+            device,
+            attempts: if attempts == 0 { 1 } else { attempts },
+            backoff_ms,
+            consecutive_failures: 0,
+        }
+    }
+
+    /// Name of the device.
+    pub fn device(&self) -> &'static str {
+        self.device
+    }
+
+    /// Number of consecutive failed readings so far.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Read the sensor with `read`, retrying if it fails.
+    ///
+    /// The error of the last attempt is returned if all of them fail.
+    pub fn read<T, E, D, F>(&mut self, delay: &mut D, mut read: F) -> Reading<T>
+    where
+        E: Debug,
+        D: DelayNs,
+        F: FnMut() -> Result<T, E>,
+    {
+        let mut wait_ms = self.backoff_ms;
+        let mut attempt = 1;
+        loop {
+            if let Some(reading) = self.attempt(read(), attempt) {
+                return reading;
+            }
+            delay.delay_ms(wait_ms);
+            wait_ms = wait_ms.saturating_mul(2);
+            attempt += 1;
+        }
+    }
+
+    /// Read the sensor with the async closure `read`, retrying if it fails.
+    ///
+    /// Same as [`read`](Monitor::read), but other tasks keep running while
+    /// waiting for the sensor and before the retries:
+    ///
+    /// ```
+    /// # use driver_examples_common::reading::{Monitor, Reading};
+    /// # use embedded_hal_async::delay::DelayNs;
+    /// # async fn example(delay: &mut impl DelayNs, mut sensor: impl FnMut() -> Result<f32, ()>) -> Reading<f32> {
+    /// let mut monitor = Monitor::new("LM75");
+    /// monitor
+    ///     .read_async(delay, async || {
+    ///         // e.g. wait for a conversion here
+    ///         sensor()
+    ///     })
+    ///     .await
+    /// # }
+    /// ```
+    pub async fn read_async<T, E, D, F>(&mut self, delay: &mut D, mut read: F) -> Reading<T>
+    where
+        E: Debug,
+        D: AsyncDelayNs,
+        F: AsyncFnMut() -> Result<T, E>,
+    {
+        let mut wait_ms = self.backoff_ms;
+        let mut attempt = 1;
+        loop {
+            if let Some(reading) = self.attempt(read().await, attempt) {
+                return reading;
+            }
+            delay.delay_ms(wait_ms).await;
+            wait_ms = wait_ms.saturating_mul(2);
+            attempt += 1;
+        }
+    }
+
+    /// Count the result of an attempt. Returns the reading, or `None` if
+    /// the attempt failed and should be retried.
+    fn attempt<T, E: Debug>(&mut self, result: Result<T, E>, attempt: u32) -> Option<Reading<T>> {
+        match result {
+            Ok(value) => {
+                self.consecutive_failures = 0;
+                Some(Ok(value))
+            }
+            Err(error) if attempt >= self.attempts => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                Some(Err(Failure::new(
+                    self.device,
+                    &error,
+                    self.consecutive_failures,
+                )))
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+/// Writer which discards what does not fit instead of failing.
+pub(crate) struct Truncating<'a, const N: usize>(pub(crate) &'a mut heapless::String<N>);
+
+impl<const N: usize> Write for Truncating<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.0.push(c).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, NoopDelay, Transaction};
+    use std::{string::String, vec};
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum Error {
+        I2C(String),
+        InvalidInputData,
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let mut monitor = Monitor::new("LM75");
+        let mut delay = NoopDelay::new();
+        assert!(monitor
+            .read(&mut delay, || Err::<f32, _>(Error::InvalidInputData))
+            .is_err());
+        assert_eq!(1, monitor.consecutive_failures());
+        assert_eq!(Ok(21.5), monitor.read(&mut delay, || Ok::<_, Error>(21.5)));
+        assert_eq!(0, monitor.consecutive_failures());
+    }
+
+    #[test]
+    fn retries_with_backoff() {
+        let mut monitor = Monitor::new("LM75");
+        let mut delay = CheckedDelay::new(&[Transaction::delay_ms(10), Transaction::delay_ms(20)]);
+        let mut attempts = 0;
+        let reading = monitor.read(&mut delay, || {
+            attempts += 1;
+            Err::<f32, _>(Error::I2C("Timeout".into()))
+        });
+        assert_eq!(3, attempts);
+        let failure = reading.unwrap_err();
+        assert_eq!("LM75", failure.device);
+        assert_eq!("I2C", failure.variant());
+        assert_eq!("\"Timeout\"", failure.details());
+        assert_eq!(1, failure.consecutive);
+        delay.done();
+    }
+
+    #[test]
+    fn retry_succeeds() {
+        let mut monitor = Monitor::with_retries("VEML6030", 2, 5);
+        let mut delay = CheckedDelay::new(&vec![Transaction::delay_ms(5)]);
+        let mut results = [Err(Error::InvalidInputData), Ok(3.0)].into_iter();
+        assert_eq!(
+            Ok(3.0),
+            monitor.read(&mut delay, || results.next().unwrap())
+        );
+        assert_eq!(0, monitor.consecutive_failures());
+        delay.done();
+    }
+
+    #[test]
+    fn counts_consecutive_failures() {
+        let mut monitor = Monitor::with_retries("TMP102", 1, 0);
+        let mut delay = NoopDelay::new();
+        for expected in 1..=3 {
+            let failure = monitor
+                .read(&mut delay, || Err::<f32, _>(Error::InvalidInputData))
+                .unwrap_err();
+            assert_eq!(expected, failure.consecutive);
+            assert_eq!("InvalidInputData", failure.variant());
+            assert_eq!("", failure.details());
+        }
+        let failure = Failure::new("TMP102", &Error::InvalidInputData, 3);
+        assert_eq!(
+            "TMP102: InvalidInputData (3 in a row)",
+            std::format!("{}", failure)
+        );
+    }
+
+    /// Poll a future which never has to wait, as with the mock delays.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn retries_asynchronously() {
+        let mut monitor = Monitor::new("CCS811");
+        let mut delay = CheckedDelay::new(&[Transaction::async_delay_ms(10)]);
+        let mut results = [Err(Error::InvalidInputData), Ok(400)].into_iter();
+        let reading = block_on(monitor.read_async(&mut delay, async || results.next().unwrap()));
+        assert_eq!(Ok(400), reading);
+        assert_eq!(0, monitor.consecutive_failures());
+        delay.done();
+    }
+
+    #[test]
+    fn long_errors_are_cut_off() {
+        let failure = Failure::new("X", &Error::I2C("x".repeat(100)), 1);
+        assert_eq!(48, failure.error.len());
+        assert_eq!("I2C", failure.variant());
+    }
+}
@@ -0,0 +1,568 @@
+//! RTTTL (Nokia ringtone) parser and a small library of tunes.
+//!
+//! An RTTTL tune is a line of text with its name, the default duration,
+//! octave and tempo, and the notes:
+//!
+//! ```text
+//! Success:d=16,o=5,b=140:c,e,g,8c6
+//! ```
+//!
+//! Every note has an optional duration (1, 2, 4, 8, 16 or 32 for a whole
+//! note, a half note...), the pitch (`c` to `b`, or `p` for a pause), an
+//! optional `#`, an optional octave (4 to 7) and an optional `.` which makes
+//! it 50% longer.
+//!
+//! [`Rtttl::steps`] turns the tune into the same [`Step`]s as a MIDI
+//! melody, so it is played the same way:
+//!
+//! ```
+//! # use driver_examples_common::rtttl::{Rtttl, LIBRARY};
+//! let tune = Rtttl::parse(LIBRARY[0]).unwrap();
+//! for step in tune.steps(1_000) {
+//!     // Wait `step.ticks` ticks of a 1 kHz timer, then play `step.note`.
+//! #   let _ = step;
+//! }
+//! ```
+
+use crate::midi::Step;
+use core::fmt;
+
+/// Built-in tunes, mostly short jingles to signal the status of a device.
+pub const LIBRARY: [&str; 6] = [
+    "Startup:d=16,o=5,b=160:c,g,c6,8e6",
+    "Success:d=16,o=5,b=140:c,e,g,8c6",
+    "Error:d=8,o=5,b=120:a#,p,a#,p,4f",
+    "Nokia:d=4,o=5,b=180:8e6,8d6,f#,g#,8c#6,8b,d,e,8b,8a,c#,e,2a",
+    "Twinkle:d=4,o=5,b=120:c,c,g,g,a,a,2g,f,f,e,e,d,d,2c",
+    "FurElise:d=16,o=5,b=125:e6,d#6,e6,d#6,e6,b,d6,c6,8a,c,e,a,8b,e,g#,b,8c6",
+];
+
+/// Error parsing a tune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RtttlError {
+    /// The name, settings and notes are not separated by `:`.
+    MissingSection,
+    /// A default duration, octave or tempo is unknown or out of range.
+    InvalidSetting,
+    /// The note with this index, starting at 0, is malformed.
+    InvalidNote(usize),
+}
+
+impl fmt::Display for RtttlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RtttlError::MissingSection => f.write_str("missing section"),
+            RtttlError::InvalidSetting => f.write_str("invalid setting"),
+            RtttlError::InvalidNote(index) => write!(f, "invalid note {}", index),
+        }
+    }
+}
+
+/// Note of a tune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// MIDI note number, or `None` for a pause.
+    pub note: Option<u8>,
+    /// Duration in microseconds.
+    pub duration_us: u32,
+}
+
+/// Parsed RTTTL tune.
+#[derive(Debug, Clone, Copy)]
+pub struct Rtttl<'a> {
+    name: &'a str,
+    duration: u32,
+    octave: u8,
+    bpm: u32,
+    notes: &'a str,
+}
+
+impl<'a> Rtttl<'a> {
+    /// Parse a tune and check all its notes.
+    pub fn parse(text: &'a str) -> Result<Self, RtttlError> {
+        let mut sections = text.trim().splitn(3, ':');
+        let (Some(name), Some(settings), Some(notes)) =
+            (sections.next(), sections.next(), sections.next())
+        else {
+            return Err(RtttlError::MissingSection);
+        };
+        let mut tune = Rtttl {
+            name: name.trim(),
+            duration: 4,
+            octave: 6,
+            bpm: 63,
+            notes,
+        };
+        for setting in settings.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting.split_once('=').ok_or(RtttlError::InvalidSetting)?;
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| RtttlError::InvalidSetting)?;
+            match key.trim() {
+                "d" if is_duration(value) => tune.duration = value,
+                "o" if is_octave(value) => tune.octave = value as u8,
+                "b" if (1..=900).contains(&value) => tune.bpm = value,
+                _ => return Err(RtttlError::InvalidSetting),
+            }
+        }
+        for (index, note) in tune.notes.split(',').enumerate() {
+            tune.note(note).ok_or(RtttlError::InvalidNote(index))?;
+        }
+        Ok(tune)
+    }
+
+    /// Name of the tune.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Tempo in beats (quarter notes) per minute.
+    pub fn bpm(&self) -> u32 {
+        self.bpm
+    }
+
+    /// Notes of the tune.
+    pub fn notes(&self) -> Notes<'a> {
+        Notes {
+            tune: *self,
+            notes: self.notes.split(','),
+        }
+    }
+
+    /// Steps to play the tune with a timer running at `tick_hz`.
+    ///
+    /// Every note is released shortly before the next one, so that repeated
+    /// notes can be told apart.
+    pub fn steps(&self, tick_hz: u32) -> Steps<'a> {
+        Steps {
+            notes: self.notes(),
+            tick_hz: u64::from(tick_hz),
+            time_us: 0,
+            emitted: 0,
+            release_us: None,
+            ended: false,
+        }
+    }
+
+    fn note(&self, text: &str) -> Option<Note> {
+        let text = text.trim();
+        let digits = text.find(|c: char| !c.is_ascii_digit())?;
+        let (duration, rest) = text.split_at(digits);
+        let duration = match duration {
+            "" => self.duration,
+            duration => duration.parse().ok().filter(|d| is_duration(*d))?,
+        };
+        let mut chars = rest.chars().peekable();
+        let semitone = match chars.next()?.to_ascii_lowercase() {
+            'c' => Some(0),
+            'd' => Some(2),
+            'e' => Some(4),
+            'f' => Some(5),
+            'g' => Some(7),
+            'a' => Some(9),
+            'b' | 'h' => Some(11),
+            'p' => None,
+            _ => return None,
+        };
+        let sharp = chars.next_if_eq(&'#').is_some();
+        // The dot is found both before and after the octave.
+        let mut dotted = chars.next_if_eq(&'.').is_some();
+        let octave = match chars.next_if(char::is_ascii_digit) {
+            Some(digit) => digit.to_digit(10).filter(|o| is_octave(*o))? as u8,
+            None => self.octave,
+        };
+        dotted |= chars.next_if_eq(&'.').is_some();
+        if chars.next().is_some() || (sharp && semitone.is_none()) {
+            return None;
+        }
+        let whole_note_us = 4 * 60_000_000 / self.bpm;
+        let mut duration_us = whole_note_us / duration;
+        if dotted {
+            duration_us += duration_us / 2;
+        }
+        Some(Note {
+            note: semitone.map(|semitone| 12 * (octave + 1) + semitone + u8::from(sharp)),
+            duration_us,
+        })
+    }
+}
+
+fn is_duration(value: u32) -> bool {
+    matches!(value, 1 | 2 | 4 | 8 | 16 | 32)
+}
+
+fn is_octave(value: u32) -> bool {
+    (4..=7).contains(&value)
+}
+
+/// Iterator over the notes of a tune.
+///
+/// The notes are checked when the tune is parsed.
+#[derive(Debug, Clone)]
+pub struct Notes<'a> {
+    tune: Rtttl<'a>,
+    notes: core::str::Split<'a, char>,
+}
+
+impl Iterator for Notes<'_> {
+    type Item = Note;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.notes.find_map(|note| self.tune.note(note))
+    }
+}
+
+/// Steps to play a tune, see [`Rtttl::steps`].
+#[derive(Debug, Clone)]
+pub struct Steps<'a> {
+    notes: Notes<'a>,
+    tick_hz: u64,
+    time_us: u64,
+    emitted: u64,
+    release_us: Option<u64>,
+    ended: bool,
+}
+
+impl Steps<'_> {
+    fn step_at(&mut self, time_us: u64, note: Option<u8>) -> Step {
+        let total = time_us * self.tick_hz / 1_000_000;
+        let ticks = (total - self.emitted) as u32;
+        self.emitted = total;
+        Step { ticks, note }
+    }
+}
+
+impl Iterator for Steps<'_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(release_us) = self.release_us.take() {
+            return Some(self.step_at(release_us, None));
+        }
+        while let Some(note) = self.notes.next() {
+            let start = self.time_us;
+            let duration = u64::from(note.duration_us);
+            self.time_us += duration;
+            if let Some(key) = note.note {
+                self.release_us = Some(start + duration * 7 / 8);
+                return Some(self.step_at(start, Some(key)));
+            }
+        }
+        if self.ended {
+            return None;
+        }
+        // Wait until the end of a final pause.
+        self.ended = true;
+        let end = self.step_at(self.time_us, None);
+        (end.ticks > 0).then_some(end)
+    }
+}
+
+/// What a line received per serial asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Play the tune of the [`LIBRARY`] with this index.
+    Play(usize),
+    /// Stop playing.
+    Stop,
+    /// Show the tunes.
+    List,
+    /// The line is not a command.
+    Unknown,
+}
+
+/// Collects the bytes received per serial into command lines.
+///
+/// A line is either the number of a tune, starting at 1, or its name,
+/// `stop` or `list`. Letter case does not matter.
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    line: heapless::String<16>,
+    overflow: bool,
+}
+
+impl CommandLine {
+    /// Add a received byte. Returns the command once a line is complete.
+    pub fn push(&mut self, byte: u8) -> Option<Command> {
+        if byte != b'\r' && byte != b'\n' {
+            if !byte.is_ascii() || self.line.push(byte as char).is_err() {
+                self.overflow = true;
+            }
+            return None;
+        }
+        let line = core::mem::take(&mut self.line);
+        if core::mem::take(&mut self.overflow) {
+            return Some(Command::Unknown);
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        Some(parse_command(line))
+    }
+}
+
+fn parse_command(line: &str) -> Command {
+    if line.eq_ignore_ascii_case("stop") {
+        return Command::Stop;
+    }
+    if line.eq_ignore_ascii_case("list") {
+        return Command::List;
+    }
+    if let Ok(number) = line.parse::<usize>() {
+        return if (1..=LIBRARY.len()).contains(&number) {
+            Command::Play(number - 1)
+        } else {
+            Command::Unknown
+        };
+    }
+    LIBRARY
+        .iter()
+        .position(|tune| {
+            tune.split(':')
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(line))
+        })
+        .map_or(Command::Unknown, Command::Play)
+}
+
+/// Plays the tunes of the [`LIBRARY`] one timer tick at a time, so that
+/// the main loop can check a button or a serial port in between.
+#[derive(Debug)]
+pub struct Jukebox {
+    tick_hz: u32,
+    playing: Option<usize>,
+    steps: Option<Steps<'static>>,
+    next: Option<Step>,
+    elapsed: u32,
+    pending: Option<Option<u8>>,
+}
+
+impl Jukebox {
+    /// Create a silent jukebox for a timer running at `tick_hz`.
+    pub fn new(tick_hz: u32) -> Self {
+        Jukebox {
+            tick_hz,
+            playing: None,
+            steps: None,
+            next: None,
+            elapsed: 0,
+            pending: None,
+        }
+    }
+
+    /// Start playing the tune with the given index from the beginning and
+    /// return its name. Panics if there is no tune with this index.
+    pub fn play(&mut self, index: usize) -> Result<&'static str, RtttlError> {
+        let tune = Rtttl::parse(LIBRARY[index])?;
+        let mut steps = tune.steps(self.tick_hz);
+        self.next = steps.next();
+        self.steps = Some(steps);
+        self.playing = Some(index);
+        self.elapsed = 0;
+        Ok(tune.name())
+    }
+
+    /// Play the tune after the current or last one.
+    pub fn play_next(&mut self) -> Result<&'static str, RtttlError> {
+        let index = self.playing.map_or(0, |index| (index + 1) % LIBRARY.len());
+        self.play(index)
+    }
+
+    /// Stop playing.
+    pub fn stop(&mut self) {
+        if self.steps.take().is_some() {
+            self.pending = Some(None);
+        }
+        self.next = None;
+    }
+
+    /// Index of the tune being played.
+    pub fn playing(&self) -> Option<usize> {
+        self.steps.as_ref().and(self.playing)
+    }
+
+    /// Advance one tick. Returns the note to switch to when it changes,
+    /// `Some(None)` for silence.
+    pub fn tick(&mut self) -> Option<Option<u8>> {
+        let mut change = self.pending.take();
+        while let Some(step) = self.next {
+            if self.elapsed < step.ticks {
+                break;
+            }
+            change = Some(step.note);
+            self.elapsed = 0;
+            self.next = self.steps.as_mut().and_then(Iterator::next);
+        }
+        if self.next.is_none() {
+            self.steps = None;
+        }
+        self.elapsed += 1;
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(tune: &str) -> heapless::Vec<(u32, Option<u8>), 64> {
+        let tune = Rtttl::parse(tune).unwrap();
+        tune.steps(1_000)
+            .map(|step| (step.ticks, step.note))
+            .collect()
+    }
+
+    #[test]
+    fn library_tunes_are_valid() {
+        for text in LIBRARY {
+            let tune = Rtttl::parse(text).unwrap();
+            assert!(text.starts_with(tune.name()));
+            assert!(tune.notes().count() > 3);
+        }
+    }
+
+    #[test]
+    fn reads_settings_and_notes() {
+        let tune = Rtttl::parse("Test:d=8,o=5,b=120:c,4d#6,p,2a.,h4,16g.").unwrap();
+        assert_eq!(("Test", 120), (tune.name(), tune.bpm()));
+        let notes: heapless::Vec<(Option<u8>, u32), 8> = tune
+            .notes()
+            .map(|note| (note.note, note.duration_us / 1000))
+            .collect();
+        assert_eq!(
+            &[
+                (Some(72), 250),
+                (Some(87), 500),
+                (None, 250),
+                (Some(81), 1500),
+                (Some(71), 250),
+                (Some(79), 187),
+            ],
+            &notes[..]
+        );
+    }
+
+    #[test]
+    fn uses_defaults_without_settings() {
+        // Quarter notes at 63 BPM in octave 6.
+        let tune = Rtttl::parse("Bare::a").unwrap();
+        let note = tune.notes().next().unwrap();
+        assert_eq!((Some(93), 952), (note.note, note.duration_us / 1000));
+    }
+
+    #[test]
+    fn releases_notes_before_the_next_one() {
+        assert_eq!(
+            &[
+                (0, Some(72)),
+                (54, None),
+                (8, Some(72)),
+                (55, None),
+                (8, Some(79)),
+                (109, None),
+                (16, None),
+            ],
+            &steps("Test:d=8,o=5,b=240:16c,16c,g")[..]
+        );
+    }
+
+    #[test]
+    fn waits_until_the_end_of_a_final_pause() {
+        assert_eq!(
+            &[(0, Some(72)), (218, None), (407, None)],
+            &steps("Test:d=4,o=5,b=240:c,8p,p")[..]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tunes() {
+        assert_eq!(
+            Err(RtttlError::MissingSection),
+            Rtttl::parse("No notes").map(|_| ())
+        );
+        assert_eq!(
+            Err(RtttlError::InvalidSetting),
+            Rtttl::parse("Test:d=3:c").map(|_| ())
+        );
+        assert_eq!(
+            Err(RtttlError::InvalidSetting),
+            Rtttl::parse("Test:x=1:c").map(|_| ())
+        );
+        assert_eq!(
+            Err(RtttlError::InvalidNote(2)),
+            Rtttl::parse("Test:d=4:c,d,x,e").map(|_| ())
+        );
+        assert_eq!(
+            Err(RtttlError::InvalidNote(0)),
+            Rtttl::parse("Test::p#").map(|_| ())
+        );
+        assert_eq!(
+            Err(RtttlError::InvalidNote(1)),
+            Rtttl::parse("Test::c,c9").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn jukebox_plays_tune_tick_by_tick() {
+        let mut jukebox = Jukebox::new(1_000);
+        assert_eq!(None, jukebox.tick());
+        assert_eq!(Ok("Success"), jukebox.play(1));
+        let changes: heapless::Vec<(usize, Option<u8>), 16> = (0..1_000)
+            .filter_map(|tick| jukebox.tick().map(|note| (tick, note)))
+            .collect();
+        // Sixteenth notes at 140 BPM last 107 ms.
+        assert_eq!((0, Some(72)), changes[0]);
+        assert_eq!((93, None), changes[1]);
+        assert_eq!((107, Some(76)), changes[2]);
+        assert_eq!(Some(&(535, None)), changes.last());
+        assert_eq!(None, jukebox.playing());
+    }
+
+    #[test]
+    fn jukebox_switches_and_stops() {
+        let mut jukebox = Jukebox::new(1_000);
+        assert_eq!(Ok("Startup"), jukebox.play_next());
+        assert_eq!(Some(Some(72)), jukebox.tick());
+        assert_eq!(Ok("Success"), jukebox.play_next());
+        assert_eq!(Some(1), jukebox.playing());
+        assert_eq!(Some(Some(72)), jukebox.tick());
+        jukebox.stop();
+        assert_eq!(None, jukebox.playing());
+        assert_eq!(Some(None), jukebox.tick());
+        assert_eq!(None, jukebox.tick());
+        jukebox.play(LIBRARY.len() - 1).unwrap();
+        assert_eq!(Ok("Startup"), jukebox.play_next());
+    }
+
+    #[test]
+    fn reads_command_lines() {
+        let mut line = CommandLine::default();
+        let mut commands = heapless::Vec::<Command, 8>::new();
+        for byte in b"2\r\nnokia\nSTOP\n\nlist\n7\nnothing\n" {
+            if let Some(command) = line.push(*byte) {
+                commands.push(command).unwrap();
+            }
+        }
+        assert_eq!(
+            &[
+                Command::Play(1),
+                Command::Play(3),
+                Command::Stop,
+                Command::List,
+                Command::Unknown,
+                Command::Unknown,
+            ],
+            &commands[..]
+        );
+        for byte in b"a very long line which does not fit\n1\n" {
+            if let Some(command) = line.push(*byte) {
+                commands.push(command).unwrap();
+            }
+        }
+        assert_eq!(&[Command::Unknown, Command::Play(0)], &commands[6..]);
+    }
+}
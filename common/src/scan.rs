@@ -0,0 +1,433 @@
+//! I2C bus scanner which identifies the supported devices.
+//!
+//! Every address from [`FIRST_ADDRESS`] to [`LAST_ADDRESS`] is probed with
+//! a one byte read. For each address which answers, the identity registers
+//! of the parts which can be at that address are read (e.g. the CCS811
+//! `HW_ID` or the BMI160 `CHIP_ID`). Parts without identity registers are
+//! only listed as candidates for their addresses.
+//!
+//! ```
+//! # use driver_examples_common::scan;
+//! # fn example<I2C: embedded_hal::i2c::I2c>(mut i2c: I2C) {
+//! for found in scan::scan(&mut i2c) {
+//!     // e.g. "0x5A: CCS811 (hw id 0x81)"
+//!     println!("{}", found);
+//! }
+//! # }
+//! ```
+//!
+//! Note that reading an identity register means writing the register
+//! address first. A device without registers at the same address, like a
+//! PCF8574, takes that byte as data.
+
+use core::fmt;
+use embedded_hal::i2c::I2c;
+
+/// First address probed. Lower ones are reserved.
+pub const FIRST_ADDRESS: u8 = 0x08;
+/// Last address probed. Higher ones are reserved.
+pub const LAST_ADDRESS: u8 = 0x77;
+
+/// Expected contents of an identity register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Id {
+    /// Single byte.
+    Byte(u8),
+    /// 16-bit value, most significant byte first.
+    BigEndian(u16),
+    /// 16-bit value, least significant byte first.
+    LittleEndian(u16),
+}
+
+impl Id {
+    fn len(self) -> usize {
+        match self {
+            Id::Byte(_) => 1,
+            Id::BigEndian(_) | Id::LittleEndian(_) => 2,
+        }
+    }
+
+    fn matches(self, data: &[u8]) -> bool {
+        match (self, data) {
+            (Id::Byte(id), [value]) => id == *value,
+            (Id::BigEndian(id), [high, low]) | (Id::LittleEndian(id), [low, high]) => {
+                id == u16::from_be_bytes([*high, *low])
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id::Byte(id) => write!(f, "0x{:02X}", id),
+            Id::BigEndian(id) | Id::LittleEndian(id) => write!(f, "0x{:04X}", id),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Id {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Id::Byte(id) => defmt::write!(f, "0x{=u8:02X}", id),
+            Id::BigEndian(id) | Id::LittleEndian(id) => defmt::write!(f, "0x{=u16:04X}", id),
+        }
+    }
+}
+
+/// Identity register of a part.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Identity {
+    /// Name of the part.
+    pub part: &'static str,
+    /// Addresses the part can have.
+    pub addresses: &'static [u8],
+    /// Address of the identity register.
+    pub register: u8,
+    /// Name of the identity register, as shown in the results.
+    pub name: &'static str,
+    /// Expected contents of the identity register.
+    pub id: Id,
+}
+
+/// Identity registers of the supported parts.
+///
+/// They are tried in this order.
+pub const IDENTITIES: &[Identity] = &[
+    Identity {
+        part: "KXCJ9",
+        addresses: &[0x0E, 0x0F],
+        register: 0x0F,
+        name: "who am i",
+        id: Id::Byte(0x0A),
+    },
+    Identity {
+        part: "VEML6030",
+        addresses: &[0x10, 0x48],
+        // Only the low byte, the high one depends on the address.
+        register: 0x07,
+        name: "id",
+        id: Id::Byte(0x81),
+    },
+    Identity {
+        part: "VEML6075",
+        addresses: &[0x10],
+        register: 0x0C,
+        name: "id",
+        id: Id::LittleEndian(0x0026),
+    },
+    Identity {
+        part: "LSM303AGR acc",
+        addresses: &[0x19],
+        register: 0x0F,
+        name: "who am i",
+        id: Id::Byte(0x33),
+    },
+    Identity {
+        part: "LSM303AGR mag",
+        addresses: &[0x1E],
+        register: 0x4F,
+        name: "who am i",
+        id: Id::Byte(0x40),
+    },
+    Identity {
+        part: "MMA8452",
+        addresses: &[0x1C, 0x1D],
+        register: 0x0D,
+        name: "who am i",
+        id: Id::Byte(0x2A),
+    },
+    Identity {
+        part: "TCS34725",
+        addresses: &[0x29],
+        // With the command bit set.
+        register: 0x92,
+        name: "id",
+        id: Id::Byte(0x44),
+    },
+    Identity {
+        part: "TCS34727",
+        addresses: &[0x29],
+        register: 0x92,
+        name: "id",
+        id: Id::Byte(0x4D),
+    },
+    Identity {
+        part: "APDS9960",
+        addresses: &[0x39],
+        register: 0x92,
+        name: "id",
+        id: Id::Byte(0xAB),
+    },
+    Identity {
+        part: "HDC2080",
+        addresses: &[0x40, 0x41],
+        register: 0xFE,
+        name: "device id",
+        id: Id::LittleEndian(0x07D0),
+    },
+    Identity {
+        part: "TMP006",
+        addresses: &[0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47],
+        register: 0xFF,
+        name: "device id",
+        id: Id::BigEndian(0x0067),
+    },
+    Identity {
+        part: "ISL29125",
+        addresses: &[0x44],
+        register: 0x00,
+        name: "device id",
+        id: Id::Byte(0x7D),
+    },
+    Identity {
+        part: "OPT3001",
+        addresses: &[0x44, 0x45, 0x46, 0x47],
+        register: 0x7F,
+        name: "device id",
+        id: Id::BigEndian(0x3001),
+    },
+    Identity {
+        part: "MAX30102",
+        addresses: &[0x57],
+        register: 0xFF,
+        name: "part id",
+        id: Id::Byte(0x15),
+    },
+    Identity {
+        part: "CCS811",
+        addresses: &[0x5A, 0x5B],
+        register: 0x20,
+        name: "hw id",
+        id: Id::Byte(0x81),
+    },
+    Identity {
+        part: "BMI160",
+        addresses: &[0x68, 0x69],
+        register: 0x00,
+        name: "chip id",
+        id: Id::Byte(0xD1),
+    },
+];
+
+/// Parts without identity registers, by address range.
+const CANDIDATES: &[(u8, u8, &str)] = &[
+    (0x10, 0x10, "Si4703/VEML6040"),
+    (0x20, 0x27, "PCF8574/PCF8575"),
+    (0x36, 0x36, "MAX170xx"),
+    (0x38, 0x39, "VEML6070/PCF8574A"),
+    (0x3A, 0x3B, "PCF8574A"),
+    (0x3C, 0x3D, "SSD1306/PCF8574A"),
+    (0x3E, 0x3F, "PCF8574A"),
+    (0x40, 0x47, "PCA9685"),
+    (0x48, 0x48, "ADS1x15/LM75/TMP1x2"),
+    (0x49, 0x49, "ADS1x15/LM75/TMP1x2"),
+    (0x4A, 0x4B, "ADS1x15/LM75/TMP1x2/MAX44009"),
+    (0x4C, 0x4F, "LM75"),
+    (0x50, 0x56, "24x EEPROM"),
+    (0x57, 0x57, "24x EEPROM/MCP794xx EEPROM"),
+    (0x5A, 0x5A, "iAQ-Core/MLX90614"),
+    (0x5B, 0x5B, "MLX90615"),
+    (0x68, 0x68, "DS1307/DS323x"),
+    (0x6F, 0x6F, "MCP794xx"),
+    (0x70, 0x77, "TCA9548A"),
+];
+
+/// Parts without identity registers which can be at an address, separated
+/// by slashes.
+pub fn candidates(address: u8) -> Option<&'static str> {
+    CANDIDATES
+        .iter()
+        .find(|(first, last, _)| (*first..=*last).contains(&address))
+        .map(|(_, _, parts)| *parts)
+}
+
+/// Whether a device answers at the address.
+pub fn probe<I2C: I2c>(i2c: &mut I2C, address: u8) -> bool {
+    i2c.read(address, &mut [0]).is_ok()
+}
+
+/// Read the identity registers of the parts which can be at the address
+/// and return the first one which matches.
+pub fn identify<I2C: I2c>(i2c: &mut I2C, address: u8) -> Option<&'static Identity> {
+    IDENTITIES
+        .iter()
+        .filter(|identity| identity.addresses.contains(&address))
+        .find(|identity| {
+            let mut data = [0; 2];
+            let data = &mut data[..identity.id.len()];
+            i2c.write_read(address, &[identity.register], data).is_ok() && identity.id.matches(data)
+        })
+}
+
+/// Device answering at an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Found {
+    /// Address of the device.
+    pub address: u8,
+    /// Identity of the device, if it could be read.
+    pub identity: Option<&'static Identity>,
+}
+
+impl Found {
+    /// Name of the part, the candidates for the address or `"unknown"`.
+    pub fn name(&self) -> &'static str {
+        match self.identity {
+            Some(identity) => identity.part,
+            None => candidates(self.address).unwrap_or("unknown"),
+        }
+    }
+}
+
+/// Shows e.g. `0x5A: CCS811 (hw id 0x81)`, `0x48: ADS1x15/LM75/TMP1x2?`
+/// or `0x12: unknown`.
+///
+/// The alternate form (`{:#}`) leaves the identity register out so that it
+/// fits in a display row.
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:02X}: {}", self.address, self.name())?;
+        match self.identity {
+            Some(identity) if !f.alternate() => {
+                write!(f, " ({} {})", identity.name, identity.id)
+            }
+            None if candidates(self.address).is_some() => f.write_str("?"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Same as the `Display` implementation.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Found {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "0x{=u8:02X}: {=str}", self.address, self.name());
+        match self.identity {
+            Some(identity) => defmt::write!(f, " ({=str} {})", identity.name, identity.id),
+            None if candidates(self.address).is_some() => defmt::write!(f, "?"),
+            None => {}
+        }
+    }
+}
+
+/// Iterator over the devices on a bus, see [`scan`].
+#[derive(Debug)]
+pub struct Scanner<I2C> {
+    i2c: I2C,
+    next: Option<u8>,
+}
+
+/// Probe all addresses and identify the devices which answer.
+///
+/// The addresses are probed as the iterator advances.
+pub fn scan<I2C: I2c>(i2c: I2C) -> Scanner<I2C> {
+    Scanner {
+        i2c,
+        next: Some(FIRST_ADDRESS),
+    }
+}
+
+impl<I2C: I2c> Iterator for Scanner<I2C> {
+    type Item = Found;
+
+    fn next(&mut self) -> Option<Found> {
+        while let Some(address) = self.next {
+            self.next = (address < LAST_ADDRESS).then(|| address + 1);
+            if probe(&mut self.i2c, address) {
+                return Some(Found {
+                    address,
+                    identity: identify(&mut self.i2c, address),
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+    use std::{format, vec, vec::Vec};
+
+    fn absent(address: u8) -> I2cTrans {
+        I2cTrans::read(address, vec![0])
+            .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+    }
+
+    fn present(address: u8) -> I2cTrans {
+        I2cTrans::read(address, vec![0])
+    }
+
+    #[test]
+    fn identifies_devices() {
+        let mut expectations = Vec::new();
+        for address in FIRST_ADDRESS..=LAST_ADDRESS {
+            match address {
+                0x40 => {
+                    expectations.push(present(address));
+                    expectations.push(I2cTrans::write_read(address, vec![0xFE], vec![0xD0, 0x07]));
+                }
+                0x48 => {
+                    expectations.push(present(address));
+                    expectations.push(I2cTrans::write_read(address, vec![0x07], vec![0x00]));
+                }
+                0x5A => {
+                    expectations.push(present(address));
+                    expectations.push(I2cTrans::write_read(address, vec![0x20], vec![0x81]));
+                }
+                _ => expectations.push(absent(address)),
+            }
+        }
+        let mut i2c = I2cMock::new(&expectations);
+
+        let found: Vec<_> = scan(&mut i2c).map(|found| format!("{}", found)).collect();
+        assert_eq!(
+            vec![
+                "0x40: HDC2080 (device id 0x07D0)",
+                "0x48: ADS1x15/LM75/TMP1x2?",
+                "0x5A: CCS811 (hw id 0x81)",
+            ],
+            found
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn tries_all_parts_at_an_address() {
+        let address = 0x44;
+        let mut i2c = I2cMock::new(&[
+            I2cTrans::write_read(address, vec![0xFF], vec![0x00, 0x00]),
+            I2cTrans::write_read(address, vec![0x00], vec![0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+            I2cTrans::write_read(address, vec![0x7F], vec![0x30, 0x01]),
+        ]);
+        let identity = identify(&mut i2c, address).unwrap();
+        assert_eq!("OPT3001", identity.part);
+        assert_eq!(
+            "0x44: OPT3001",
+            format!(
+                "{:#}",
+                Found {
+                    address,
+                    identity: Some(identity)
+                }
+            )
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn unknown_address() {
+        let found = Found {
+            address: 0x12,
+            identity: None,
+        };
+        assert_eq!("unknown", found.name());
+        assert_eq!("0x12: unknown", format!("{}", found));
+    }
+}
@@ -0,0 +1,297 @@
+//! Common interface to read any sensor.
+//!
+//! Every driver has its own read methods and measurement types, e.g.
+//! `read_lux`, `read_temperature`, `read_all_channels` or `data`. The
+//! [`Sensor`] trait measures every quantity a device provides and returns
+//! them as [`Quantities`] with their units, so that one generic loop can log
+//! or show the readings of any attached device:
+//!
+//! ```
+//! # use driver_examples_common::{reading::Reading, sensor::{Quantities, Sensor}, Monitor};
+//! # use embedded_hal::delay::DelayNs;
+//! fn read<S: Sensor>(sensor: &mut S, monitor: &mut Monitor, delay: &mut impl DelayNs) -> Reading<Quantities> {
+//!     monitor.read(delay, || sensor.measure())
+//! }
+//! ```
+//!
+//! The adapters for the drivers are behind features named after them, like
+//! the rest of this crate. The devices must be configured and enabled as in
+//! their examples before measuring.
+//!
+//! Every driver in the examples which measures something has an adapter.
+//! The rest do not measure a physical quantity: the DACs, digital
+//! potentiometers, PWM and DDS generators (`mcp49xx`, `mcp4x`,
+//! `pwm-pca9685`, `ad983x`), the memories (`eeprom24x`, `w25`), the I/O
+//! expanders and the multiplexer (`pcf857x`, `xca9548a`), the FM receiver
+//! (`si4703`) and the real-time clocks without a thermometer (`ds1307`,
+//! `mcp794xx`).
+
+#[cfg(feature = "ads1x1x")]
+mod ads1x1x;
+#[cfg(feature = "apds9960")]
+mod apds9960;
+#[cfg(feature = "bmi160")]
+mod bmi160;
+#[cfg(feature = "ds323x")]
+mod ds323x;
+#[cfg(feature = "embedded-ccs811")]
+mod embedded_ccs811;
+#[cfg(feature = "hdc20xx")]
+mod hdc20xx;
+#[cfg(feature = "iaq-core")]
+mod iaq_core;
+#[cfg(feature = "isl29125")]
+mod isl29125;
+#[cfg(feature = "kxcj9")]
+mod kxcj9;
+#[cfg(feature = "lm75")]
+mod lm75;
+#[cfg(feature = "lsm303agr")]
+mod lsm303agr;
+#[cfg(feature = "max170xx")]
+mod max170xx;
+#[cfg(feature = "max3010x")]
+mod max3010x;
+#[cfg(feature = "max44009")]
+mod max44009;
+#[cfg(feature = "mlx9061x")]
+mod mlx9061x;
+#[cfg(feature = "mma8x5x")]
+mod mma8x5x;
+#[cfg(feature = "opt300x")]
+mod opt300x;
+#[cfg(feature = "tcs3472")]
+mod tcs3472;
+#[cfg(feature = "tmp006")]
+mod tmp006;
+#[cfg(feature = "tmp1x2")]
+mod tmp1x2;
+#[cfg(feature = "veml6030")]
+mod veml6030;
+#[cfg(feature = "veml6040")]
+mod veml6040;
+#[cfg(feature = "veml6070")]
+mod veml6070;
+#[cfg(feature = "veml6075")]
+mod veml6075;
+
+#[cfg(feature = "bmi160")]
+pub use self::bmi160::Bmi160Error;
+
+use core::fmt::{self, Debug};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of quantities measured by one device.
+pub const MAX_QUANTITIES: usize = 8;
+
+/// Quantities measured by a device at once.
+pub type Quantities = heapless::Vec<Quantity, MAX_QUANTITIES>;
+
+/// Unit of a measured quantity.
+///
+/// New units go at the end so that the telemetry of older firmware is still
+/// decoded correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Unit {
+    /// Temperature in degrees Celsius.
+    Celsius,
+    /// Relative humidity in percent.
+    RelativeHumidity,
+    /// Illuminance in lux.
+    Lux,
+    /// Concentration in parts per million, e.g. eCO2.
+    Ppm,
+    /// Concentration in parts per billion, e.g. eTVOC.
+    Ppb,
+    /// Resistance in ohms.
+    Ohm,
+    /// Acceleration in thousandths of the standard gravity.
+    MilliG,
+    /// Angular rate in degrees per second.
+    DegreesPerSecond,
+    /// Magnetic field in nanotesla.
+    NanoTesla,
+    /// Voltage in volts.
+    Volt,
+    /// Ratio in percent, e.g. the state of charge of a battery.
+    Percent,
+    /// UV index.
+    UvIndex,
+    /// Raw reading of the device, e.g. the counts of a color channel or an
+    /// ADC.
+    Count,
+}
+
+impl Unit {
+    /// Symbol shown after the value.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Celsius => "ºC",
+            Unit::RelativeHumidity => "%RH",
+            Unit::Lux => " lux",
+            Unit::Ppm => " ppm",
+            Unit::Ppb => " ppb",
+            Unit::Ohm => " Ohm",
+            Unit::MilliG => " mg",
+            Unit::DegreesPerSecond => " º/s",
+            Unit::NanoTesla => " nT",
+            Unit::Volt => " V",
+            Unit::Percent => "%",
+            Unit::UvIndex | Unit::Count => "",
+        }
+    }
+
+    /// Number of decimals worth showing.
+    pub fn decimals(self) -> usize {
+        match self {
+            Unit::Ppm | Unit::Ppb | Unit::Ohm | Unit::MilliG | Unit::NanoTesla | Unit::Count => 0,
+            Unit::Celsius
+            | Unit::RelativeHumidity
+            | Unit::Lux
+            | Unit::DegreesPerSecond
+            | Unit::Volt
+            | Unit::Percent
+            | Unit::UvIndex => 2,
+        }
+    }
+}
+
+/// Value of a quantity measured by a device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    /// Short name of the quantity, e.g. `"Temp"` or `"eCO2"`.
+    pub name: &'static str,
+    /// Unit of the value.
+    pub unit: Unit,
+    /// Measured value.
+    pub value: f32,
+}
+
+impl Quantity {
+    /// Create a quantity.
+    pub const fn new(name: &'static str, unit: Unit, value: f32) -> Self {
+        Quantity { name, unit, value }
+    }
+}
+
+/// Shows e.g. `Temp: 21.50ºC` or `eCO2: 400 ppm`.
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.*}{}",
+            self.name,
+            self.unit.decimals(),
+            self.value,
+            self.unit.symbol()
+        )
+    }
+}
+
+/// Same as the `Display` implementation, but always with two decimals.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Quantity {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "{=str}: {=f32}{=str}",
+            self.name,
+            self.value,
+            self.unit.symbol()
+        )
+    }
+}
+
+/// Device which measures one or more quantities.
+pub trait Sensor {
+    /// Error of the driver.
+    type Error: Debug;
+
+    /// Name of the device, e.g. `"LM75"`.
+    fn name(&self) -> &'static str;
+
+    /// Measure every quantity the device provides.
+    ///
+    /// Waits until the measurement is ready if the device is busy.
+    fn measure(&mut self) -> Result<Quantities, Self::Error>;
+}
+
+/// Collect the quantities measured by a device, e.g. in an adapter for
+/// another driver. Anything beyond [`MAX_QUANTITIES`] is dropped.
+pub fn quantities<const N: usize>(values: [Quantity; N]) -> Quantities {
+    values.into_iter().take(MAX_QUANTITIES).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::Monitor;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use std::format;
+
+    /// Fails every other measurement.
+    struct Thermometer {
+        fail: bool,
+    }
+
+    impl Sensor for Thermometer {
+        type Error = ();
+
+        fn name(&self) -> &'static str {
+            "THERMO"
+        }
+
+        fn measure(&mut self) -> Result<Quantities, ()> {
+            self.fail = !self.fail;
+            if !self.fail {
+                return Err(());
+            }
+            Ok(quantities([
+                Quantity::new("Temp", Unit::Celsius, 21.5),
+                Quantity::new("Humidity", Unit::RelativeHumidity, 40.25),
+            ]))
+        }
+    }
+
+    #[test]
+    fn shows_values_with_units() {
+        assert_eq!(
+            "Temp: 21.50ºC",
+            format!("{}", Quantity::new("Temp", Unit::Celsius, 21.5))
+        );
+        assert_eq!(
+            "eCO2: 412 ppm",
+            format!("{}", Quantity::new("eCO2", Unit::Ppm, 412.0))
+        );
+        assert_eq!(
+            "Red: 1024",
+            format!("{}", Quantity::new("Red", Unit::Count, 1024.0))
+        );
+        assert_eq!(
+            "X: -981 mg",
+            format!("{}", Quantity::new("X", Unit::MilliG, -981.0))
+        );
+    }
+
+    #[test]
+    fn generic_loop_reads_any_sensor() {
+        fn read<S: Sensor>(sensor: &mut S) -> crate::Reading<Quantities> {
+            let mut monitor = Monitor::with_retries(sensor.name(), 1, 0);
+            monitor.read(&mut NoopDelay::new(), || sensor.measure())
+        }
+
+        let mut sensor = Thermometer { fail: false };
+        let quantities = read(&mut sensor).unwrap();
+        assert_eq!(2, quantities.len());
+        assert_eq!(Unit::RelativeHumidity, quantities[1].unit);
+        assert_eq!("THERMO", read(&mut sensor).unwrap_err().device);
+    }
+
+    #[test]
+    fn keeps_at_most_max_quantities() {
+        let many = quantities([Quantity::new("A0", Unit::Count, 0.0); MAX_QUANTITIES + 2]);
+        assert_eq!(MAX_QUANTITIES, many.len());
+    }
+}
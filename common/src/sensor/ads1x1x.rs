@@ -0,0 +1,39 @@
+//! ADS1015 and ADS1115 ADCs in one-shot mode.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use ads1x1x::{channel, ic, mode, Ads1x1x, Error};
+use embedded_hal::i2c::I2c;
+use nb::block;
+
+macro_rules! impl_sensor {
+    ($ic:ident, $resolution:ident, $name:expr) => {
+        /// Counts of the four single-ended channels `A0`..`A3`.
+        impl<I2C, E> Sensor for Ads1x1x<I2C, ic::$ic, ic::$resolution, mode::OneShot>
+        where
+            I2C: I2c<Error = E>,
+            E: core::fmt::Debug,
+        {
+            type Error = Error<E>;
+
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn measure(&mut self) -> Result<Quantities, Self::Error> {
+                let a0 = block!(self.read(channel::SingleA0))?;
+                let a1 = block!(self.read(channel::SingleA1))?;
+                let a2 = block!(self.read(channel::SingleA2))?;
+                let a3 = block!(self.read(channel::SingleA3))?;
+                Ok(quantities([
+                    Quantity::new("A0", Unit::Count, f32::from(a0)),
+                    Quantity::new("A1", Unit::Count, f32::from(a1)),
+                    Quantity::new("A2", Unit::Count, f32::from(a2)),
+                    Quantity::new("A3", Unit::Count, f32::from(a3)),
+                ]))
+            }
+        }
+    };
+}
+
+impl_sensor!(Ads1015, Resolution12Bit, "ADS1015");
+impl_sensor!(Ads1115, Resolution16Bit, "ADS1115");
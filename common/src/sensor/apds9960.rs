@@ -0,0 +1,32 @@
+//! APDS9960 color, ambient light and proximity sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use apds9960::{Apds9960, Error};
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+use nb::block;
+
+/// Clear, red, green and blue counts and the proximity. The light and
+/// proximity engines must be enabled.
+impl<I2C, E> Sensor for Apds9960<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "APDS9960"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let light = block!(self.read_light())?;
+        let proximity = block!(self.read_proximity())?;
+        Ok(quantities([
+            Quantity::new("Clear", Unit::Count, f32::from(light.clear)),
+            Quantity::new("Red", Unit::Count, f32::from(light.red)),
+            Quantity::new("Green", Unit::Count, f32::from(light.green)),
+            Quantity::new("Blue", Unit::Count, f32::from(light.blue)),
+            Quantity::new("Prox", Unit::Count, f32::from(proximity)),
+        ]))
+    }
+}
@@ -0,0 +1,57 @@
+//! BMI160 inertial measurement unit.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use bmi160::{interface::I2cInterface, Bmi160, Error, SensorSelector};
+use embedded_hal::i2c::I2c;
+
+/// Counts per g at the default range of ±2 g.
+const ACCEL_COUNTS_PER_G: f32 = 16_384.0;
+/// Counts per º/s at the default range of ±2000 º/s.
+const GYRO_COUNTS_PER_DPS: f32 = 16.4;
+
+/// Error of the BMI160 adapter.
+#[derive(Debug)]
+pub enum Bmi160Error<E> {
+    /// Error of the driver.
+    Device(Error<E>),
+    /// The device returned no acceleration or no angular rate, e.g.
+    /// because a sensor is suspended.
+    MissingData,
+}
+
+impl<E> From<Error<E>> for Bmi160Error<E> {
+    fn from(error: Error<E>) -> Self {
+        Bmi160Error::Device(error)
+    }
+}
+
+/// Acceleration and angular rate at the default ranges. Both sensors must be
+/// in normal power mode.
+impl<I2C, E> Sensor for Bmi160<I2cInterface<I2C>>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Bmi160Error<E>;
+
+    fn name(&self) -> &'static str {
+        "BMI160"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = self.data(SensorSelector::new().accel().gyro())?;
+        let (Some(accel), Some(gyro)) = (data.accel, data.gyro) else {
+            return Err(Bmi160Error::MissingData);
+        };
+        let mg = |raw: i16| f32::from(raw) * 1000.0 / ACCEL_COUNTS_PER_G;
+        let dps = |raw: i16| f32::from(raw) / GYRO_COUNTS_PER_DPS;
+        Ok(quantities([
+            Quantity::new("Acc X", Unit::MilliG, mg(accel.x)),
+            Quantity::new("Acc Y", Unit::MilliG, mg(accel.y)),
+            Quantity::new("Acc Z", Unit::MilliG, mg(accel.z)),
+            Quantity::new("Gyr X", Unit::DegreesPerSecond, dps(gyro.x)),
+            Quantity::new("Gyr Y", Unit::DegreesPerSecond, dps(gyro.y)),
+            Quantity::new("Gyr Z", Unit::DegreesPerSecond, dps(gyro.z)),
+        ]))
+    }
+}
@@ -0,0 +1,29 @@
+//! Temperature sensor of the DS3231, DS3232 and DS3234 real-time clocks.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use ds323x::{
+    interface::{ReadData, WriteData},
+    Ds323x, Error,
+};
+
+/// Temperature of the compensated oscillator.
+impl<DI, IC, CommE> Sensor for Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<CommE>> + WriteData<Error = Error<CommE>>,
+    CommE: core::fmt::Debug,
+{
+    type Error = Error<CommE>;
+
+    fn name(&self) -> &'static str {
+        "DS323x"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let temperature = self.temperature()?;
+        Ok(quantities([Quantity::new(
+            "Temp",
+            Unit::Celsius,
+            temperature,
+        )]))
+    }
+}
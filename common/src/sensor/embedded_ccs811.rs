@@ -0,0 +1,28 @@
+//! CCS811 gas and VOC sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_ccs811::{mode, prelude::*, Ccs811Awake, ErrorAwake};
+use embedded_hal::i2c::I2c;
+use nb::block;
+
+/// Equivalent CO2 and total VOC. The application must be running in a
+/// measurement mode.
+impl<I2C, E> Sensor for Ccs811Awake<I2C, mode::App>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = ErrorAwake<E>;
+
+    fn name(&self) -> &'static str {
+        "CCS811"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = block!(self.data())?;
+        Ok(quantities([
+            Quantity::new("eCO2", Unit::Ppm, f32::from(data.eco2)),
+            Quantity::new("eTVOC", Unit::Ppb, f32::from(data.etvoc)),
+        ]))
+    }
+}
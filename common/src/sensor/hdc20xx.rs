@@ -0,0 +1,29 @@
+//! HDC2080 and HDC2010 temperature and humidity sensors in one-shot mode.
+
+use super::{Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+use hdc20xx::{mode, Error, Hdc20xx};
+use nb::block;
+
+/// Temperature and, if measured, relative humidity.
+impl<I2C, E> Sensor for Hdc20xx<I2C, mode::OneShot>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "HDC20xx"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = block!(self.read())?;
+        let mut quantities = Quantities::new();
+        let _ = quantities.push(Quantity::new("Temp", Unit::Celsius, data.temperature));
+        if let Some(humidity) = data.humidity {
+            let _ = quantities.push(Quantity::new("Humidity", Unit::RelativeHumidity, humidity));
+        }
+        Ok(quantities)
+    }
+}
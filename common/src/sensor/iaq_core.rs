@@ -0,0 +1,28 @@
+//! iAQ-Core indoor air quality sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::Read;
+use iaq_core::{Error, IaqCore};
+use nb::block;
+
+/// CO2 prediction, total VOC and the resistance of the sensing element.
+impl<I2C, E> Sensor for IaqCore<I2C>
+where
+    I2C: Read<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "iAQ-Core"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = block!(self.data())?;
+        Ok(quantities([
+            Quantity::new("CO2", Unit::Ppm, f32::from(data.co2)),
+            Quantity::new("TVOC", Unit::Ppb, f32::from(data.tvoc)),
+            Quantity::new("Resist", Unit::Ohm, data.resistance as f32),
+        ]))
+    }
+}
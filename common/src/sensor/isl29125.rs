@@ -0,0 +1,28 @@
+//! ISL29125 RGB color sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+use isl29125::{Error, Isl29125};
+
+/// Red, green and blue counts. The sensor must be in the red, green and
+/// blue operating mode.
+impl<I2C, E> Sensor for Isl29125<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "ISL29125"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = self.read()?;
+        Ok(quantities([
+            Quantity::new("Red", Unit::Count, f32::from(data.red)),
+            Quantity::new("Green", Unit::Count, f32::from(data.green)),
+            Quantity::new("Blue", Unit::Count, f32::from(data.blue)),
+        ]))
+    }
+}
@@ -0,0 +1,28 @@
+//! KXCJ9 and KXCJB accelerometers.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+use kxcj9::{Error, Kxcj9, ScaledDevice};
+
+/// Acceleration. The device must be enabled.
+impl<I2C, E, IC> Sensor for Kxcj9<I2C, IC>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    E: core::fmt::Debug,
+    IC: ScaledDevice,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "KXCJ9"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let accel = self.read()?;
+        Ok(quantities([
+            Quantity::new("X", Unit::MilliG, accel.x * 1000.0),
+            Quantity::new("Y", Unit::MilliG, accel.y * 1000.0),
+            Quantity::new("Z", Unit::MilliG, accel.z * 1000.0),
+        ]))
+    }
+}
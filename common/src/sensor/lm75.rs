@@ -0,0 +1,27 @@
+//! LM75 temperature sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use lm75::{ic, Error, Lm75};
+
+/// Temperature.
+impl<I2C, E> Sensor for Lm75<I2C, ic::Lm75>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "LM75"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let temperature = self.read_temperature()?;
+        Ok(quantities([Quantity::new(
+            "Temp",
+            Unit::Celsius,
+            temperature,
+        )]))
+    }
+}
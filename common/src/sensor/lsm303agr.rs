@@ -0,0 +1,32 @@
+//! LSM303AGR accelerometer and magnetometer.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use lsm303agr::{interface::I2cInterface, mode, Error, Lsm303agr};
+
+/// Acceleration and magnetic field. The accelerometer must have an output
+/// data rate set.
+impl<I2C, E> Sensor for Lsm303agr<I2cInterface<I2C>, mode::MagContinuous>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "LSM303AGR"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let accel = self.acceleration()?;
+        let field = self.magnetic_field()?;
+        Ok(quantities([
+            Quantity::new("Acc X", Unit::MilliG, accel.x_mg() as f32),
+            Quantity::new("Acc Y", Unit::MilliG, accel.y_mg() as f32),
+            Quantity::new("Acc Z", Unit::MilliG, accel.z_mg() as f32),
+            Quantity::new("Mag X", Unit::NanoTesla, field.x_nt() as f32),
+            Quantity::new("Mag Y", Unit::NanoTesla, field.y_nt() as f32),
+            Quantity::new("Mag Z", Unit::NanoTesla, field.z_nt() as f32),
+        ]))
+    }
+}
@@ -0,0 +1,27 @@
+//! MAX17043 fuel gauge.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use max170xx::{Error, Max17043};
+
+/// State of charge and voltage of the battery.
+impl<I2C, E> Sensor for Max17043<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "MAX17043"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let soc = self.soc()?;
+        let voltage = self.voltage()?;
+        Ok(quantities([
+            Quantity::new("Charge", Unit::Percent, soc),
+            Quantity::new("Voltage", Unit::Volt, voltage),
+        ]))
+    }
+}
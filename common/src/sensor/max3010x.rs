@@ -0,0 +1,28 @@
+//! MAX30102 pulse oximeter in heart-rate mode.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use max3010x::{marker, Error, Max3010x};
+
+/// Number of new samples in the FIFO and the last infrared sample.
+impl<I2C, E> Sensor for Max3010x<I2C, marker::ic::Max30102, marker::mode::HeartRate>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "MAX30102"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let mut data = [0; 16];
+        let count = self.read_fifo(&mut data)?;
+        let last = data[..usize::from(count)].last().copied().unwrap_or(0);
+        Ok(quantities([
+            Quantity::new("Samples", Unit::Count, f32::from(count)),
+            Quantity::new("IR", Unit::Count, last as f32),
+        ]))
+    }
+}
@@ -0,0 +1,23 @@
+//! MAX44009 ambient light sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::WriteRead;
+use max44009::{Error, Max44009};
+
+/// Illuminance.
+impl<I2C, E> Sensor for Max44009<I2C>
+where
+    I2C: WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "MAX44009"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let lux = self.read_lux()?;
+        Ok(quantities([Quantity::new("Light", Unit::Lux, lux)]))
+    }
+}
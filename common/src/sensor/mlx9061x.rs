@@ -0,0 +1,49 @@
+//! MLX90614 and MLX90615 infrared thermometers.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use mlx9061x::{ic, Error, Mlx9061x};
+
+/// Object and ambient temperature.
+impl<I2C, E> Sensor for Mlx9061x<I2C, ic::Mlx90614>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "MLX90614"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let object = self.object1_temperature()?;
+        let ambient = self.ambient_temperature()?;
+        Ok(quantities([
+            Quantity::new("Object", Unit::Celsius, object),
+            Quantity::new("Ambient", Unit::Celsius, ambient),
+        ]))
+    }
+}
+
+/// Object and ambient temperature.
+impl<I2C, E> Sensor for Mlx9061x<I2C, ic::Mlx90615>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "MLX90615"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let object = self.object_temperature()?;
+        let ambient = self.ambient_temperature()?;
+        Ok(quantities([
+            Quantity::new("Object", Unit::Celsius, object),
+            Quantity::new("Ambient", Unit::Celsius, ambient),
+        ]))
+    }
+}
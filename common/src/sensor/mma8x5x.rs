@@ -0,0 +1,37 @@
+//! MMA845x and MMA865x accelerometers in active mode.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::{Write, WriteRead};
+use mma8x5x::{ic, mode, Error, Mma8x5x};
+
+macro_rules! impl_sensor {
+    ($ic:ident, $name:expr) => {
+        /// Acceleration.
+        impl<I2C, E> Sensor for Mma8x5x<I2C, ic::$ic, mode::Active>
+        where
+            I2C: Write<Error = E> + WriteRead<Error = E>,
+            E: core::fmt::Debug,
+        {
+            type Error = Error<E>;
+
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn measure(&mut self) -> Result<Quantities, Self::Error> {
+                let accel = self.read()?;
+                Ok(quantities([
+                    Quantity::new("X", Unit::MilliG, accel.x * 1000.0),
+                    Quantity::new("Y", Unit::MilliG, accel.y * 1000.0),
+                    Quantity::new("Z", Unit::MilliG, accel.z * 1000.0),
+                ]))
+            }
+        }
+    };
+}
+
+impl_sensor!(Mma8451, "MMA8451");
+impl_sensor!(Mma8452, "MMA8452");
+impl_sensor!(Mma8453, "MMA8453");
+impl_sensor!(Mma8652, "MMA8652");
+impl_sensor!(Mma8653, "MMA8653");
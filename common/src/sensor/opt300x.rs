@@ -0,0 +1,24 @@
+//! OPT3001 ambient light sensor in one-shot mode.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use nb::block;
+use opt300x::{ic, mode, Error, Opt300x};
+
+/// Illuminance.
+impl<I2C, E> Sensor for Opt300x<I2C, ic::Opt3001, mode::OneShot>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "OPT3001"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let lux = block!(self.read_lux())?.result;
+        Ok(quantities([Quantity::new("Light", Unit::Lux, lux)]))
+    }
+}
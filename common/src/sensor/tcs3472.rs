@@ -0,0 +1,29 @@
+//! TCS3472 RGB color sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use tcs3472::{Error, Tcs3472};
+
+/// Red, green, blue and clear counts. The device and its RGB converter must
+/// be enabled.
+impl<I2C, E> Sensor for Tcs3472<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "TCS3472"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = self.read_all_channels()?;
+        Ok(quantities([
+            Quantity::new("Red", Unit::Count, f32::from(data.red)),
+            Quantity::new("Green", Unit::Count, f32::from(data.green)),
+            Quantity::new("Blue", Unit::Count, f32::from(data.blue)),
+            Quantity::new("Clear", Unit::Count, f32::from(data.clear)),
+        ]))
+    }
+}
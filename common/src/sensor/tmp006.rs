@@ -0,0 +1,31 @@
+//! TMP006 infrared thermometer.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use nb::block;
+use tmp006::{Error, Tmp006};
+
+/// Calibration factor of the sensor from the datasheet.
+const CALIBRATION_FACTOR: f64 = 6e-14;
+
+/// Object temperature.
+impl<I2C, E> Sensor for Tmp006<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "TMP006"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let kelvin = block!(self.read_object_temperature(CALIBRATION_FACTOR))?;
+        Ok(quantities([Quantity::new(
+            "Object",
+            Unit::Celsius,
+            (kelvin - 273.15) as f32,
+        )]))
+    }
+}
@@ -0,0 +1,27 @@
+//! TMP102 temperature sensor in continuous mode.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use tmp1x2::{marker::mode, Error, Tmp1x2};
+
+/// Temperature.
+impl<I2C, E> Sensor for Tmp1x2<I2C, mode::Continuous>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "TMP102"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let temperature = self.read_temperature()?;
+        Ok(quantities([Quantity::new(
+            "Temp",
+            Unit::Celsius,
+            temperature,
+        )]))
+    }
+}
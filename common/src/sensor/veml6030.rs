@@ -0,0 +1,23 @@
+//! VEML6030 and VEML7700 ambient light sensors.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal::i2c::I2c;
+use veml6030::{Error, Veml6030};
+
+/// Illuminance. The device must be enabled.
+impl<I2C, E> Sensor for Veml6030<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "VEML6030"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let lux = self.read_lux()?;
+        Ok(quantities([Quantity::new("Light", Unit::Lux, lux)]))
+    }
+}
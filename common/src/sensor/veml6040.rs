@@ -0,0 +1,28 @@
+//! VEML6040 RGBW color sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::WriteRead;
+use veml6040::{Error, Veml6040};
+
+/// Red, green, blue and white counts. The device must be enabled.
+impl<I2C, E> Sensor for Veml6040<I2C>
+where
+    I2C: WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "VEML6040"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = self.read_all_channels()?;
+        Ok(quantities([
+            Quantity::new("Red", Unit::Count, f32::from(data.red)),
+            Quantity::new("Green", Unit::Count, f32::from(data.green)),
+            Quantity::new("Blue", Unit::Count, f32::from(data.blue)),
+            Quantity::new("White", Unit::Count, f32::from(data.white)),
+        ]))
+    }
+}
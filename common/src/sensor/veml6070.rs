@@ -0,0 +1,27 @@
+//! VEML6070 UVA sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::Read;
+use veml6070::{Error, VEML6070};
+
+/// UVA counts. The device must be enabled.
+impl<I2C, E> Sensor for VEML6070<I2C>
+where
+    I2C: Read<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "VEML6070"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let uva = self.read_uv()?;
+        Ok(quantities([Quantity::new(
+            "UVA",
+            Unit::Count,
+            f32::from(uva),
+        )]))
+    }
+}
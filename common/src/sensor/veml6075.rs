@@ -0,0 +1,27 @@
+//! VEML6075 UVA and UVB sensor.
+
+use super::{quantities, Quantities, Quantity, Sensor, Unit};
+use embedded_hal_02::blocking::i2c::WriteRead;
+use veml6075::{Error, Veml6075};
+
+/// UVA and UVB counts and the UV index. The device must be enabled.
+impl<I2C, E> Sensor for Veml6075<I2C>
+where
+    I2C: WriteRead<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+
+    fn name(&self) -> &'static str {
+        "VEML6075"
+    }
+
+    fn measure(&mut self) -> Result<Quantities, Self::Error> {
+        let data = self.read()?;
+        Ok(quantities([
+            Quantity::new("UVA", Unit::Count, data.uva),
+            Quantity::new("UVB", Unit::Count, data.uvb),
+            Quantity::new("UV index", Unit::UvIndex, data.uv_index),
+        ]))
+    }
+}
@@ -0,0 +1,491 @@
+//! Melody playback on AD9833 waveform generators.
+//!
+//! The melody and the frequency calculations are always available. Playing
+//! it on the device requires the `ad983x` feature. The melodies are MIDI
+//! files read with the [`midi`](crate::midi) module:
+//!
+//! ```ignore
+//! # use driver_examples_common::{midi::{Melody, Smf}, synth::{NotePlayer, ODE_TO_JOY}};
+//! # fn example<SPI: embedded_hal::spi::SpiDevice>(spi: SPI) {
+//! use ad983x::Ad983x;
+//!
+//! let mut synth = Ad983x::new_ad9833(spi);
+//! synth.reset().unwrap();
+//! synth.enable().unwrap();
+//! let smf = Smf::parse(ODE_TO_JOY).unwrap();
+//! let mut player = NotePlayer::default();
+//! for step in Melody::new(&smf, 1, 1_000).unwrap() {
+//!     // wait for `step.ticks` ticks of a 1 kHz timer
+//!     player.play(&mut synth, step.note).unwrap();
+//! }
+//! # }
+//! ```
+//!
+//! Several AD9833 can play the notes of a [`Performance`](crate::midi::Performance)
+//! together, one voice each. [`Polyphony`] chooses the voice of every note
+//! and shapes its volume with an [`Adsr`] envelope scaled by the velocity,
+//! e.g. to set the position of a digital potentiometer in the output of
+//! each voice.
+
+#[cfg(feature = "ad983x")]
+use ad983x::{Ad983x, Error, FrequencyRegister, OutputWaveform};
+#[cfg(feature = "ad983x")]
+use embedded_hal::spi::SpiDevice;
+
+/// Frequency of the master clock of the usual AD9833 modules.
+pub const MCLK_HZ: f64 = 25_000_000.0;
+
+/// Frequency in Hz of a MIDI note number.
+pub fn note_frequency(note: u32) -> f64 {
+    libm::pow(2.0, (f64::from(note) - 69.0) / 12.0) * 440.0
+}
+
+/// Value of the 28-bit frequency register for a frequency in Hz.
+pub fn frequency_word(frequency_hz: f64) -> u32 {
+    (frequency_hz * f64::from(1 << 28) / MCLK_HZ) as u32
+}
+
+/// Value of the 12-bit phase register for a phase in degrees.
+pub fn phase_word(degrees: f64) -> u16 {
+    (libm::round(degrees * f64::from(1 << 12) / 360.0) as u16) & 0xFFF
+}
+
+/// Get the frequency register which is not the given one.
+#[cfg(feature = "ad983x")]
+pub fn get_opposite(register: FrequencyRegister) -> FrequencyRegister {
+    match register {
+        FrequencyRegister::F0 => FrequencyRegister::F1,
+        FrequencyRegister::F1 => FrequencyRegister::F0,
+    }
+}
+
+/// "Ode to Joy" as a format 1 MIDI file: the tempo in track 0 and the
+/// melody in track 1.
+pub const ODE_TO_JOY: &[u8] = include_bytes!("../songs/ode-to-joy.mid");
+
+/// "Ode to Joy" for two voices: the melody on channel 0 and a bass line on
+/// channel 1, both in track 1.
+pub const ODE_TO_JOY_DUET: &[u8] = include_bytes!("../songs/ode-to-joy-duet.mid");
+
+/// Plays notes on an AD9833 without glitches between them.
+#[cfg(feature = "ad983x")]
+#[derive(Debug)]
+pub struct NotePlayer {
+    current_register: FrequencyRegister,
+}
+
+#[cfg(feature = "ad983x")]
+impl Default for NotePlayer {
+    fn default() -> Self {
+        NotePlayer {
+            current_register: FrequencyRegister::F0,
+        }
+    }
+}
+
+#[cfg(feature = "ad983x")]
+impl NotePlayer {
+    /// Output a MIDI note, or nothing for `None`.
+    ///
+    /// To ensure a smooth transition, the frequency is set in the frequency
+    /// register that is not currently in use, and then the output is
+    /// switched to it.
+    pub fn play<SPI, IC, E>(
+        &mut self,
+        synth: &mut Ad983x<SPI, IC>,
+        note: Option<u8>,
+    ) -> Result<(), Error<E>>
+    where
+        SPI: SpiDevice<u8, Error = E>,
+    {
+        let word = note.map_or(0, |note| frequency_word(note_frequency(note.into())));
+        let opposite = get_opposite(self.current_register);
+        synth.set_frequency(opposite, word)?;
+        synth.select_frequency(opposite)?;
+        self.current_register = opposite;
+        Ok(())
+    }
+}
+
+/// Shape of the output of an AD9833.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// Sine wave of about 0.6 Vpp.
+    Sine,
+    /// Triangle wave of about 0.6 Vpp.
+    Triangle,
+    /// Square wave from the MSB of the DAC, which swings from 0 V to VDD and
+    /// is thus much louder than the others.
+    Square,
+}
+
+#[cfg(feature = "ad983x")]
+impl From<Waveform> for OutputWaveform {
+    fn from(waveform: Waveform) -> Self {
+        match waveform {
+            Waveform::Sine => OutputWaveform::Sinusoidal,
+            Waveform::Triangle => OutputWaveform::Triangle,
+            Waveform::Square => OutputWaveform::SquareMsbOfDac,
+        }
+    }
+}
+
+/// Highest volume level, e.g. the last position of an MCP42x
+/// potentiometer.
+pub const MAX_LEVEL: u8 = 255;
+
+/// Fixed point factor of the level of an envelope, so that it can change by
+/// less than one step per tick.
+const LEVEL_SCALE: u32 = 1 << 16;
+
+/// Shape of the volume of a note: how long it takes to rise to the full
+/// level when the key is pressed (attack), to fall to the level held while
+/// the key is pressed (decay and sustain) and to fade out when it is
+/// released (release).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adsr {
+    /// Milliseconds from silence to the full level.
+    pub attack_ms: u32,
+    /// Milliseconds from the full level to the sustain level.
+    pub decay_ms: u32,
+    /// Level while the key is held, up to [`MAX_LEVEL`].
+    pub sustain: u8,
+    /// Milliseconds from the level when the key is released to silence.
+    pub release_ms: u32,
+}
+
+impl Default for Adsr {
+    /// Short attack and decay with a soft release, which sounds like a
+    /// plucked string.
+    fn default() -> Self {
+        Adsr {
+            attack_ms: 10,
+            decay_ms: 150,
+            sustain: 160,
+            release_ms: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Volume of a voice over time following an [`Adsr`], advanced by the ticks
+/// of a timer.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    adsr: Adsr,
+    tick_hz: u32,
+    stage: Stage,
+    /// Level times [`LEVEL_SCALE`].
+    level: u32,
+    /// Change of the level per tick in the current stage.
+    rate: u32,
+    velocity: u8,
+}
+
+impl Envelope {
+    /// Create a silent envelope for a timer running at `tick_hz`.
+    pub fn new(adsr: Adsr, tick_hz: u32) -> Self {
+        Envelope {
+            adsr,
+            tick_hz,
+            stage: Stage::Idle,
+            level: 0,
+            rate: 0,
+            velocity: 0,
+        }
+    }
+
+    /// Start a note with a MIDI velocity (1-127), which scales the whole
+    /// envelope.
+    ///
+    /// The attack starts from the current level so that a note played
+    /// again before it has faded out does not click.
+    pub fn trigger(&mut self, velocity: u8) {
+        self.velocity = velocity.min(127);
+        self.enter(Stage::Attack);
+    }
+
+    /// Release the key: fade out from the current level.
+    pub fn release(&mut self) {
+        if self.stage != Stage::Idle {
+            self.enter(Stage::Release);
+        }
+    }
+
+    /// Whether the envelope has faded out completely.
+    pub fn is_idle(&self) -> bool {
+        self.stage == Stage::Idle
+    }
+
+    /// Current volume level, up to [`MAX_LEVEL`] for the full level at
+    /// velocity 127.
+    pub fn level(&self) -> u8 {
+        (self.level / LEVEL_SCALE * u32::from(self.velocity) / 127) as u8
+    }
+
+    /// Advance the envelope by one tick and return the new level.
+    pub fn tick(&mut self) -> u8 {
+        let full = u32::from(MAX_LEVEL) * LEVEL_SCALE;
+        let sustain = u32::from(self.adsr.sustain) * LEVEL_SCALE;
+        match self.stage {
+            Stage::Idle | Stage::Sustain => {}
+            Stage::Attack => {
+                self.level = (self.level + self.rate).min(full);
+                if self.level == full {
+                    self.enter(Stage::Decay);
+                }
+            }
+            Stage::Decay => {
+                self.level = self.level.saturating_sub(self.rate).max(sustain);
+                if self.level == sustain {
+                    self.enter(Stage::Sustain);
+                }
+            }
+            Stage::Release => {
+                self.level = self.level.saturating_sub(self.rate);
+                if self.level == 0 {
+                    self.enter(Stage::Idle);
+                }
+            }
+        }
+        self.level()
+    }
+
+    fn enter(&mut self, stage: Stage) {
+        let full = u32::from(MAX_LEVEL) * LEVEL_SCALE;
+        let sustain = u32::from(self.adsr.sustain) * LEVEL_SCALE;
+        self.rate = match stage {
+            Stage::Idle | Stage::Sustain => 0,
+            Stage::Attack => full / self.ticks(self.adsr.attack_ms),
+            Stage::Decay => (full - sustain) / self.ticks(self.adsr.decay_ms),
+            Stage::Release => (self.level / self.ticks(self.adsr.release_ms)).max(1),
+        };
+        self.stage = stage;
+    }
+
+    /// Ticks of the timer in some milliseconds, at least one.
+    fn ticks(&self, ms: u32) -> u32 {
+        (u64::from(ms) * u64::from(self.tick_hz) / 1_000).max(1) as u32
+    }
+}
+
+/// Assigns the notes to a fixed number of voices, e.g. one AD9833 each, and
+/// shapes their volume with an [`Envelope`] per voice.
+///
+/// A new note goes to a voice which is not playing, or else to the one
+/// which has been fading out for the longest time. If all the keys are
+/// held, the oldest note is cut off:
+///
+/// ```
+/// # use driver_examples_common::synth::{Adsr, Polyphony};
+/// let mut voices = Polyphony::<2>::new(Adsr::default(), 1_000);
+/// assert_eq!(0, voices.note_on(60, 100));
+/// assert_eq!(1, voices.note_on(64, 100));
+/// // Set the frequency of voice 0 to note 67.
+/// assert_eq!(0, voices.note_on(67, 100));
+/// assert_eq!(Some(1), voices.note_off(64));
+/// // On every tick, set the volume of the voices whose level changed.
+/// let levels: [Option<u8>; 2] = voices.tick();
+/// # assert!(levels[0].is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Polyphony<const N: usize> {
+    envelopes: [Envelope; N],
+    /// Note of every voice, kept while it fades out.
+    notes: [Option<u8>; N],
+    held: [bool; N],
+    /// When each voice was last started or released, to find the oldest.
+    changed: [u32; N],
+    count: u32,
+    levels: [u8; N],
+}
+
+impl<const N: usize> Polyphony<N> {
+    /// Create `N` silent voices with the same envelope, for a timer running
+    /// at `tick_hz`.
+    pub fn new(adsr: Adsr, tick_hz: u32) -> Self {
+        Polyphony {
+            envelopes: core::array::from_fn(|_| Envelope::new(adsr, tick_hz)),
+            notes: [None; N],
+            held: [false; N],
+            changed: [0; N],
+            count: 0,
+            levels: [0; N],
+        }
+    }
+
+    /// Press a key with a MIDI velocity and return the voice which plays
+    /// it. Its frequency must be set to the note.
+    pub fn note_on(&mut self, note: u8, velocity: u8) -> usize {
+        let voice = match self.notes.iter().position(|n| *n == Some(note)) {
+            Some(voice) => voice,
+            None => (0..N)
+                .min_by_key(|&voice| {
+                    let busy = !self.envelopes[voice].is_idle();
+                    (self.held[voice], busy, self.changed[voice])
+                })
+                .expect("there must be at least one voice"),
+        };
+        self.notes[voice] = Some(note);
+        self.held[voice] = true;
+        self.envelopes[voice].trigger(velocity);
+        self.touch(voice);
+        voice
+    }
+
+    /// Release a key and return the voice which was playing it, if any.
+    pub fn note_off(&mut self, note: u8) -> Option<usize> {
+        let voice = (0..N).find(|&voice| self.held[voice] && self.notes[voice] == Some(note))?;
+        self.held[voice] = false;
+        self.envelopes[voice].release();
+        self.touch(voice);
+        Some(voice)
+    }
+
+    /// Release all the keys, e.g. at the end of a song.
+    pub fn release_all(&mut self) {
+        for voice in 0..N {
+            if let Some(note) = self.notes[voice] {
+                self.note_off(note);
+            }
+        }
+    }
+
+    /// Note of every voice, which may be fading out.
+    pub fn notes(&self) -> [Option<u8>; N] {
+        self.notes
+    }
+
+    /// Whether all the voices have faded out.
+    pub fn is_idle(&self) -> bool {
+        self.envelopes.iter().all(Envelope::is_idle)
+    }
+
+    /// Advance the envelopes by one tick.
+    ///
+    /// Returns the new level of the voices whose level changed, so that
+    /// only those have to be written to the volume control.
+    pub fn tick(&mut self) -> [Option<u8>; N] {
+        core::array::from_fn(|voice| {
+            let level = self.envelopes[voice].tick();
+            if self.envelopes[voice].is_idle() {
+                self.notes[voice] = None;
+            }
+            (level != core::mem::replace(&mut self.levels[voice], level)).then_some(level)
+        })
+    }
+
+    fn touch(&mut self, voice: usize) {
+        self.count += 1;
+        self.changed[voice] = self.count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ad983x")]
+    #[test]
+    fn opposite_register() {
+        assert_eq!(FrequencyRegister::F1, get_opposite(FrequencyRegister::F0));
+        assert_eq!(FrequencyRegister::F0, get_opposite(FrequencyRegister::F1));
+    }
+
+    #[test]
+    fn a4_is_440_hz() {
+        assert!((note_frequency(69) - 440.0).abs() < 1e-9);
+        assert!((note_frequency(81) - 880.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn frequency_word_for_1_khz() {
+        // 1000 * 2^28 / 25 MHz = 10737.4
+        assert_eq!(10737, frequency_word(1000.0));
+    }
+
+    #[test]
+    fn phase_word_wraps_at_360_degrees() {
+        assert_eq!(0, phase_word(0.0));
+        assert_eq!(1024, phase_word(90.0));
+        assert_eq!(4095, phase_word(359.95));
+        assert_eq!(0, phase_word(360.0));
+    }
+
+    #[test]
+    fn envelope_follows_adsr() {
+        let adsr = Adsr {
+            attack_ms: 4,
+            decay_ms: 4,
+            sustain: 128,
+            release_ms: 8,
+        };
+        let mut envelope = Envelope::new(adsr, 1_000);
+        envelope.trigger(127);
+        let held: [u8; 9] = core::array::from_fn(|_| envelope.tick());
+        assert_eq!([63, 127, 191, 255, 223, 191, 159, 128, 128], held);
+        envelope.release();
+        let released: [u8; 8] = core::array::from_fn(|_| envelope.tick());
+        assert_eq!([112, 96, 80, 64, 48, 32, 16, 0], released);
+        assert!(envelope.is_idle());
+    }
+
+    #[test]
+    fn velocity_scales_envelope() {
+        let adsr = Adsr {
+            attack_ms: 0,
+            sustain: MAX_LEVEL,
+            ..Adsr::default()
+        };
+        let mut envelope = Envelope::new(adsr, 1_000);
+        envelope.trigger(64);
+        assert_eq!(128, envelope.tick());
+        assert_eq!(128, envelope.tick());
+    }
+
+    #[test]
+    fn assigns_notes_to_free_voices_first() {
+        let mut voices = Polyphony::<2>::new(Adsr::default(), 1_000);
+        assert_eq!(0, voices.note_on(60, 100));
+        assert_eq!(1, voices.note_on(64, 100));
+        assert_eq!(Some(0), voices.note_off(60));
+        assert_eq!(None, voices.note_off(60));
+        // Voice 0 fades out while voice 1 is still held.
+        assert_eq!(0, voices.note_on(67, 100));
+        // Both held: the oldest note is cut off.
+        assert_eq!(1, voices.note_on(72, 100));
+        assert_eq!([Some(67), Some(72)], voices.notes());
+        // A note played again keeps its voice.
+        assert_eq!(0, voices.note_on(67, 100));
+    }
+
+    #[test]
+    fn reports_changed_levels_until_silent() {
+        let adsr = Adsr {
+            attack_ms: 2,
+            decay_ms: 0,
+            sustain: MAX_LEVEL,
+            release_ms: 2,
+        };
+        let mut voices = Polyphony::<2>::new(adsr, 1_000);
+        voices.note_on(60, 127);
+        assert_eq!([Some(127), None], voices.tick());
+        assert_eq!([Some(255), None], voices.tick());
+        assert_eq!([None, None], voices.tick());
+        voices.release_all();
+        assert_eq!([Some(127), None], voices.tick());
+        assert!(!voices.is_idle());
+        assert_eq!([Some(0), None], voices.tick());
+        assert!(voices.is_idle());
+        assert_eq!([None, None], voices.notes());
+    }
+}
@@ -0,0 +1,214 @@
+//! Framed binary telemetry for the streaming examples.
+//!
+//! Every [`Sample`] tags a value with the device, the channel, the unit and
+//! a monotonic timestamp from a [`Clock`](crate::clock::Clock). On the wire
+//! each sample is a frame:
+//!
+//! 1. The sample serialized with [postcard].
+//! 2. The CRC-16/IBM-SDLC (X.25) of it, little endian.
+//! 3. Both encoded with COBS, so that they contain no zero bytes.
+//! 4. A zero byte which ends the frame.
+//!
+//! A receiver which starts listening in the middle of a frame or loses some
+//! bytes resynchronizes on the next zero, and corrupted frames are dropped
+//! thanks to the CRC. The `telemetry` host crate decodes the frames on the
+//! computer:
+//!
+//! ```
+//! # use driver_examples_common::{sensor::Unit, telemetry::{self, Sample, MAX_FRAME_LEN}};
+//! let sample = Sample {
+//!     timestamp_ms: 1_500,
+//!     device: "LM75",
+//!     channel: "Temp",
+//!     unit: Unit::Celsius,
+//!     value: 21.5,
+//! };
+//! let mut buffer = [0; MAX_FRAME_LEN];
+//! let frame = telemetry::encode(&sample, &mut buffer).unwrap();
+//! // serial.bwrite_all(frame).unwrap();
+//! # assert_eq!(Some(&0), frame.last());
+//! ```
+//!
+//! [postcard]: https://docs.rs/postcard
+
+use crate::sensor::{Quantity, Unit};
+use core::fmt;
+use crc::{Crc, CRC_16_IBM_SDLC};
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a serialized sample.
+///
+/// Enough for device and channel names of up to 20 characters each.
+pub const MAX_SAMPLE_LEN: usize = 56;
+
+const CRC_LEN: usize = 2;
+
+/// Maximum length of a frame, including the zero at the end.
+pub const MAX_FRAME_LEN: usize = cobs::max_encoding_length(MAX_SAMPLE_LEN + CRC_LEN) + 1;
+
+/// Byte which ends every frame.
+pub const DELIMITER: u8 = 0;
+
+const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_SDLC);
+
+/// Value measured on a channel of a device at some point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sample<'a> {
+    /// Milliseconds since the board started.
+    pub timestamp_ms: u32,
+    /// Name of the device, e.g. `"LM75"`.
+    pub device: &'a str,
+    /// Name of the channel of the device, e.g. `"Temp"` or `"X"`.
+    pub channel: &'a str,
+    /// Unit of the value.
+    pub unit: Unit,
+    /// Measured value.
+    pub value: f32,
+}
+
+impl Sample<'static> {
+    /// Tag a quantity measured through the [`Sensor`](crate::sensor::Sensor)
+    /// trait with the device and time.
+    pub fn of(timestamp_ms: u32, device: &'static str, quantity: &Quantity) -> Self {
+        Sample {
+            timestamp_ms,
+            device,
+            channel: quantity.name,
+            unit: quantity.unit,
+            value: quantity.value,
+        }
+    }
+}
+
+/// Error encoding or decoding a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// The sample does not fit in [`MAX_SAMPLE_LEN`] bytes.
+    TooLong,
+    /// The frame is not valid COBS.
+    Cobs,
+    /// The CRC does not match, so the frame was corrupted.
+    Crc,
+    /// The frame does not contain a sample.
+    Sample,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FrameError::TooLong => "sample too long",
+            FrameError::Cobs => "invalid COBS encoding",
+            FrameError::Crc => "CRC mismatch",
+            FrameError::Sample => "invalid sample",
+        })
+    }
+}
+
+/// Encode a sample into a frame in `buffer` and return the frame.
+pub fn encode<'b>(
+    sample: &Sample<'_>,
+    buffer: &'b mut [u8; MAX_FRAME_LEN],
+) -> Result<&'b [u8], FrameError> {
+    let mut message = [0; MAX_SAMPLE_LEN + CRC_LEN];
+    let len = postcard::to_slice(sample, &mut message[..MAX_SAMPLE_LEN])
+        .map_err(|_| FrameError::TooLong)?
+        .len();
+    let crc = CRC.checksum(&message[..len]);
+    message[len..len + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+    let encoded = cobs::encode(&message[..len + CRC_LEN], buffer);
+    buffer[encoded] = DELIMITER;
+    Ok(&buffer[..=encoded])
+}
+
+/// Decode a frame without the zero at the end.
+///
+/// The frame is decoded in place, so the strings of the sample borrow from
+/// it.
+pub fn decode(frame: &mut [u8]) -> Result<Sample<'_>, FrameError> {
+    let len = cobs::decode_in_place(frame).map_err(|_| FrameError::Cobs)?;
+    let Some(len) = len.checked_sub(CRC_LEN) else {
+        return Err(FrameError::Sample);
+    };
+    let (message, crc) = frame[..len + CRC_LEN].split_at(len);
+    if CRC.checksum(message).to_le_bytes() != crc {
+        return Err(FrameError::Crc);
+    }
+    postcard::from_bytes(message).map_err(|_| FrameError::Sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: Sample<'static> = Sample {
+        timestamp_ms: 123_456,
+        device: "MMA8452",
+        channel: "X",
+        unit: Unit::MilliG,
+        value: -981.5,
+    };
+
+    fn frame_of(sample: &Sample<'_>) -> ([u8; MAX_FRAME_LEN], usize) {
+        let mut buffer = [0; MAX_FRAME_LEN];
+        let len = encode(sample, &mut buffer).unwrap().len();
+        (buffer, len)
+    }
+
+    #[test]
+    fn round_trip() {
+        let (mut buffer, len) = frame_of(&SAMPLE);
+        assert_eq!(DELIMITER, buffer[len - 1]);
+        assert!(!buffer[..len - 1].contains(&DELIMITER));
+        assert_eq!(Ok(SAMPLE), decode(&mut buffer[..len - 1]));
+    }
+
+    #[test]
+    fn sample_of_quantity() {
+        let quantity = Quantity::new("Temp", Unit::Celsius, 21.5);
+        let sample = Sample::of(10, "LM75", &quantity);
+        assert_eq!(
+            ("LM75", "Temp", Unit::Celsius),
+            (sample.device, sample.channel, sample.unit)
+        );
+        assert_eq!((10, 21.5), (sample.timestamp_ms, sample.value));
+    }
+
+    #[test]
+    fn longest_names_fit() {
+        let name = "12345678901234567890";
+        let sample = Sample {
+            timestamp_ms: u32::MAX,
+            device: name,
+            channel: name,
+            ..SAMPLE
+        };
+        let (mut buffer, len) = frame_of(&sample);
+        assert_eq!(Ok(sample), decode(&mut buffer[..len - 1]));
+    }
+
+    #[test]
+    fn too_long() {
+        let sample = Sample {
+            device: "a device with a very long name",
+            channel: "and a long channel name too",
+            ..SAMPLE
+        };
+        let mut buffer = [0; MAX_FRAME_LEN];
+        assert_eq!(Err(FrameError::TooLong), encode(&sample, &mut buffer));
+    }
+
+    #[test]
+    fn corrupted_frame() {
+        let (mut buffer, len) = frame_of(&SAMPLE);
+        buffer[len / 2] ^= 0x10;
+        assert_eq!(Err(FrameError::Crc), decode(&mut buffer[..len - 1]));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        // Frames of an empty message and of a single byte.
+        assert_eq!(Err(FrameError::Sample), decode(&mut [0x01]));
+        assert_eq!(Err(FrameError::Sample), decode(&mut [0x02, 0x05]));
+    }
+}
@@ -0,0 +1,462 @@
+//! Multi-line text output on an SSD1306 OLED display.
+//!
+//! [`TextPanel`] sends the contents with the blocking display interface.
+//! With the `async` feature, [`AsyncTextPanel`] offers the same rows but
+//! initializes and flushes the display with `.await`.
+
+#[cfg(feature = "async")]
+use crate::display::AsyncWriteOnlyDataCommand;
+use crate::display::{DisplayError, WriteOnlyDataCommand};
+use crate::reading::{Failure, Reading};
+use core::fmt::{self, Write};
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle},
+    text::{Baseline, Text},
+};
+use ssd1306::{mode::BufferedGraphicsMode, prelude::*, Ssd1306};
+#[cfg(feature = "async")]
+use ssd1306::{mode::BufferedGraphicsModeAsync, size::DisplaySizeAsync, Ssd1306Async};
+
+/// Number of text rows shown on the panel.
+pub const ROWS: usize = 4;
+
+/// Number of characters that fit in a row of a 128 pixel wide display.
+pub const COLUMNS: usize = 21;
+
+/// Contents of one row of text.
+///
+/// The capacity is given in bytes and leaves room for some non-ASCII
+/// characters like `º`.
+pub type Row = heapless::String<32>;
+
+const ROW_PITCH: i32 = 16;
+const TITLED_ROW_PITCH: i32 = 12;
+const TITLE_SEPARATOR_Y: i32 = 11;
+
+/// Text rows drawn on an SSD1306 display in buffered graphics mode.
+///
+/// Rows are written with `core::fmt` and anything which does not fit is
+/// cut off. The panel can optionally show a title above the rows, which is
+/// useful when cycling through several pages of information.
+///
+/// Nothing is sent to the display until [`flush`](TextPanel::flush) is
+/// called and then only if the contents have changed since the last time.
+///
+/// ```no_run
+/// # use driver_examples_common::{Display, TextPanel, WriteOnlyDataCommand};
+/// # fn example<DI: WriteOnlyDataCommand>(display: Display<DI>) {
+/// use core::fmt::Write;
+///
+/// let mut panel = TextPanel::new(display);
+/// panel.init().unwrap();
+/// panel.page("LM75");
+/// write!(panel.row(0), "Temperature: {:.1}C", 21.5).unwrap();
+/// panel.flush().unwrap();
+/// # }
+/// ```
+pub struct TextPanel<DI, SIZE: DisplaySize> {
+    display: Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>,
+    contents: Contents,
+}
+
+impl<DI, SIZE> TextPanel<DI, SIZE>
+where
+    DI: WriteOnlyDataCommand,
+    SIZE: DisplaySize,
+{
+    /// Create a new panel drawing on the given display.
+    ///
+    /// The display is not initialized. Call [`init`](TextPanel::init)
+    /// before using it unless this has already been done.
+    pub fn new(display: Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>>) -> Self {
+        TextPanel {
+            display,
+            contents: Contents::new(),
+        }
+    }
+
+    /// Initialize the display and clear it.
+    pub fn init(&mut self) -> Result<(), DisplayError> {
+        self.display.init()?;
+        self.contents.stale = true;
+        self.flush().map(|_| ())
+    }
+
+    /// Start a new page with the given title. All rows are cleared.
+    pub fn page(&mut self, title: &str) {
+        self.contents.page(title);
+    }
+
+    /// Remove the title and clear all rows.
+    pub fn clear(&mut self) {
+        self.contents.clear();
+    }
+
+    /// Clear all rows, keeping the title.
+    pub fn clear_rows(&mut self) {
+        self.contents.clear_rows();
+    }
+
+    /// Get a writer for the row with the given index.
+    ///
+    /// The row is cleared first. A newline continues on the next row.
+    /// Text which does not fit in the row or goes beyond the last row
+    /// is discarded, so writing never fails.
+    pub fn row(&mut self, index: usize) -> RowWriter<'_> {
+        self.contents.row(index)
+    }
+
+    /// Replace the contents of the row with the given index.
+    pub fn set_row(&mut self, index: usize, text: &str) {
+        let _ = self.row(index).write_str(text);
+    }
+
+    /// Show a sensor reading and flush.
+    ///
+    /// A value is shown by `show`, which writes it to the rows. A failure
+    /// is shown on an error screen instead, see
+    /// [`show_failure`](TextPanel::show_failure). The panel is cleared
+    /// when going back from the error screen to the value.
+    ///
+    /// ```no_run
+    /// # use driver_examples_common::{reading::Reading, Display, TextPanel, WriteOnlyDataCommand};
+    /// # fn example<DI: WriteOnlyDataCommand>(mut panel: TextPanel<DI, ssd1306::prelude::DisplaySize128x64>, reading: Reading<f32>) {
+    /// use core::fmt::Write;
+    ///
+    /// panel
+    ///     .show_reading(&reading, |panel, temperature| {
+    ///         write!(panel.row(0), "Temperature: {:.1}C", temperature).unwrap();
+    ///     })
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn show_reading<T, F>(
+        &mut self,
+        reading: &Reading<T>,
+        show: F,
+    ) -> Result<bool, DisplayError>
+    where
+        F: FnOnce(&mut Self, &T),
+    {
+        match reading {
+            Ok(value) => {
+                self.contents.leave_failure();
+                show(self, value);
+            }
+            Err(failure) => self.show_failure(failure),
+        }
+        self.flush()
+    }
+
+    /// Replace the contents with an error screen naming the failing device,
+    /// the driver error and the number of consecutive failures.
+    ///
+    /// The screen is shown on the next flush.
+    pub fn show_failure(&mut self, failure: &Failure) {
+        self.contents.show_failure(failure);
+    }
+
+    /// Draw the contents and send them to the display if they have changed
+    /// since the last flush.
+    ///
+    /// Returns whether the display was updated.
+    pub fn flush(&mut self) -> Result<bool, DisplayError> {
+        if !self.contents.is_changed() {
+            return Ok(false);
+        }
+        self.display.clear_buffer();
+        self.contents.draw(&mut self.display)?;
+        self.display.flush()?;
+        self.contents.mark_shown();
+        Ok(true)
+    }
+
+    /// Access the display, for example to draw something else on it.
+    ///
+    /// The next flush will redraw the panel.
+    pub fn display(&mut self) -> &mut Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>> {
+        self.contents.stale = true;
+        &mut self.display
+    }
+
+    /// Destroy the panel and return the display.
+    pub fn release(self) -> Ssd1306<DI, SIZE, BufferedGraphicsMode<SIZE>> {
+        self.display
+    }
+}
+
+/// Text rows drawn on an SSD1306 display in async buffered graphics mode.
+///
+/// This is the same as [`TextPanel`] except that initializing and flushing
+/// the display has to be awaited, so that other tasks keep running while
+/// the contents are sent.
+///
+/// ```no_run
+/// # use driver_examples_common::{AsyncDisplay, AsyncTextPanel, AsyncWriteOnlyDataCommand};
+/// # async fn example<DI: AsyncWriteOnlyDataCommand>(display: AsyncDisplay<DI>) {
+/// use core::fmt::Write;
+///
+/// let mut panel = AsyncTextPanel::new(display);
+/// panel.init().await.unwrap();
+/// write!(panel.row(0), "Temperature: {:.1}C", 21.5).unwrap();
+/// panel.flush().await.unwrap();
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub struct AsyncTextPanel<DI, SIZE: DisplaySizeAsync> {
+    display: Ssd1306Async<DI, SIZE, BufferedGraphicsModeAsync<SIZE>>,
+    contents: Contents,
+}
+
+#[cfg(feature = "async")]
+impl<DI, SIZE> AsyncTextPanel<DI, SIZE>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    SIZE: DisplaySizeAsync,
+{
+    /// Create a new panel drawing on the given display.
+    ///
+    /// The display is not initialized. Call [`init`](AsyncTextPanel::init)
+    /// before using it unless this has already been done.
+    pub fn new(display: Ssd1306Async<DI, SIZE, BufferedGraphicsModeAsync<SIZE>>) -> Self {
+        AsyncTextPanel {
+            display,
+            contents: Contents::new(),
+        }
+    }
+
+    /// Initialize the display and clear it.
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        self.display.init().await?;
+        self.contents.stale = true;
+        self.flush().await.map(|_| ())
+    }
+
+    /// Start a new page with the given title. All rows are cleared.
+    pub fn page(&mut self, title: &str) {
+        self.contents.page(title);
+    }
+
+    /// Remove the title and clear all rows.
+    pub fn clear(&mut self) {
+        self.contents.clear();
+    }
+
+    /// Clear all rows, keeping the title.
+    pub fn clear_rows(&mut self) {
+        self.contents.clear_rows();
+    }
+
+    /// Get a writer for the row with the given index.
+    ///
+    /// See [`TextPanel::row`].
+    pub fn row(&mut self, index: usize) -> RowWriter<'_> {
+        self.contents.row(index)
+    }
+
+    /// Replace the contents of the row with the given index.
+    pub fn set_row(&mut self, index: usize, text: &str) {
+        let _ = self.row(index).write_str(text);
+    }
+
+    /// Show a sensor reading and flush.
+    ///
+    /// See [`TextPanel::show_reading`].
+    pub async fn show_reading<T, F>(
+        &mut self,
+        reading: &Reading<T>,
+        show: F,
+    ) -> Result<bool, DisplayError>
+    where
+        F: FnOnce(&mut Self, &T),
+    {
+        match reading {
+            Ok(value) => {
+                self.contents.leave_failure();
+                show(self, value);
+            }
+            Err(failure) => self.show_failure(failure),
+        }
+        self.flush().await
+    }
+
+    /// Replace the contents with an error screen.
+    ///
+    /// See [`TextPanel::show_failure`].
+    pub fn show_failure(&mut self, failure: &Failure) {
+        self.contents.show_failure(failure);
+    }
+
+    /// Draw the contents and send them to the display if they have changed
+    /// since the last flush.
+    ///
+    /// Returns whether the display was updated.
+    pub async fn flush(&mut self) -> Result<bool, DisplayError> {
+        if !self.contents.is_changed() {
+            return Ok(false);
+        }
+        self.display.clear_buffer();
+        self.contents.draw(&mut self.display)?;
+        self.display.flush().await?;
+        self.contents.mark_shown();
+        Ok(true)
+    }
+
+    /// Access the display, for example to draw something else on it.
+    ///
+    /// The next flush will redraw the panel.
+    pub fn display(&mut self) -> &mut Ssd1306Async<DI, SIZE, BufferedGraphicsModeAsync<SIZE>> {
+        self.contents.stale = true;
+        &mut self.display
+    }
+
+    /// Destroy the panel and return the display.
+    pub fn release(self) -> Ssd1306Async<DI, SIZE, BufferedGraphicsModeAsync<SIZE>> {
+        self.display
+    }
+}
+
+/// Title and rows of a panel, independent of how they are sent to the
+/// display.
+struct Contents {
+    title: Option<Row>,
+    rows: [Row; ROWS],
+    shown_title: Option<Row>,
+    shown_rows: [Row; ROWS],
+    stale: bool,
+    showing_failure: bool,
+}
+
+impl Contents {
+    fn new() -> Self {
+        Contents {
+            title: None,
+            rows: Default::default(),
+            shown_title: None,
+            shown_rows: Default::default(),
+            stale: true,
+            showing_failure: false,
+        }
+    }
+
+    fn page(&mut self, title: &str) {
+        let mut row = Row::new();
+        push_truncated(&mut row, title);
+        self.title = Some(row);
+        self.clear_rows();
+    }
+
+    fn clear(&mut self) {
+        self.title = None;
+        self.clear_rows();
+    }
+
+    fn clear_rows(&mut self) {
+        for row in self.rows.iter_mut() {
+            row.clear();
+        }
+    }
+
+    fn row(&mut self, index: usize) -> RowWriter<'_> {
+        if let Some(row) = self.rows.get_mut(index) {
+            row.clear();
+        }
+        RowWriter {
+            rows: &mut self.rows,
+            index,
+        }
+    }
+
+    fn show_failure(&mut self, failure: &Failure) {
+        let mut title = Row::new();
+        let _ = write!(title, "{} ERROR", failure.device);
+        self.page(&title);
+        let _ = write!(self.row(0), "{}", failure.variant());
+        let _ = write!(self.row(1), "{}", failure.details());
+        let _ = write!(self.row(2), "Failed {} times", failure.consecutive);
+        let _ = self.row(3).write_str("Retrying...");
+        self.showing_failure = true;
+    }
+
+    /// Clear the error screen, if shown, before showing a value again.
+    fn leave_failure(&mut self) {
+        if self.showing_failure {
+            self.clear();
+            self.showing_failure = false;
+        }
+    }
+
+    fn is_changed(&self) -> bool {
+        self.stale || self.title != self.shown_title || self.rows != self.shown_rows
+    }
+
+    fn mark_shown(&mut self) {
+        self.shown_title = self.title.clone();
+        self.shown_rows = self.rows.clone();
+        self.stale = false;
+    }
+
+    /// Draw the title and the rows on a cleared display buffer.
+    fn draw<D>(&self, display: &mut D) -> Result<(), DisplayError>
+    where
+        D: DrawTarget<Color = BinaryColor, Error = DisplayError>,
+    {
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+        let (top, pitch) = match &self.title {
+            Some(title) => {
+                Text::with_baseline(title, Point::zero(), style, Baseline::Top).draw(display)?;
+                let width = display.bounding_box().size.width as i32;
+                Line::new(
+                    Point::new(0, TITLE_SEPARATOR_Y),
+                    Point::new(width - 1, TITLE_SEPARATOR_Y),
+                )
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)?;
+                (TITLE_SEPARATOR_Y + 2, TITLED_ROW_PITCH)
+            }
+            None => (0, ROW_PITCH),
+        };
+        for (i, row) in self.rows.iter().enumerate() {
+            let position = Point::new(0, top + i as i32 * pitch);
+            Text::with_baseline(row, position, style, Baseline::Top).draw(display)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer for a panel row returned by [`TextPanel::row`].
+pub struct RowWriter<'a> {
+    rows: &'a mut [Row; ROWS],
+    index: usize,
+}
+
+impl fmt::Write for RowWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            if let Some(row) = self.rows.get_mut(self.index) {
+                push_truncated(row, first);
+            }
+        }
+        for line in lines {
+            self.index += 1;
+            if let Some(row) = self.rows.get_mut(self.index) {
+                row.clear();
+                push_truncated(row, line);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn push_truncated(row: &mut Row, text: &str) {
+    let free = COLUMNS.saturating_sub(row.chars().count());
+    for c in text.chars().take(free) {
+        if row.push(c).is_err() {
+            break;
+        }
+    }
+}
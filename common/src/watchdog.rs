@@ -0,0 +1,235 @@
+//! Per-task feeding of a hardware watchdog and the cause of the last reset.
+//!
+//! A program which runs for days can hang in a driver, e.g. waiting for a
+//! measurement which never comes. A hardware watchdog resets the chip if it
+//! is not fed in time, but feeding it from a single place only proves that
+//! this place is still reached. With a [`Supervisor`], every subsystem of
+//! the program registers a [`Task`] and checks in when it has done its job.
+//! The watchdog is only fed once all tasks have checked in:
+//!
+//! ```
+//! # use driver_examples_common::watchdog::Supervisor;
+//! let mut supervisor = Supervisor::new();
+//! let sensor = supervisor.register("CCS811").unwrap();
+//! let display = supervisor.register("display").unwrap();
+//! // In the main loop:
+//! supervisor.check_in(sensor);
+//! assert!(!supervisor.all_checked_in());
+//! supervisor.check_in(display);
+//! if supervisor.all_checked_in() {
+//!     // feed the watchdog
+//! }
+//! ```
+//!
+//! After a reset, the [`ResetCause`] tells whether the watchdog had to
+//! recover the board.
+
+/// Maximum number of tasks which can be registered.
+pub const MAX_TASKS: usize = 8;
+
+/// Handle of a task registered with a [`Supervisor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Task(u8);
+
+/// Keeps track of which tasks have checked in since the watchdog was last
+/// fed.
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    names: heapless::Vec<&'static str, MAX_TASKS>,
+    checked_in: u8,
+    /// Missing tasks which have already been returned by
+    /// [`newly_missing`](Supervisor::newly_missing).
+    reported: u8,
+}
+
+impl Supervisor {
+    /// Create a supervisor without tasks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a task which must check in before every feed.
+    ///
+    /// Returns `None` if [`MAX_TASKS`] tasks are already registered.
+    pub fn register(&mut self, name: &'static str) -> Option<Task> {
+        let index = self.names.len() as u8;
+        self.names.push(name).ok()?;
+        Some(Task(index))
+    }
+
+    /// Tell that the task is alive.
+    pub fn check_in(&mut self, task: Task) {
+        self.checked_in |= 1 << task.0;
+    }
+
+    /// Whether every registered task has checked in since the last call
+    /// to [`restart`](Supervisor::restart).
+    pub fn all_checked_in(&self) -> bool {
+        self.checked_in.count_ones() as usize == self.names.len()
+    }
+
+    /// Forget the check-ins, once the watchdog has been fed.
+    pub fn restart(&mut self) {
+        self.checked_in = 0;
+        self.reported = 0;
+    }
+
+    /// Names of the tasks which have not checked in yet.
+    pub fn missing(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.names_in(!self.checked_in)
+    }
+
+    /// Names of the tasks which have not checked in yet, except those
+    /// already returned since the last [`restart`](Supervisor::restart).
+    ///
+    /// This reports each stall once, however often the feed is attempted.
+    pub fn newly_missing(&mut self) -> impl Iterator<Item = &'static str> + '_ {
+        let missing = !self.checked_in & !self.reported;
+        self.reported |= missing;
+        self.names_in(missing)
+    }
+
+    fn names_in(&self, tasks: u8) -> impl Iterator<Item = &'static str> + '_ {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| tasks & (1 << i) != 0)
+            .map(|(_, name)| *name)
+    }
+}
+
+/// Cause of the last reset of an STM32, from the flags in the RCC_CSR
+/// register.
+///
+/// The flags are in the same place on the STM32F1 and STM32F3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ResetCause {
+    /// The board was powered up.
+    PowerOn,
+    /// The reset pin was pulled low, e.g. with the reset button.
+    Pin,
+    /// The program requested a reset.
+    Software,
+    /// The independent watchdog was not fed in time.
+    IndependentWatchdog,
+    /// The window watchdog was not fed in time.
+    WindowWatchdog,
+    /// The chip entered a low-power mode which is not allowed.
+    LowPower,
+    /// None of the known flags is set.
+    Unknown,
+}
+
+const PINRSTF: u32 = 1 << 26;
+const PORRSTF: u32 = 1 << 27;
+const SFTRSTF: u32 = 1 << 28;
+const IWDGRSTF: u32 = 1 << 29;
+const WWDGRSTF: u32 = 1 << 30;
+const LPWRRSTF: u32 = 1 << 31;
+
+impl ResetCause {
+    /// Decode the value of the RCC_CSR register.
+    ///
+    /// A reset which is not a power-on reset also drives the reset pin, so
+    /// several flags can be set at once. The most specific one is returned.
+    pub fn from_csr(csr: u32) -> Self {
+        [
+            (IWDGRSTF, ResetCause::IndependentWatchdog),
+            (WWDGRSTF, ResetCause::WindowWatchdog),
+            (LPWRRSTF, ResetCause::LowPower),
+            (SFTRSTF, ResetCause::Software),
+            (PORRSTF, ResetCause::PowerOn),
+            (PINRSTF, ResetCause::Pin),
+        ]
+        .into_iter()
+        .find(|(flag, _)| csr & flag != 0)
+        .map_or(ResetCause::Unknown, |(_, cause)| cause)
+    }
+
+    /// Whether a watchdog had to reset the chip.
+    pub fn is_watchdog(self) -> bool {
+        matches!(
+            self,
+            ResetCause::IndependentWatchdog | ResetCause::WindowWatchdog
+        )
+    }
+
+    /// Short description, e.g. to show it on the display at startup.
+    pub fn description(self) -> &'static str {
+        match self {
+            ResetCause::PowerOn => "power on",
+            ResetCause::Pin => "reset pin",
+            ResetCause::Software => "software reset",
+            ResetCause::IndependentWatchdog | ResetCause::WindowWatchdog => {
+                "recovered from watchdog reset"
+            }
+            ResetCause::LowPower => "low-power reset",
+            ResetCause::Unknown => "unknown reset",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_ready_when_all_tasks_checked_in() {
+        let mut supervisor = Supervisor::new();
+        let sensor = supervisor.register("sensor").unwrap();
+        let display = supervisor.register("display").unwrap();
+        assert!(!supervisor.all_checked_in());
+
+        supervisor.check_in(sensor);
+        supervisor.check_in(sensor);
+        assert!(!supervisor.all_checked_in());
+        assert!(supervisor.missing().eq(["display"]));
+
+        supervisor.check_in(display);
+        assert!(supervisor.all_checked_in());
+        assert_eq!(supervisor.missing().count(), 0);
+
+        supervisor.restart();
+        assert!(!supervisor.all_checked_in());
+        assert!(supervisor.missing().eq(["sensor", "display"]));
+    }
+
+    #[test]
+    fn reports_each_stall_once() {
+        let mut supervisor = Supervisor::new();
+        let sensor = supervisor.register("sensor").unwrap();
+        let display = supervisor.register("display").unwrap();
+
+        supervisor.check_in(sensor);
+        assert!(supervisor.newly_missing().eq(["display"]));
+        assert_eq!(supervisor.newly_missing().count(), 0);
+        assert!(supervisor.missing().eq(["display"]));
+
+        supervisor.check_in(display);
+        supervisor.restart();
+        supervisor.check_in(display);
+        assert!(supervisor.newly_missing().eq(["sensor"]));
+    }
+
+    #[test]
+    fn limits_the_number_of_tasks() {
+        let mut supervisor = Supervisor::new();
+        for _ in 0..MAX_TASKS {
+            assert!(supervisor.register("task").is_some());
+        }
+        assert_eq!(supervisor.register("one too many"), None);
+    }
+
+    #[test]
+    fn decodes_the_most_specific_reset_cause() {
+        assert_eq!(ResetCause::from_csr(PINRSTF | PORRSTF), ResetCause::PowerOn);
+        assert_eq!(ResetCause::from_csr(PINRSTF), ResetCause::Pin);
+        let watchdog = ResetCause::from_csr(PINRSTF | IWDGRSTF | 0x0C00_0003);
+        assert_eq!(watchdog, ResetCause::IndependentWatchdog);
+        assert!(watchdog.is_watchdog());
+        assert_eq!(watchdog.description(), "recovered from watchdog reset");
+        assert_eq!(ResetCause::from_csr(0), ResetCause::Unknown);
+        assert!(!ResetCause::from_csr(SFTRSTF | PINRSTF).is_watchdog());
+    }
+}
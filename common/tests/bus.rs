@@ -0,0 +1,125 @@
+//! Devices sharing an `I2cBus` or an `SpiBus` on top of an embedded-hal 0.2
+//! peripheral.
+
+use driver_examples_common::{
+    bus::NoChipSelect,
+    compat::{CompatError, ErrorKinds},
+    Compat, I2cBus, SpiBus,
+};
+use embedded_hal::i2c::{Error, ErrorKind, I2c, NoAcknowledgeSource, Operation};
+use embedded_hal_mock::eh0::{
+    i2c::{Mock as I2cMock, Transaction as I2cTrans},
+    spi::{Mock as SpiMock, Transaction as SpiTrans},
+    MockError,
+};
+use std::io;
+
+const DEV1: u8 = 0x40;
+const DEV2: u8 = 0x5A;
+
+#[test]
+fn handles_share_the_bus() {
+    let expectations = [
+        I2cTrans::write(DEV1, vec![0x01, 0x02]),
+        I2cTrans::write_read(DEV2, vec![0x00], vec![0x98]),
+        I2cTrans::read(DEV1, vec![0xAB, 0xCD]),
+    ];
+    let bus = I2cBus::new(Compat::new(I2cMock::new(&expectations)));
+    let mut dev1 = bus.acquire_i2c();
+    let mut dev2 = bus.acquire_i2c();
+
+    dev1.write(DEV1, &[0x01, 0x02]).unwrap();
+    let mut status = [0];
+    dev2.write_read(DEV2, &[0x00], &mut status).unwrap();
+    assert_eq!([0x98], status);
+    let mut data = [0; 2];
+    dev1.read(DEV1, &mut data).unwrap();
+    assert_eq!([0xAB, 0xCD], data);
+
+    bus.release().into_inner().done();
+}
+
+#[test]
+fn write_then_read_transaction_uses_repeated_start() {
+    let expectations = [I2cTrans::write_read(DEV1, vec![0x06], vec![1, 2, 3, 4])];
+    let bus = I2cBus::new(Compat::new(I2cMock::new(&expectations)));
+    let mut dev = bus.acquire_i2c();
+
+    let mut data = [0; 4];
+    dev.transaction(
+        DEV1,
+        &mut [Operation::Write(&[0x06]), Operation::Read(&mut data)],
+    )
+    .unwrap();
+    assert_eq!([1, 2, 3, 4], data);
+
+    bus.release().into_inner().done();
+}
+
+#[test]
+fn other_transactions_are_rejected() {
+    let bus = I2cBus::new(Compat::new(I2cMock::new(&[])));
+    let mut dev = bus.acquire_i2c();
+
+    let result = dev.transaction(
+        DEV1,
+        &mut [Operation::Write(&[0x00]), Operation::Write(&[0x01])],
+    );
+    assert_eq!(Err(CompatError::UnsupportedTransaction), result);
+
+    bus.release().into_inner().done();
+}
+
+/// Reports a refused connection as a missing acknowledge.
+#[derive(Debug)]
+struct MockErrorKinds;
+
+impl ErrorKinds<MockError> for MockErrorKinds {
+    fn i2c(error: &MockError) -> ErrorKind {
+        match error {
+            MockError::Io(io::ErrorKind::ConnectionRefused) => {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+            }
+            MockError::Io(_) => ErrorKind::Bus,
+        }
+    }
+}
+
+#[test]
+fn errors_are_classified() {
+    let expectations = [I2cTrans::write(DEV1, vec![0x00])
+        .with_error(MockError::Io(io::ErrorKind::ConnectionRefused))];
+    let mut i2c = Compat::<_, MockErrorKinds>::with_error_kinds(I2cMock::new(&expectations));
+
+    let error = i2c.write(DEV1, &[0x00]).unwrap_err();
+    assert_eq!(
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        error.kind()
+    );
+
+    i2c.into_inner().done();
+}
+
+#[test]
+fn lock_gives_access_to_the_peripheral() {
+    let expectations = [I2cTrans::write(DEV2, vec![0xF4])];
+    let bus = I2cBus::new(Compat::new(I2cMock::new(&expectations)));
+
+    bus.lock(|i2c| i2c.write(DEV2, &[0xF4])).unwrap();
+
+    bus.release().into_inner().done();
+}
+
+#[test]
+fn spi_devices_can_be_passed_to_embedded_hal_02_drivers() {
+    use embedded_hal_02::blocking::spi::Write;
+
+    let expectations = [SpiTrans::write(vec![0x3F, 0xFF])];
+    let mut spi = SpiMock::new(&expectations);
+    let bus = SpiBus::new(Compat::new(spi.clone()));
+    let mut device = Compat::new(bus.acquire_spi(NoChipSelect).unwrap());
+
+    device.write(&[0x3F, 0xFF]).unwrap();
+
+    spi.done();
+}
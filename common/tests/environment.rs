@@ -0,0 +1,69 @@
+//! CCS811 environment compensation driven through I2C expectations.
+
+use driver_examples_common::environment::Compensation;
+use embedded_ccs811::{prelude::*, Ccs811Awake, SlaveAddr};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+
+const ADDR: u8 = 0x5A;
+const STATUS: u8 = 0x00;
+const ENV_DATA: u8 = 0x05;
+const APP_START: u8 = 0xF4;
+const APP_VALID: u8 = 0x10;
+const FW_MODE: u8 = 0x80;
+
+fn start_application() -> Vec<I2cTrans> {
+    vec![
+        I2cTrans::write_read(ADDR, vec![STATUS], vec![APP_VALID]),
+        I2cTrans::write(ADDR, vec![APP_START]),
+        I2cTrans::write_read(ADDR, vec![STATUS], vec![FW_MODE | APP_VALID]),
+    ]
+}
+
+/// 25ºC and 50% are encoded as 50 and 50 with 9 fractional bits.
+fn set_environment() -> Vec<I2cTrans> {
+    vec![
+        I2cTrans::write(ADDR, vec![ENV_DATA, 0x64, 0x00, 0x64, 0x00]),
+        I2cTrans::write_read(ADDR, vec![STATUS], vec![FW_MODE | APP_VALID]),
+    ]
+}
+
+#[test]
+fn compensates_every_eleventh_reading() {
+    let mut expectations = start_application();
+    expectations.extend(set_environment());
+    expectations.extend(set_environment());
+    let ccs811 = Ccs811Awake::new(I2cMock::new(&expectations), SlaveAddr::default());
+    let mut ccs811 = ccs811.start_application().ok().unwrap();
+
+    let mut compensation = Compensation::new(11);
+    let mut measurements = 0;
+    for reading in 1..=22 {
+        let updated = compensation
+            .update(&mut ccs811, || {
+                measurements += 1;
+                (25.0, 50.0)
+            })
+            .unwrap();
+        assert_eq!(reading % 11 == 0, updated);
+    }
+    assert_eq!(2, measurements);
+
+    ccs811.destroy().done();
+}
+
+#[test]
+fn compensates_every_reading() {
+    let mut expectations = start_application();
+    for _ in 0..3 {
+        expectations.extend(set_environment());
+    }
+    let ccs811 = Ccs811Awake::new(I2cMock::new(&expectations), SlaveAddr::default());
+    let mut ccs811 = ccs811.start_application().ok().unwrap();
+
+    let mut compensation = Compensation::new(1);
+    for _ in 0..3 {
+        assert!(compensation.update(&mut ccs811, || (25.0, 50.0)).unwrap());
+    }
+
+    ccs811.destroy().done();
+}
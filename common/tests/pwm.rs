@@ -0,0 +1,40 @@
+//! Rainbow and servo animation sent to a PCA9685 through I2C expectations.
+
+use driver_examples_common::pwm::{RainbowServos, CHANNEL_COUNT};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+use pwm_pca9685::{Address, Pca9685};
+
+const ADDR: u8 = 0x40;
+const MODE1: u8 = 0x00;
+const C0_ON_L: u8 = 0x06;
+const MODE1_DEFAULT: u8 = 0x11;
+const MODE1_AUTO_INCREMENT: u8 = 0x20;
+
+fn all_off(values: &[u16; CHANNEL_COUNT]) -> I2cTrans {
+    let mut data = vec![C0_ON_L];
+    for value in values {
+        data.extend_from_slice(&[0, 0, *value as u8, (*value >> 8) as u8]);
+    }
+    I2cTrans::write(ADDR, data)
+}
+
+#[test]
+fn sends_all_channels_at_once() {
+    let mut animation = RainbowServos::default();
+    let first = *animation.next_values();
+    let second = *animation.next_values();
+    assert_eq!([127, 2, 0, 0, 125, 127], first[..6]);
+    assert_eq!([134, 174, 214, 254, 294], first[10..15]);
+    assert_eq!([136, 176, 216, 256, 296], second[10..15]);
+
+    let expectations = [
+        I2cTrans::write(ADDR, vec![MODE1, MODE1_DEFAULT | MODE1_AUTO_INCREMENT]),
+        all_off(&first),
+        all_off(&second),
+    ];
+    let mut pwm = Pca9685::new(I2cMock::new(&expectations), Address::default()).unwrap();
+    pwm.set_all_on_off(&[0; CHANNEL_COUNT], &first).unwrap();
+    pwm.set_all_on_off(&[0; CHANNEL_COUNT], &second).unwrap();
+
+    pwm.destroy().done();
+}
@@ -0,0 +1,43 @@
+//! CCS811 measured through the generic sensor interface.
+
+use driver_examples_common::sensor::{Sensor, Unit};
+use embedded_ccs811::{prelude::*, Ccs811Awake, SlaveAddr};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+
+const ADDR: u8 = 0x5A;
+const STATUS: u8 = 0x00;
+const ALG_RESULT_DATA: u8 = 0x02;
+const APP_START: u8 = 0xF4;
+const APP_VALID: u8 = 0x10;
+const DATA_READY: u8 = 0x08;
+const FW_MODE: u8 = 0x80;
+
+#[test]
+fn measures_eco2_and_etvoc() {
+    let status = FW_MODE | APP_VALID | DATA_READY;
+    let expectations = [
+        I2cTrans::write_read(ADDR, vec![STATUS], vec![APP_VALID]),
+        I2cTrans::write(ADDR, vec![APP_START]),
+        I2cTrans::write_read(ADDR, vec![STATUS], vec![FW_MODE | APP_VALID]),
+        I2cTrans::write_read(
+            ADDR,
+            vec![ALG_RESULT_DATA],
+            vec![0x01, 0x9C, 0x00, 0x2A, status, 0, 0, 0],
+        ),
+    ];
+    let ccs811 = Ccs811Awake::new(I2cMock::new(&expectations), SlaveAddr::default());
+    let mut ccs811 = ccs811.start_application().ok().unwrap();
+
+    let quantities = ccs811.measure().unwrap();
+    assert_eq!("CCS811", ccs811.name());
+    assert_eq!(2, quantities.len());
+    assert_eq!(
+        ("eCO2", Unit::Ppm, 412.0),
+        (quantities[0].name, quantities[0].unit, quantities[0].value)
+    );
+    assert_eq!(
+        ("eTVOC", Unit::Ppb, 42.0),
+        (quantities[1].name, quantities[1].unit, quantities[1].value)
+    );
+    ccs811.destroy().done();
+}
@@ -0,0 +1,73 @@
+//! Melody playback on an AD9833 driven through SPI expectations.
+
+use ad983x::Ad983x;
+use driver_examples_common::midi::{Melody, Smf};
+use driver_examples_common::synth::{frequency_word, note_frequency, NotePlayer, ODE_TO_JOY};
+use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTrans};
+
+const CONTROL_B28: u16 = 0x2000;
+const CONTROL_FSELECT: u16 = 0x0800;
+/// The driver keeps the output in reset until it is enabled.
+const CONTROL_RESET: u16 = 0x0100;
+const FREQ0: u16 = 0x4000;
+const FREQ1: u16 = 0x8000;
+
+fn word(value: u16) -> [SpiTrans<u8>; 3] {
+    [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![(value >> 8) as u8, value as u8]),
+        SpiTrans::transaction_end(),
+    ]
+}
+
+/// Set the frequency of a register while the other one is in use and
+/// select it.
+fn play(register: u16, frequency: u32) -> Vec<SpiTrans<u8>> {
+    let select = if register == FREQ1 {
+        CONTROL_FSELECT
+    } else {
+        0
+    };
+    [
+        word(register | (frequency & 0x3FFF) as u16),
+        word(register | (frequency >> 14) as u16),
+        word(CONTROL_B28 | CONTROL_RESET | select),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[test]
+fn frequency_of_the_first_note() {
+    assert_eq!(7078, frequency_word(note_frequency(76)));
+}
+
+#[test]
+fn alternates_frequency_registers() {
+    let e3 = frequency_word(note_frequency(76));
+    // The driver only writes the control register when it changes, so the
+    // 28-bit mode is enabled once before the first frequency.
+    let mut expectations = word(CONTROL_B28 | CONTROL_RESET).to_vec();
+    // The first note is played twice with a silence in between.
+    expectations.extend(play(FREQ1, e3));
+    expectations.extend(play(FREQ0, 0));
+    expectations.extend(play(FREQ1, e3));
+    expectations.extend(play(FREQ0, 0));
+    let spi = SpiMock::new(&expectations);
+    let mut synth = Ad983x::new_ad9833(spi);
+    let mut player = NotePlayer::default();
+
+    let smf = Smf::parse(ODE_TO_JOY).unwrap();
+    let notes: Vec<Option<u8>> = Melody::new(&smf, 1, 1_000)
+        .unwrap()
+        .take(4)
+        .map(|step| {
+            player.play(&mut synth, step.note).unwrap();
+            step.note
+        })
+        .collect();
+    assert_eq!(vec![Some(76), None, Some(76), None], notes);
+
+    synth.destroy().done();
+}
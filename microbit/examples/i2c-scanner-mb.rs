@@ -0,0 +1,62 @@
+//! Scan the I2C bus and identify the devices which are connected to it.
+//!
+//! Every address is probed and for each device which answers, the identity
+//! registers of the supported parts which can be at that address are read.
+//! The results are printed through RTT and the serial interface, e.g.:
+//!
+//! ```text
+//! 0x19: LSM303AGR acc (who am i 0x33)
+//! 0x1E: LSM303AGR mag (who am i 0x40)
+//! 2 devices found
+//! ```
+//!
+//! Install cargo-embed with:
+//! `cargo install cargo-embed`
+//!
+//! Run with:
+//! `cargo embed --example i2c-scanner-mb`
+//!
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples_common::scan;
+use driver_examples_microbit::{info, logging};
+use microbit::hal::twi;
+use microbit::hal::uart::{self, Baudrate, Parity};
+use microbit::pac::twi0::frequency::FREQUENCY_A;
+use microbit::Board;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("I2C scanner example");
+    if let Some(board) = Board::take() {
+        let mut tx = uart::Uart::new(
+            board.UART0,
+            board.uart.into(),
+            Parity::EXCLUDED,
+            Baudrate::BAUD115200,
+        );
+
+        let mut i2c = twi::Twi::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+        loop {
+            let mut count = 0;
+            for found in scan::scan(&mut i2c) {
+                info!("{}", found);
+                let _ = write!(&mut tx, "{}\n\r", found);
+                count += 1;
+            }
+            info!("{} devices found", count);
+            let _ = write!(&mut tx, "{} devices found\n\r", count);
+
+            for _ in 0..2_000_000 {
+                cortex_m::asm::nop();
+            }
+        }
+    }
+    loop {
+        continue;
+    }
+}
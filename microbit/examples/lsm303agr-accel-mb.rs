@@ -1,6 +1,10 @@
 //! Measure the acceleration with the LSM303AGR sensor and transmit the
 //! data through the serial interface.
 //!
+//! Every axis is sent as a binary telemetry frame tagged with the device,
+//! channel, unit and time, see `driver_examples_common::telemetry`. Decode
+//! them on the computer with the `telemetry` crate.
+//!
 //! Install cargo-embed with:
 //! `cargo install cargo-embed`
 //!
@@ -10,52 +14,61 @@
 #![no_main]
 #![no_std]
 
-use core::fmt::Write;
 use cortex_m_rt::entry;
+use driver_examples_common::{
+    sensor::Unit,
+    telemetry::{self, Sample, MAX_FRAME_LEN},
+};
+use driver_examples_microbit::{clock, info, logging};
 use lsm303agr::{AccelMode, AccelOutputDataRate, Lsm303agr};
-use microbit::hal::delay::Delay;
-use microbit::hal::i2c;
 use microbit::hal::prelude::*;
-use microbit::hal::serial;
-use microbit::hal::serial::BAUD115200;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
+use microbit::hal::twi;
+use microbit::hal::uart::{self, Baudrate, Parity};
+use microbit::hal::Timer;
+use microbit::pac::twi0::frequency::FREQUENCY_A;
+use microbit::Board;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("LSM303AGR accelerometer example");
-    if let Some(p) = microbit::Peripherals::take() {
-        let gpio = p.GPIO.split();
-
-        let tx = gpio.pin24.into_push_pull_output().into();
-        let rx = gpio.pin25.into_floating_input().into();
-
-        let (mut tx, _) = serial::Serial::uart0(p.UART0, tx, rx, BAUD115200).split();
+    logging::init();
+    info!("LSM303AGR accelerometer example");
+    if let Some(board) = Board::take() {
+        let mut tx = uart::Uart::new(
+            board.UART0,
+            board.uart.into(),
+            Parity::EXCLUDED,
+            Baudrate::BAUD115200,
+        );
 
-        let _ = write!(&mut tx, "\n\rAccelerometer\n\r");
+        let mut clock = clock::start(board.CLOCK, board.RTC0);
 
-        let scl = gpio.pin0.into_open_drain_input().into();
-        let sda = gpio.pin30.into_open_drain_input().into();
-        let i2c = i2c::I2c::i2c1(p.TWI1, sda, scl);
-        let mut delay = Delay::new(p.TIMER0);
+        let i2c = twi::Twi::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+        let mut delay = Timer::new(board.TIMER0);
 
         let mut accel = Lsm303agr::new_with_i2c(i2c);
         accel.init().unwrap();
         accel
             .set_accel_mode_and_odr(&mut delay, AccelMode::Normal, AccelOutputDataRate::Hz10)
             .unwrap();
+        let mut frame = [0; MAX_FRAME_LEN];
         loop {
             let status = accel.accel_status().unwrap();
             if status.xyz_new_data() {
                 let data = accel.acceleration().unwrap();
-                let _ = write!(
-                    &mut tx,
-                    "{:>4} {:>4} {:>4}\n\r",
-                    data.x_mg(),
-                    data.y_mg(),
-                    data.z_mg()
-                );
+                let timestamp_ms = clock.now_ms();
+                let axes = [("X", data.x_mg()), ("Y", data.y_mg()), ("Z", data.z_mg())];
+                for (channel, value) in axes {
+                    let sample = Sample {
+                        timestamp_ms,
+                        device: "LSM303AGR",
+                        channel,
+                        unit: Unit::MilliG,
+                        value: value as f32,
+                    };
+                    for byte in telemetry::encode(&sample, &mut frame).unwrap() {
+                        let _ = nb::block!(tx.write(*byte));
+                    }
+                }
             }
             for _ in 0..200_000 {
                 cortex_m::asm::nop();
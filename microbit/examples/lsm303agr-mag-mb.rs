@@ -11,24 +11,20 @@
 #![no_std]
 
 use cortex_m_rt::entry;
+use driver_examples_microbit::{info, logging};
 use lsm303agr::{Lsm303agr, MagMode, MagOutputDataRate};
-use microbit::hal::delay::Delay;
-use microbit::hal::i2c;
-use microbit::hal::prelude::*;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
+use microbit::hal::twi;
+use microbit::hal::Timer;
+use microbit::pac::twi0::frequency::FREQUENCY_A;
+use microbit::Board;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("LSM303AGR magnetometer example");
-    if let Some(p) = microbit::Peripherals::take() {
-        let gpio = p.GPIO.split();
-
-        let scl = gpio.pin0.into_open_drain_input().into();
-        let sda = gpio.pin30.into_open_drain_input().into();
-        let i2c = i2c::I2c::i2c1(p.TWI1, sda, scl);
-        let mut delay = Delay::new(p.TIMER0);
+    logging::init();
+    info!("LSM303AGR magnetometer example");
+    if let Some(board) = Board::take() {
+        let i2c = twi::Twi::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+        let mut delay = Timer::new(board.TIMER0);
 
         let mut lsm = Lsm303agr::new_with_i2c(i2c);
         lsm.init().unwrap();
@@ -43,7 +39,7 @@ fn main() -> ! {
             let status = lsm.mag_status().unwrap();
             if status.xyz_new_data() {
                 let data = lsm.magnetic_field().unwrap();
-                rprintln!("{:>4} {:>4} {:>4}", data.x_nt(), data.y_nt(), data.z_nt());
+                info!("{} {} {}", data.x_nt(), data.y_nt(), data.z_nt());
             }
             for _ in 0..20_000 {
                 cortex_m::asm::nop();
@@ -11,28 +11,24 @@
 #![no_std]
 
 use cortex_m_rt::entry;
+use driver_examples_microbit::{info, logging};
 use lsm303agr::Lsm303agr;
-use microbit::hal::i2c;
-use microbit::hal::prelude::*;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
+use microbit::hal::twi;
+use microbit::pac::twi0::frequency::FREQUENCY_A;
+use microbit::Board;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("LSM303AGR magnetometer example");
-    if let Some(p) = microbit::Peripherals::take() {
-        let gpio = p.GPIO.split();
-
-        let scl = gpio.pin0.into_open_drain_input().into();
-        let sda = gpio.pin30.into_open_drain_input().into();
-        let i2c = i2c::I2c::i2c1(p.TWI1, sda, scl);
+    logging::init();
+    info!("LSM303AGR magnetometer example");
+    if let Some(board) = Board::take() {
+        let i2c = twi::Twi::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
 
         let mut lsm = Lsm303agr::new_with_i2c(i2c);
         lsm.init().unwrap();
         loop {
             let data = nb::block!(lsm.magnetic_field()).unwrap();
-            rprintln!("{:>4} {:>4} {:>4}", data.x_nt(), data.y_nt(), data.z_nt());
+            info!("{} {} {}", data.x_nt(), data.y_nt(), data.z_nt());
 
             for _ in 0..20_000 {
                 cortex_m::asm::nop();
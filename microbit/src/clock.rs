@@ -0,0 +1,29 @@
+//! Monotonic clock of the Micro:bit examples.
+//!
+//! The nRF51 has no cycle counter, so the clock counts the 24-bit RTC0,
+//! which runs from the 32.768 kHz low-frequency clock and wraps around every
+//! 512 seconds.
+
+use driver_examples_common::clock::Clock;
+use microbit::hal::{clocks::Clocks, rtc::Rtc};
+use microbit::pac::{CLOCK, RTC0};
+
+/// Frequency of RTC0 without prescaler.
+const RTC_HZ: u32 = 32_768;
+
+/// Width of the RTC counter.
+const RTC_BITS: u32 = 24;
+
+/// Start the low-frequency clock and RTC0 and return a clock which counts
+/// the milliseconds from now on.
+pub fn start(clock: CLOCK, rtc: RTC0) -> Clock {
+    Clocks::new(clock).start_lfclk();
+    let rtc = Rtc::new(rtc, 0).unwrap();
+    rtc.enable_counter();
+    Clock::with_width(RTC_HZ, RTC_BITS, read_counter)
+}
+
+fn read_counter() -> u32 {
+    // SAFETY: Reading the counter register has no side effects.
+    unsafe { (*RTC0::ptr()).counter.read().bits() }
+}
@@ -1,3 +1,20 @@
-//! Empty. Please have a look at the examples.
+//! Logging, panic handler and clock of the Micro:bit examples.
 //!
+//! The examples log over RTT, either as formatted text or with `defmt`, see
+//! [`logging`]. The panic handler is the one of `panic-rtt-target` by
+//! default or `panic-probe` with the `defmt` feature.
+//!
+//! The streaming examples tag their telemetry with the time from the
+//! [`clock`].
 #![no_std]
+
+pub mod clock;
+#[path = "../../common/board/logging.rs"]
+pub mod logging;
+
+#[cfg(feature = "defmt")]
+use defmt_rtt as _;
+#[cfg(feature = "defmt")]
+use panic_probe as _;
+#[cfg(not(feature = "defmt"))]
+use panic_rtt_target as _;
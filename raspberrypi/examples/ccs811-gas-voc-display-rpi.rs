@@ -17,43 +17,42 @@
 //! ```
 //!
 //! Run with:
-//! `cargo run --example ccs811-gas-voc-display-rpi`,
+//! `cargo run --example ccs811-gas-voc-display-rpi`
+//!
+//! Run without the devices with:
+//! `cargo run --example ccs811-gas-voc-display-rpi -- --simulate`
 //!
 use core::fmt::Write;
+use driver_examples_raspberrypi::{
+    environment::Compensation, new_display, open_i2c, sim, Compat, I2cBus, TextPanel,
+};
 use embedded_ccs811::{
     prelude::*, Ccs811Awake, MeasurementMode, ModeChangeError, SlaveAddr as Ccs811Addr,
 };
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
-};
-use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::delay::DelayNs;
 use hdc20xx::{Hdc20xx, SlaveAddr as Hdc20xxAddr};
-use linux_embedded_hal::{Delay, I2cdev};
+use linux_embedded_hal::Delay;
 use nb::block;
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
+use ssd1306::I2CDisplayInterface;
 
 fn main() {
-    let dev = I2cdev::new("/dev/i2c-1").unwrap();
-    let bus = shared_bus::BusManagerStd::new(dev);
+    let dev = open_i2c(|display| {
+        sim::SimBus::new()
+            .with_device(0x5A, sim::Ccs811::new())
+            .with_device(0x40, sim::Hdc2080::new())
+            .with_device(0x3C, sim::Ssd1306::new(display))
+    })
+    .unwrap();
+    let bus = I2cBus::new(dev);
     let mut delay = Delay {};
     let interface = I2CDisplayInterface::new(bus.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
+    let mut panel = TextPanel::new(new_display(interface));
+    panel.init().unwrap();
 
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut hdc2080 = Hdc20xx::new(bus.acquire_i2c(), Hdc20xxAddr::default());
+    let mut hdc2080 = Hdc20xx::new(Compat::new(bus.acquire_i2c()), Hdc20xxAddr::default());
     let mut ccs811 = Ccs811Awake::new(bus.acquire_i2c(), Ccs811Addr::default());
     ccs811.software_reset().unwrap();
-    delay.delay_ms(10_u16);
+    delay.delay_ms(10);
 
     match ccs811.start_application() {
         Err(ModeChangeError { dev: _, error }) => {
@@ -62,34 +61,31 @@ fn main() {
         Ok(mut ccs811) => {
             let mut env = block!(hdc2080.read()).unwrap();
             ccs811
-                .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
+                .set_environment(env.humidity.unwrap_or(0.0), env.temperature)
                 .unwrap();
             ccs811.set_mode(MeasurementMode::ConstantPower1s).unwrap();
+            // Readings are 10 seconds apart so compensate after every one.
+            let mut compensation = Compensation::new(1);
             loop {
-                let mut lines = [String::new(), String::new(), String::new(), String::new()];
                 let data = block!(ccs811.data()).unwrap();
-                write!(lines[0], "eCO2: {}", data.eco2).unwrap();
-                write!(lines[1], "eTVOC: {}", data.etvoc).unwrap();
-                write!(lines[2], "Temp: {:.2}ºC", env.temperature).unwrap();
-                write!(lines[3], "Humidity: {:.2}%", env.humidity.unwrap_or(0.0)).unwrap();
-                disp.clear();
-                for (i, line) in lines.iter().enumerate() {
-                    Text::with_baseline(
-                        line,
-                        Point::new(0, i as i32 * 16),
-                        text_style,
-                        Baseline::Top,
-                    )
-                    .draw(&mut disp)
-                    .unwrap();
-                }
-                disp.flush().unwrap();
+                write!(panel.row(0), "eCO2: {}", data.eco2).unwrap();
+                write!(panel.row(1), "eTVOC: {}", data.etvoc).unwrap();
+                write!(panel.row(2), "Temp: {:.2}ºC", env.temperature).unwrap();
+                write!(
+                    panel.row(3),
+                    "Humidity: {:.2}%",
+                    env.humidity.unwrap_or(0.0)
+                )
+                .unwrap();
+                panel.flush().unwrap();
 
-                env = block!(hdc2080.read()).unwrap();
-                ccs811
-                    .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
+                compensation
+                    .update(&mut ccs811, || {
+                        env = block!(hdc2080.read()).unwrap();
+                        (env.temperature, env.humidity.unwrap_or(0.0))
+                    })
                     .unwrap();
-                delay.delay_ms(10_000_u32); // wait 10 seconds
+                delay.delay_ms(10_000); // wait 10 seconds
             }
         }
     }
@@ -18,20 +18,29 @@
 //! Run with:
 //! `cargo run --example ccs811-gas-voc-logging-rpi`
 //!
+//! Run without the devices with:
+//! `cargo run --example ccs811-gas-voc-logging-rpi -- --simulate`
+//!
 
+use driver_examples_raspberrypi::{environment::Compensation, open_i2c, sim, Compat, I2cBus};
 use embedded_ccs811::{
     prelude::*, Ccs811Awake, MeasurementMode, ModeChangeError, SlaveAddr as Ccs811Addr,
 };
-use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::delay::DelayNs;
 use hdc20xx::{Hdc20xx, SlaveAddr as Hdc20xxAddr};
-use linux_embedded_hal::{Delay, I2cdev};
+use linux_embedded_hal::Delay;
 use nb::block;
 
 fn main() {
-    let dev = I2cdev::new("/dev/i2c-1").unwrap();
-    let bus = shared_bus::BusManagerStd::new(dev);
+    let dev = open_i2c(|_| {
+        sim::SimBus::new()
+            .with_device(0x5A, sim::Ccs811::new())
+            .with_device(0x40, sim::Hdc2080::new())
+    })
+    .unwrap();
+    let bus = I2cBus::new(dev);
     let mut delay = Delay {};
-    let mut hdc2080 = Hdc20xx::new(bus.acquire_i2c(), Hdc20xxAddr::default());
+    let mut hdc2080 = Hdc20xx::new(Compat::new(bus.acquire_i2c()), Hdc20xxAddr::default());
     let ccs811 = Ccs811Awake::new(bus.acquire_i2c(), Ccs811Addr::default());
     match ccs811.start_application() {
         Err(ModeChangeError { dev: _, error }) => {
@@ -40,9 +49,11 @@ fn main() {
         Ok(mut ccs811) => {
             let mut env = block!(hdc2080.read()).unwrap();
             ccs811
-                .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
+                .set_environment(env.humidity.unwrap_or(0.0), env.temperature)
                 .unwrap();
             ccs811.set_mode(MeasurementMode::ConstantPower1s).unwrap();
+            // Readings are 10 seconds apart so compensate after every one.
+            let mut compensation = Compensation::new(1);
             println!("eco2,etvoc,raw_current,raw_voltage,temperature,humidity");
             loop {
                 let data = block!(ccs811.data()).unwrap();
@@ -55,11 +66,13 @@ fn main() {
                     env.temperature,
                     env.humidity.unwrap_or(0.0)
                 );
-                env = block!(hdc2080.read()).unwrap();
-                ccs811
-                    .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
+                compensation
+                    .update(&mut ccs811, || {
+                        env = block!(hdc2080.read()).unwrap();
+                        (env.temperature, env.humidity.unwrap_or(0.0))
+                    })
                     .unwrap();
-                delay.delay_ms(10_000_u32); // wait 10 seconds
+                delay.delay_ms(10_000); // wait 10 seconds
             }
         }
     }
@@ -0,0 +1,50 @@
+//! Scan the I2C bus and identify the devices which are connected to it.
+//!
+//! Every address is probed and for each device which answers, the identity
+//! registers of the supported parts which can be at that address are read:
+//!
+//! ```text
+//! 0x3C: SSD1306/PCF8574A?
+//! 0x40: HDC2080 (device id 0x07D0)
+//! 0x5A: CCS811 (hw id 0x81)
+//! 3 devices found
+//! ```
+//!
+//! Parts without identity registers are listed as candidates for their
+//! address, followed by a question mark.
+//!
+//! This is useful to check the wiring before running any other example.
+//!
+//! ```
+//! RPi   <-> Devices
+//! GND   <-> GND
+//! 3.3V  <-> VCC
+//! Pin 5 <-> SCL
+//! Pin 3 <-> SDA
+//! ```
+//!
+//! Run with:
+//! `cargo run --example i2c-scanner-rpi`
+//!
+//! Run without the devices with:
+//! `cargo run --example i2c-scanner-rpi -- --simulate`
+//!
+
+use driver_examples_raspberrypi::{open_i2c, scan, sim};
+
+fn main() {
+    let mut dev = open_i2c(|display| {
+        sim::SimBus::new()
+            .with_device(0x3C, sim::Ssd1306::new(display))
+            .with_device(0x40, sim::Hdc2080::new())
+            .with_device(0x5A, sim::Ccs811::new())
+    })
+    .unwrap();
+
+    let mut count = 0;
+    for found in scan::scan(&mut dev) {
+        println!("{}", found);
+        count += 1;
+    }
+    println!("{} devices found", count);
+}
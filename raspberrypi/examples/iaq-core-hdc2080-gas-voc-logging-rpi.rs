@@ -15,19 +15,28 @@
 //! Run with:
 //! `cargo run --example iaq-core-hdc2080-gas-voc-logging-rpi`
 //!
+//! Run without the devices with:
+//! `cargo run --example iaq-core-hdc2080-gas-voc-logging-rpi -- --simulate`
+//!
 
-use embedded_hal::blocking::delay::DelayMs;
+use driver_examples_raspberrypi::{open_i2c, sim, Compat, I2cBus};
+use embedded_hal::delay::DelayNs;
 use hdc20xx::{Hdc20xx, SlaveAddr as Hdc20xxAddr};
 use iaq_core::IaqCore;
-use linux_embedded_hal::{Delay, I2cdev};
+use linux_embedded_hal::Delay;
 use nb::block;
 
 fn main() {
-    let dev = I2cdev::new("/dev/i2c-1").unwrap();
-    let bus = shared_bus::BusManagerStd::new(dev);
+    let dev = open_i2c(|_| {
+        sim::SimBus::new()
+            .with_device(0x5A, sim::IaqCore::new())
+            .with_device(0x40, sim::Hdc2080::new())
+    })
+    .unwrap();
+    let bus = I2cBus::new(dev);
     let mut delay = Delay {};
-    let mut hdc2080 = Hdc20xx::new(bus.acquire_i2c(), Hdc20xxAddr::default());
-    let mut iaq_core = IaqCore::new(bus.acquire_i2c());
+    let mut hdc2080 = Hdc20xx::new(Compat::new(bus.acquire_i2c()), Hdc20xxAddr::default());
+    let mut iaq_core = IaqCore::new(Compat::new(bus.acquire_i2c()));
 
     println!("co2,tvoc,resistance,temperature,humidity");
     loop {
@@ -41,6 +50,6 @@ fn main() {
             env.temperature,
             env.humidity.unwrap_or(0.0)
         );
-        delay.delay_ms(10_000_u32); // wait 10 seconds
+        delay.delay_ms(10_000); // wait 10 seconds
     }
 }
@@ -0,0 +1,74 @@
+//! I2C bus of the Raspberry Pi or its simulation.
+
+use crate::sim::{self, DisplayOutput, Options, SimBus};
+use embedded_hal::i2c::{self, Operation};
+use linux_embedded_hal::{i2cdev::linux::LinuxI2CError, I2CError, I2cdev};
+
+/// Either the `/dev/i2c-1` bus or a [`SimBus`].
+pub enum HostI2c {
+    /// Bus of the Raspberry Pi.
+    Linux(I2cdev),
+    /// Simulated devices.
+    Simulated(SimBus),
+}
+
+/// Error of a [`HostI2c`].
+#[derive(Debug)]
+pub enum HostI2cError {
+    /// Error of the Raspberry Pi bus.
+    Linux(I2CError),
+    /// Error of the simulated bus.
+    Simulated(sim::Error),
+}
+
+impl i2c::Error for HostI2cError {
+    fn kind(&self) -> i2c::ErrorKind {
+        match self {
+            HostI2cError::Linux(e) => e.kind(),
+            HostI2cError::Simulated(e) => e.kind(),
+        }
+    }
+}
+
+impl i2c::ErrorType for HostI2c {
+    type Error = HostI2cError;
+}
+
+impl i2c::I2c for HostI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match self {
+            HostI2c::Linux(dev) => dev
+                .transaction(address, operations)
+                .map_err(HostI2cError::Linux),
+            HostI2c::Simulated(bus) => bus
+                .transaction(address, operations)
+                .map_err(HostI2cError::Simulated),
+        }
+    }
+}
+
+/// Open `/dev/i2c-1` or, if the program was run with `--simulate`, create
+/// the simulated bus.
+///
+/// `simulation` gets where a simulated display should be shown.
+///
+/// ```no_run
+/// use driver_examples_raspberrypi::{open_i2c, sim::{Hdc2080, SimBus}};
+///
+/// let i2c = open_i2c(|_| SimBus::new().with_device(0x40, Hdc2080::new())).unwrap();
+/// ```
+pub fn open_i2c<F>(simulation: F) -> Result<HostI2c, LinuxI2CError>
+where
+    F: FnOnce(DisplayOutput) -> SimBus,
+{
+    let options = Options::from_args();
+    if options.simulate {
+        Ok(HostI2c::Simulated(simulation(options.display)))
+    } else {
+        I2cdev::new("/dev/i2c-1").map(HostI2c::Linux)
+    }
+}
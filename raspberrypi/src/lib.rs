@@ -1,3 +1,20 @@
-//! Empty. Please have a look at the examples.
+//! Code shared by the Raspberry Pi examples.
 //!
-#![no_std]
+//! The I2C bus is shared between the devices through an [`I2cBus`] and text
+//! can be shown on an SSD1306 display through a [`TextPanel`].
+//!
+//! The bus is opened with [`open_i2c`]. When an example is run with
+//! `--simulate` the devices are replaced by the models in [`sim`] so that
+//! it runs on any Linux host:
+//!
+//! ```text
+//! cargo run --example ccs811-gas-voc-display-rpi -- --simulate
+//! cargo run --example ccs811-gas-voc-display-rpi -- --simulate --png display.png
+//! ```
+
+mod i2c;
+pub use crate::i2c::{open_i2c, HostI2c, HostI2cError};
+pub mod sim;
+pub use driver_examples_common::{
+    environment, new_display, scan, Compat, Display, I2cBus, Panel, TextPanel,
+};
@@ -0,0 +1,226 @@
+//! Simulated CCS811 gas and VOC sensor.
+
+use super::{fill, Ambient, SimDevice};
+use std::time::{Duration, Instant};
+
+struct Register;
+impl Register {
+    const STATUS: u8 = 0x00;
+    const MEAS_MODE: u8 = 0x01;
+    const ALG_RESULT_DATA: u8 = 0x02;
+    const RAW_DATA: u8 = 0x03;
+    const ENV_DATA: u8 = 0x05;
+    const BASELINE: u8 = 0x11;
+    const HW_ID: u8 = 0x20;
+    const HW_VERSION: u8 = 0x21;
+    const FW_BOOT_VERSION: u8 = 0x23;
+    const FW_APP_VERSION: u8 = 0x24;
+    const APP_START: u8 = 0xF4;
+    const SW_RESET: u8 = 0xFF;
+}
+
+struct BitFlags;
+impl BitFlags {
+    const DATA_READY: u8 = 1 << 3;
+    const APP_VALID: u8 = 1 << 4;
+    const FW_MODE: u8 = 1 << 7;
+}
+
+const HW_ID: u8 = 0x81;
+const SW_RESET_SEQUENCE: [u8; 4] = [0x11, 0xE5, 0x72, 0x8A];
+
+/// Simulated CCS811 with a valid application firmware.
+///
+/// Its address is usually 0x5A. The results follow the [`Ambient`] CO2
+/// and TVOC concentrations.
+#[derive(Debug)]
+pub struct Ccs811 {
+    start: Instant,
+    pointer: u8,
+    app_mode: bool,
+    meas_mode: u8,
+    env_data: [u8; 4],
+    baseline: [u8; 2],
+    last_result: Option<Instant>,
+    result: [u8; 8],
+}
+
+impl Default for Ccs811 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ccs811 {
+    /// Create a device in boot mode.
+    pub fn new() -> Self {
+        Ccs811 {
+            start: Instant::now(),
+            pointer: Register::STATUS,
+            app_mode: false,
+            meas_mode: 0,
+            env_data: [0x64, 0x00, 0x64, 0x00],
+            baseline: [0x84, 0x47],
+            last_result: None,
+            result: [0; 8],
+        }
+    }
+
+    /// Time between results in the current drive mode.
+    fn period(&self) -> Option<Duration> {
+        match (self.meas_mode >> 4) & 0b111 {
+            1 => Some(Duration::from_secs(1)),
+            2 => Some(Duration::from_secs(10)),
+            3 => Some(Duration::from_secs(60)),
+            4 => Some(Duration::from_millis(250)),
+            _ => None,
+        }
+    }
+
+    fn is_data_ready(&self) -> bool {
+        if !self.app_mode {
+            return false;
+        }
+        match (self.period(), self.last_result) {
+            (Some(_), None) => true,
+            (Some(period), Some(last)) => last.elapsed() >= period,
+            (None, _) => false,
+        }
+    }
+
+    fn status(&self) -> u8 {
+        let mut status = BitFlags::APP_VALID;
+        if self.app_mode {
+            status |= BitFlags::FW_MODE;
+        }
+        if self.is_data_ready() {
+            status |= BitFlags::DATA_READY;
+        }
+        status
+    }
+
+    /// Take a new measurement if one is available.
+    ///
+    /// Like the status register, the status in the result tells whether
+    /// the data is new.
+    fn update_result(&mut self) {
+        let status = self.status();
+        self.result[4] = status;
+        if status & BitFlags::DATA_READY == 0 {
+            return;
+        }
+        self.last_result = Some(Instant::now());
+        let ambient = Ambient::at(self.start.elapsed());
+        // The current is in µA (6 bits) and the voltage a 10-bit value.
+        let raw_current: u16 = 20;
+        let raw_voltage = (f32::from(ambient.tvoc) * 2.0) as u16 & 0x3FF;
+        let raw = (raw_current << 10) | raw_voltage;
+        self.result = [
+            (ambient.co2 >> 8) as u8,
+            ambient.co2 as u8,
+            (ambient.tvoc >> 8) as u8,
+            ambient.tvoc as u8,
+            status,
+            0,
+            (raw >> 8) as u8,
+            raw as u8,
+        ];
+    }
+}
+
+impl SimDevice for Ccs811 {
+    fn write(&mut self, data: &[u8]) {
+        let Some((&register, payload)) = data.split_first() else {
+            return;
+        };
+        self.pointer = register;
+        match register {
+            Register::MEAS_MODE if self.app_mode => {
+                if let Some(&mode) = payload.first() {
+                    self.meas_mode = mode;
+                    self.last_result = None;
+                }
+            }
+            Register::ENV_DATA if payload.len() >= 4 => {
+                self.env_data.copy_from_slice(&payload[..4]);
+            }
+            Register::BASELINE if payload.len() >= 2 => {
+                self.baseline.copy_from_slice(&payload[..2]);
+            }
+            Register::APP_START if !self.app_mode => self.app_mode = true,
+            Register::SW_RESET if payload == SW_RESET_SEQUENCE => {
+                *self = Ccs811 {
+                    start: self.start,
+                    ..Ccs811::new()
+                };
+            }
+            _ => (),
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        let data = match self.pointer {
+            Register::STATUS => vec![self.status()],
+            Register::MEAS_MODE => vec![self.meas_mode],
+            Register::ALG_RESULT_DATA => {
+                self.update_result();
+                self.result.to_vec()
+            }
+            Register::RAW_DATA => self.result[6..].to_vec(),
+            Register::ENV_DATA => self.env_data.to_vec(),
+            Register::BASELINE => self.baseline.to_vec(),
+            Register::HW_ID => vec![HW_ID],
+            Register::HW_VERSION => vec![0x12],
+            Register::FW_BOOT_VERSION => vec![0x10, 0x00],
+            Register::FW_APP_VERSION => vec![0x20, 0x00],
+            _ => vec![],
+        };
+        fill(buffer, &data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(device: &mut Ccs811, register: u8, buffer: &mut [u8]) {
+        device.write(&[register]);
+        device.read(buffer);
+    }
+
+    #[test]
+    fn starts_application_and_measures() {
+        let mut device = Ccs811::new();
+        let mut status = [0];
+        read(&mut device, Register::STATUS, &mut status);
+        assert_eq!(BitFlags::APP_VALID, status[0]);
+
+        device.write(&[Register::APP_START]);
+        device.write(&[Register::MEAS_MODE, 0x10]);
+        read(&mut device, Register::STATUS, &mut status);
+        assert_eq!(
+            BitFlags::FW_MODE | BitFlags::APP_VALID | BitFlags::DATA_READY,
+            status[0]
+        );
+
+        let mut result = [0; 8];
+        read(&mut device, Register::ALG_RESULT_DATA, &mut result);
+        let co2 = u16::from_be_bytes([result[0], result[1]]);
+        assert!((400..1000).contains(&co2));
+        assert_ne!(0, result[4] & BitFlags::DATA_READY);
+        read(&mut device, Register::STATUS, &mut status);
+        assert_eq!(BitFlags::FW_MODE | BitFlags::APP_VALID, status[0]);
+    }
+
+    #[test]
+    fn software_reset_returns_to_boot_mode() {
+        let mut device = Ccs811::new();
+        device.write(&[Register::APP_START]);
+        let mut reset = vec![Register::SW_RESET];
+        reset.extend_from_slice(&SW_RESET_SEQUENCE);
+        device.write(&reset);
+        let mut status = [0];
+        read(&mut device, Register::STATUS, &mut status);
+        assert_eq!(BitFlags::APP_VALID, status[0]);
+    }
+}
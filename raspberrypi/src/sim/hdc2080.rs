@@ -0,0 +1,137 @@
+//! Simulated HDC2080 temperature and humidity sensor.
+
+use super::{fill, Ambient, SimDevice};
+use std::time::Instant;
+
+struct Register;
+impl Register {
+    const TEMPERATURE: u8 = 0x00;
+    const DRDY_STATUS: u8 = 0x04;
+    const DEVICE_CONFIG: u8 = 0x0E;
+    const MEAS_CONFIG: u8 = 0x0F;
+    const MANUFACTURER_ID: u8 = 0xFC;
+}
+
+struct BitFlags;
+impl BitFlags {
+    const DRDY: u8 = 1 << 7;
+    const MEAS_TRIG: u8 = 1;
+    const SOFT_RESET: u8 = 1 << 7;
+}
+
+const IDS: [u8; 4] = [0x49, 0x54, 0xD0, 0x07];
+
+/// Simulated HDC2080 measuring the [`Ambient`] temperature and humidity.
+///
+/// Its address is usually 0x40. Measurements triggered through the
+/// measurement configuration register complete immediately.
+#[derive(Debug)]
+pub struct Hdc2080 {
+    start: Instant,
+    pointer: u8,
+    registers: [u8; 256],
+}
+
+impl Default for Hdc2080 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hdc2080 {
+    /// Create a device after power-up.
+    pub fn new() -> Self {
+        let mut registers = [0; 256];
+        registers[usize::from(Register::MANUFACTURER_ID)..].copy_from_slice(&IDS);
+        Hdc2080 {
+            start: Instant::now(),
+            pointer: 0,
+            registers,
+        }
+    }
+
+    fn measure(&mut self) {
+        let ambient = Ambient::at(self.start.elapsed());
+        let temperature = ((ambient.temperature + 40.0) / 165.0 * 65536.0) as u16;
+        let humidity = (ambient.humidity / 100.0 * 65536.0) as u16;
+        let first = usize::from(Register::TEMPERATURE);
+        self.registers[first..first + 2].copy_from_slice(&temperature.to_le_bytes());
+        self.registers[first + 2..first + 4].copy_from_slice(&humidity.to_le_bytes());
+        self.registers[usize::from(Register::DRDY_STATUS)] |= BitFlags::DRDY;
+    }
+}
+
+impl SimDevice for Hdc2080 {
+    fn write(&mut self, data: &[u8]) {
+        let Some((&register, payload)) = data.split_first() else {
+            return;
+        };
+        self.pointer = register;
+        for (offset, &value) in payload.iter().enumerate() {
+            let register = register.wrapping_add(offset as u8);
+            match register {
+                Register::DEVICE_CONFIG if value & BitFlags::SOFT_RESET != 0 => {
+                    *self = Hdc2080 {
+                        start: self.start,
+                        ..Hdc2080::new()
+                    };
+                }
+                Register::MEAS_CONFIG if value & BitFlags::MEAS_TRIG != 0 => {
+                    self.registers[usize::from(register)] = value & !BitFlags::MEAS_TRIG;
+                    self.measure();
+                }
+                Register::DRDY_STATUS | Register::MANUFACTURER_ID..=0xFF => (),
+                _ => self.registers[usize::from(register)] = value,
+            }
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        let data: Vec<u8> = (0..buffer.len())
+            .map(|offset| {
+                let register = self.pointer.wrapping_add(offset as u8);
+                let value = self.registers[usize::from(register)];
+                if register == Register::DRDY_STATUS {
+                    // The status is cleared when read.
+                    self.registers[usize::from(register)] &= !BitFlags::DRDY;
+                }
+                value
+            })
+            .collect();
+        fill(buffer, &data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggered_measurement() {
+        let mut device = Hdc2080::new();
+        device.write(&[Register::MEAS_CONFIG, BitFlags::MEAS_TRIG]);
+        let mut status = [0];
+        device.write(&[Register::DRDY_STATUS]);
+        device.read(&mut status);
+        assert_eq!(BitFlags::DRDY, status[0]);
+        device.read(&mut status);
+        assert_eq!(0, status[0]);
+
+        let mut data = [0; 4];
+        device.write(&[Register::TEMPERATURE]);
+        device.read(&mut data);
+        let temperature = f32::from(u16::from_le_bytes([data[0], data[1]])) * 165.0 / 65536.0;
+        let humidity = f32::from(u16::from_le_bytes([data[2], data[3]])) * 100.0 / 65536.0;
+        assert!((20.0..24.0).contains(&(temperature - 40.0)));
+        assert!((40.0..=50.0).contains(&humidity));
+    }
+
+    #[test]
+    fn identification() {
+        let mut device = Hdc2080::new();
+        let mut ids = [0; 4];
+        device.write(&[Register::MANUFACTURER_ID]);
+        device.read(&mut ids);
+        assert_eq!(IDS, ids);
+    }
+}
@@ -0,0 +1,63 @@
+//! Simulated iAQ-Core indoor air quality sensor.
+
+use super::{fill, Ambient, SimDevice};
+use std::time::Instant;
+
+/// Simulated iAQ-Core measuring the [`Ambient`] CO2 and TVOC.
+///
+/// Its address is 0x5A. Every read returns the 9 bytes of the latest
+/// measurement.
+#[derive(Debug)]
+pub struct IaqCore {
+    start: Instant,
+}
+
+impl Default for IaqCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IaqCore {
+    /// Create a device which has already warmed up.
+    pub fn new() -> Self {
+        IaqCore {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl SimDevice for IaqCore {
+    fn write(&mut self, _data: &[u8]) {}
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        let ambient = Ambient::at(self.start.elapsed());
+        // The sensor resistance drops when there are more VOCs.
+        let resistance = 500_000 - u32::from(ambient.tvoc) * 1_000;
+        let status = 0;
+        let mut data = Vec::with_capacity(9);
+        data.extend_from_slice(&ambient.co2.to_be_bytes());
+        data.push(status);
+        data.extend_from_slice(&resistance.to_be_bytes());
+        data.extend_from_slice(&ambient.tvoc.to_be_bytes());
+        fill(buffer, &data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurement() {
+        let mut device = IaqCore::new();
+        let mut data = [0xFF; 9];
+        device.read(&mut data);
+        let co2 = u16::from_be_bytes([data[0], data[1]]);
+        let resistance = u32::from_be_bytes([data[3], data[4], data[5], data[6]]);
+        let tvoc = u16::from_be_bytes([data[7], data[8]]);
+        assert!((400..1000).contains(&co2));
+        assert_eq!(0, data[2]);
+        assert_eq!(500_000 - u32::from(tvoc) * 1_000, resistance);
+    }
+}
@@ -0,0 +1,231 @@
+//! Simulated I2C devices so that the examples run on any Linux host.
+//!
+//! A [`SimBus`] dispatches the I2C transactions to models of the devices
+//! connected to it. The models implement the registers the drivers use and
+//! return slowly changing [`Ambient`] conditions.
+//!
+//! ```
+//! use driver_examples_raspberrypi::sim::{Ccs811, DisplayOutput, Hdc2080, SimBus, Ssd1306};
+//!
+//! let bus = SimBus::new()
+//!     .with_device(0x5A, Ccs811::new())
+//!     .with_device(0x40, Hdc2080::new())
+//!     .with_device(0x3C, Ssd1306::new(DisplayOutput::Terminal));
+//! ```
+
+use core::f32::consts::PI;
+use embedded_hal::i2c::{self, NoAcknowledgeSource, Operation};
+use std::{path::PathBuf, time::Duration};
+
+mod ccs811;
+pub use self::ccs811::Ccs811;
+mod hdc2080;
+pub use self::hdc2080::Hdc2080;
+mod iaq_core;
+pub use self::iaq_core::IaqCore;
+mod ssd1306;
+pub use self::ssd1306::Ssd1306;
+
+/// Model of a device connected to a [`SimBus`].
+pub trait SimDevice {
+    /// Handle the data written by the host.
+    fn write(&mut self, data: &[u8]);
+
+    /// Fill the buffer with the data read by the host.
+    fn read(&mut self, buffer: &mut [u8]);
+}
+
+/// Simulated I2C bus.
+///
+/// It implements `embedded_hal::i2c::I2c` so it can be used like the
+/// `I2cdev` of the Raspberry Pi.
+#[derive(Default)]
+pub struct SimBus {
+    devices: Vec<(u8, Box<dyn SimDevice>)>,
+}
+
+impl SimBus {
+    /// Create an empty bus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect a device with the given address.
+    pub fn with_device(mut self, address: u8, device: impl SimDevice + 'static) -> Self {
+        self.devices.push((address, Box::new(device)));
+        self
+    }
+}
+
+/// Error of a [`SimBus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// No device is connected with the given address.
+    NoDevice(u8),
+}
+
+impl i2c::Error for Error {
+    fn kind(&self) -> i2c::ErrorKind {
+        i2c::ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+    }
+}
+
+impl i2c::ErrorType for SimBus {
+    type Error = Error;
+}
+
+impl i2c::I2c for SimBus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let (_, device) = self
+            .devices
+            .iter_mut()
+            .find(|(a, _)| *a == address)
+            .ok_or(Error::NoDevice(address))?;
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => device.write(data),
+                Operation::Read(buffer) => device.read(buffer),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy the data read from a device into a buffer, padding it with zeros.
+fn fill(buffer: &mut [u8], data: &[u8]) {
+    let len = buffer.len().min(data.len());
+    buffer[..len].copy_from_slice(&data[..len]);
+    buffer[len..].fill(0);
+}
+
+/// Conditions measured by the simulated sensors.
+///
+/// All values follow slow sine waves so that changes can be seen when
+/// running an example for a few minutes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ambient {
+    /// Temperature in ºC.
+    pub temperature: f32,
+    /// Relative humidity in percent.
+    pub humidity: f32,
+    /// CO2 concentration in ppm.
+    pub co2: u16,
+    /// TVOC concentration in ppb.
+    pub tvoc: u16,
+}
+
+impl Ambient {
+    /// Conditions after the given time since the start of the simulation.
+    pub fn at(elapsed: Duration) -> Self {
+        let t = elapsed.as_secs_f32();
+        let wave = |period: f32| (t * 2.0 * PI / period).sin();
+        Ambient {
+            temperature: 22.0 + 1.5 * wave(600.0),
+            humidity: 45.0 + 5.0 * wave(900.0),
+            co2: (700.0 + 250.0 * wave(300.0)) as u16,
+            tvoc: (150.0 + 100.0 * wave(240.0)) as u16,
+        }
+    }
+}
+
+/// Where a simulated display is shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayOutput {
+    /// Draw the display in the terminal.
+    Terminal,
+    /// Write the display to a PNG file, overwriting it on every update.
+    Png(PathBuf),
+}
+
+/// Command line options of the examples.
+///
+/// - `--simulate`: use a [`SimBus`] instead of `/dev/i2c-1`.
+/// - `--png <file>`: write the simulated display to a PNG file instead of
+///   drawing it in the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Whether to simulate the devices.
+    pub simulate: bool,
+    /// Where to show a simulated display.
+    pub display: DisplayOutput,
+}
+
+impl Options {
+    /// Parse the options from the command line arguments of the program.
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Options {
+            simulate: false,
+            display: DisplayOutput::Terminal,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--simulate" => options.simulate = true,
+                "--png" => {
+                    if let Some(path) = args.next() {
+                        options.display = DisplayOutput::Png(path.into());
+                    }
+                }
+                _ => eprintln!("Ignoring unknown argument: {}", arg),
+            }
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::I2c;
+
+    struct Echo(Vec<u8>);
+
+    impl SimDevice for Echo {
+        fn write(&mut self, data: &[u8]) {
+            self.0 = data.to_vec();
+        }
+
+        fn read(&mut self, buffer: &mut [u8]) {
+            buffer.copy_from_slice(&self.0[..buffer.len()]);
+        }
+    }
+
+    #[test]
+    fn dispatches_by_address() {
+        let mut bus = SimBus::new().with_device(0x10, Echo(vec![]));
+        let mut data = [0; 2];
+        bus.write_read(0x10, &[1, 2], &mut data).unwrap();
+        assert_eq!([1, 2], data);
+        assert_eq!(Err(Error::NoDevice(0x11)), bus.write(0x11, &[0]));
+    }
+
+    #[test]
+    fn parses_options() {
+        let args = ["--simulate", "--png", "display.png"].map(String::from);
+        let options = Options::parse(args.into_iter());
+        assert!(options.simulate);
+        assert_eq!(DisplayOutput::Png("display.png".into()), options.display);
+
+        let options = Options::parse(core::iter::empty());
+        assert!(!options.simulate);
+        assert_eq!(DisplayOutput::Terminal, options.display);
+    }
+
+    #[test]
+    fn ambient_is_plausible() {
+        for minute in 0..60 {
+            let ambient = Ambient::at(Duration::from_secs(minute * 60));
+            assert!((20.0..24.0).contains(&ambient.temperature));
+            assert!((40.0..=50.0).contains(&ambient.humidity));
+            assert!((400..1000).contains(&ambient.co2));
+            assert!((49..=250).contains(&ambient.tvoc));
+        }
+    }
+}
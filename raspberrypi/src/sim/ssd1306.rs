@@ -0,0 +1,346 @@
+//! Simulated 128x64 SSD1306 OLED display.
+
+use super::{DisplayOutput, SimDevice};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+const PAGES: usize = HEIGHT / 8;
+/// Size of each pixel in the PNG output.
+const PNG_SCALE: usize = 4;
+
+struct Command;
+impl Command {
+    const ADDRESSING_MODE: u8 = 0x20;
+    const COLUMN_ADDRESS: u8 = 0x21;
+    const PAGE_ADDRESS: u8 = 0x22;
+    const SEGMENT_REMAP_OFF: u8 = 0xA0;
+    const SEGMENT_REMAP_ON: u8 = 0xA1;
+    const NORMAL: u8 = 0xA6;
+    const INVERT: u8 = 0xA7;
+    const DISPLAY_OFF: u8 = 0xAE;
+    const DISPLAY_ON: u8 = 0xAF;
+    const COM_SCAN_NORMAL: u8 = 0xC0;
+    const COM_SCAN_REVERSE: u8 = 0xC8;
+}
+
+/// Number of argument bytes following a command.
+fn argument_count(command: u8) -> usize {
+    match command {
+        0x20 | 0x81 | 0x8D | 0xA8 | 0xAD | 0xD3 | 0xD5 | 0xD9 | 0xDA | 0xDB => 1,
+        0x21 | 0x22 | 0xA3 => 2,
+        0x29 | 0x2A => 5,
+        0x26 | 0x27 => 6,
+        _ => 0,
+    }
+}
+
+/// Simulated SSD1306 on I2C.
+///
+/// Its address is usually 0x3C. The display contents are shown every
+/// time the host finishes writing the selected area of the display RAM,
+/// which is what a buffered flush does, and when the display is switched
+/// on or off or inverted.
+#[derive(Debug)]
+pub struct Ssd1306 {
+    output: DisplayOutput,
+    ram: [u8; WIDTH * PAGES],
+    command: Vec<u8>,
+    horizontal_addressing: bool,
+    columns: (usize, usize),
+    pages: (usize, usize),
+    column: usize,
+    page: usize,
+    on: bool,
+    inverted: bool,
+    segment_remap: bool,
+    com_scan_reverse: bool,
+    first_frame: bool,
+}
+
+impl Ssd1306 {
+    /// Create a display after power-up.
+    pub fn new(output: DisplayOutput) -> Self {
+        Ssd1306 {
+            output,
+            ram: [0; WIDTH * PAGES],
+            command: Vec::new(),
+            horizontal_addressing: false,
+            columns: (0, WIDTH - 1),
+            pages: (0, PAGES - 1),
+            column: 0,
+            page: 0,
+            on: false,
+            inverted: false,
+            segment_remap: false,
+            com_scan_reverse: false,
+            first_frame: true,
+        }
+    }
+
+    fn command_byte(&mut self, byte: u8) {
+        self.command.push(byte);
+        if self.command.len() <= argument_count(self.command[0]) {
+            return;
+        }
+        let command = core::mem::take(&mut self.command);
+        let redraw = match command[..] {
+            [Command::ADDRESSING_MODE, mode] => {
+                self.horizontal_addressing = mode & 0b11 == 0;
+                false
+            }
+            [Command::COLUMN_ADDRESS, start, end] => {
+                self.columns = (usize::from(start) % WIDTH, usize::from(end) % WIDTH);
+                self.column = self.columns.0;
+                false
+            }
+            [Command::PAGE_ADDRESS, start, end] => {
+                self.pages = (usize::from(start) % PAGES, usize::from(end) % PAGES);
+                self.page = self.pages.0;
+                false
+            }
+            [Command::SEGMENT_REMAP_OFF] | [Command::SEGMENT_REMAP_ON] => {
+                self.segment_remap = command[0] == Command::SEGMENT_REMAP_ON;
+                false
+            }
+            [Command::COM_SCAN_NORMAL] | [Command::COM_SCAN_REVERSE] => {
+                self.com_scan_reverse = command[0] == Command::COM_SCAN_REVERSE;
+                false
+            }
+            [Command::NORMAL] | [Command::INVERT] => {
+                self.inverted = command[0] == Command::INVERT;
+                true
+            }
+            [Command::DISPLAY_OFF] | [Command::DISPLAY_ON] => {
+                self.on = command[0] == Command::DISPLAY_ON;
+                true
+            }
+            // Page addressing mode start page and column.
+            [page @ 0xB0..=0xB7] => {
+                self.page = usize::from(page & 0x07);
+                false
+            }
+            [low @ 0x00..=0x0F] => {
+                self.column = (self.column & 0xF0) | usize::from(low);
+                false
+            }
+            [high @ 0x10..=0x1F] => {
+                self.column = ((usize::from(high & 0x0F) << 4) | (self.column & 0x0F)) % WIDTH;
+                false
+            }
+            _ => false,
+        };
+        if redraw {
+            self.show();
+        }
+    }
+
+    fn data_byte(&mut self, byte: u8) {
+        self.ram[self.page * WIDTH + self.column] = byte;
+        if !self.horizontal_addressing {
+            self.column = (self.column + 1) % WIDTH;
+            return;
+        }
+        if self.column < self.columns.1 {
+            self.column += 1;
+            return;
+        }
+        self.column = self.columns.0;
+        if self.page < self.pages.1 {
+            self.page += 1;
+            return;
+        }
+        self.page = self.pages.0;
+        self.show();
+    }
+
+    /// Whether the pixel is lit, taking the remapping set up by the driver
+    /// into account.
+    ///
+    /// The usual setup (segment remap and reverse COM scan) shows the RAM
+    /// as is.
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        let x = if self.segment_remap { x } else { WIDTH - 1 - x };
+        let y = if self.com_scan_reverse {
+            y
+        } else {
+            HEIGHT - 1 - y
+        };
+        let lit = self.ram[(y / 8) * WIDTH + x] & (1 << (y % 8)) != 0;
+        self.on && (lit != self.inverted)
+    }
+
+    fn show(&mut self) {
+        let result = match &self.output {
+            DisplayOutput::Terminal => self.draw_terminal(),
+            DisplayOutput::Png(path) => self.write_png(path),
+        };
+        if let Err(e) = result {
+            eprintln!("Could not show the simulated display: {}", e);
+        }
+        self.first_frame = false;
+    }
+
+    /// Draw the display with half blocks so that each character shows
+    /// two pixel rows.
+    fn draw_terminal(&self) -> io::Result<()> {
+        let mut frame = String::new();
+        if self.first_frame {
+            frame.push_str("\x1b[2J");
+        }
+        frame.push_str("\x1b[H┌");
+        frame.push_str(&"─".repeat(WIDTH));
+        frame.push_str("┐\n");
+        for y in (0..HEIGHT).step_by(2) {
+            frame.push('│');
+            for x in 0..WIDTH {
+                frame.push(match (self.pixel(x, y), self.pixel(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            frame.push_str("│\n");
+        }
+        frame.push('└');
+        frame.push_str(&"─".repeat(WIDTH));
+        frame.push_str("┘\n");
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()
+    }
+
+    /// Write the display to a PNG file.
+    ///
+    /// The image is written to a temporary file first so that viewers
+    /// never see a partially written file.
+    fn write_png(&self, path: &Path) -> io::Result<()> {
+        let (width, height) = (WIDTH * PNG_SCALE, HEIGHT * PNG_SCALE);
+        let mut image = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let lit = self.pixel(x / PNG_SCALE, y / PNG_SCALE);
+                image.push(if lit { 0xFF } else { 0x00 });
+            }
+        }
+        let temporary = path.with_extension("png.tmp");
+        let file = BufWriter::new(File::create(&temporary)?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&image))
+            .map_err(io::Error::other)?;
+        fs::rename(temporary, path)
+    }
+}
+
+impl SimDevice for Ssd1306 {
+    fn write(&mut self, data: &[u8]) {
+        let mut bytes = data.iter();
+        while let Some(control) = bytes.next() {
+            let is_data = control & 0x40 != 0;
+            let continuation = control & 0x80 != 0;
+            // With the continuation bit only one byte follows, then
+            // another control byte.
+            let count = if continuation { 1 } else { usize::MAX };
+            for &byte in bytes.by_ref().take(count) {
+                if is_data {
+                    self.data_byte(byte);
+                } else {
+                    self.command_byte(byte);
+                }
+            }
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        // Status: display on or off.
+        buffer.fill(0);
+        if let Some(status) = buffer.first_mut() {
+            *status = if self.on { 0x00 } else { 0x40 };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display() -> Ssd1306 {
+        let mut display = Ssd1306::new(DisplayOutput::Png(
+            std::env::temp_dir().join("ssd1306-sim-test.png"),
+        ));
+        display.write(&[
+            0x00,
+            Command::ADDRESSING_MODE,
+            0x00,
+            Command::SEGMENT_REMAP_ON,
+            Command::COM_SCAN_REVERSE,
+            0x81,
+            0x8F,
+        ]);
+        display
+    }
+
+    #[test]
+    fn full_frame() {
+        let mut display = display();
+        display.write(&[
+            0x00,
+            Command::COLUMN_ADDRESS,
+            0,
+            127,
+            Command::PAGE_ADDRESS,
+            0,
+            7,
+        ]);
+        let mut frame = vec![0x40];
+        frame.extend_from_slice(&[0; WIDTH * PAGES]);
+        frame[1] = 0b0000_0001;
+        frame[1 + WIDTH + 5] = 0b1000_0000;
+        display.write(&frame);
+        display.write(&[0x00, Command::DISPLAY_ON]);
+
+        assert!(display.pixel(0, 0));
+        assert!(display.pixel(5, 15));
+        assert!(!display.pixel(1, 0));
+        assert_eq!((0, 0), (display.column, display.page));
+    }
+
+    #[test]
+    fn partial_area_and_invert() {
+        let mut display = display();
+        display.write(&[
+            0x00,
+            Command::COLUMN_ADDRESS,
+            10,
+            11,
+            Command::PAGE_ADDRESS,
+            2,
+            3,
+        ]);
+        display.write(&[0x40, 0xFF, 0x00, 0x00, 0xFF]);
+        display.write(&[0x00, Command::DISPLAY_ON, Command::INVERT]);
+
+        assert!(!display.pixel(10, 16));
+        assert!(display.pixel(11, 16));
+        assert!(display.pixel(10, 24));
+        assert!(!display.pixel(11, 24));
+        assert_eq!((10, 2), (display.column, display.page));
+    }
+
+    #[test]
+    fn arguments_split_across_writes() {
+        let mut display = display();
+        display.write(&[0x00, Command::COLUMN_ADDRESS, 4]);
+        display.write(&[0x00, 8]);
+        assert_eq!((4, 8), display.columns);
+    }
+}
@@ -0,0 +1,146 @@
+//! This plays a MIDI file with two voices, e.g. a melody and its bass line,
+//! using two AD9833 waveform generators / direct digital synthesizers and
+//! an MCP42x dual digital potentiometer as volume control.
+//!
+//! `Polyphony` assigns every note of `TRACK` to one of the AD9833 and
+//! shapes its volume with an ADSR envelope scaled by the velocity of the
+//! note. TIM2 ticks every millisecond, times the notes and advances the
+//! envelopes, which set the position of the potentiometer of each voice.
+//! Each voice plays its own waveform, see `WAVEFORMS`.
+//!
+//! The AD9833 share SPI1 with their own FSYNC pin. The MCP42x cannot share
+//! it since it reads the data on the other clock edge, so it is on SPI2.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using SPI1 and
+//! SPI2.
+//!
+//! ```
+//! BP   <-> AD9833 (both)
+//! GND  <-> VSS
+//! 3.3V <-> VDD
+//! PA5  <-> CLK
+//! PA7  <-> DAT
+//! PA4  <-> FSYNC of voice 0
+//! PA3  <-> FSYNC of voice 1
+//!
+//! BP   <-> MCP42x
+//! GND  <-> VSS, PB0, PB1
+//! 3.3V <-> VDD, RS, SHDN
+//! PB13 <-> SCK
+//! PB15 <-> SI
+//! PB12 <-> CS
+//!
+//! AD9833 of voice 0 OUT <-> PA0 of MCP42x
+//! AD9833 of voice 1 OUT <-> PA1 of MCP42x
+//! MCP42x PW0 <-> 10K resistor <-> Amplifier IN
+//! MCP42x PW1 <-> 10K resistor <-> Amplifier IN
+//! ```
+//!
+//! You will need an amplifier like the PAM8403 or similar and a speaker.
+//!
+//! Run with:
+//! `cargo embed --example ad9833-duo-synth-bp --release`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use ad983x::Ad983x;
+use core::iter;
+use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging,
+    midi::{Cue, EventKind, Performance, Smf},
+    synth::{Adsr, NotePlayer, Polyphony, Waveform, ODE_TO_JOY_DUET},
+    Board, Compat,
+};
+use mcp4x::{Channel, Mcp4x};
+use nb::block;
+use stm32f1xx_hal::{prelude::*, timer::Timer};
+
+/// MIDI file to play.
+const SONG: &[u8] = ODE_TO_JOY_DUET;
+/// Track with the notes. Track 0 of format 1 files usually only has the
+/// tempo.
+const TRACK: usize = 1;
+/// Frequency of the timer which times the notes and the envelopes.
+const TICK_HZ: u32 = 1_000;
+/// Volume envelope of every note.
+const ADSR: Adsr = Adsr {
+    attack_ms: 10,
+    decay_ms: 150,
+    sustain: 160,
+    release_ms: 200,
+};
+/// Waveform of each voice. Note that the square wave is much louder.
+const WAVEFORMS: [Waveform; 2] = [Waveform::Sine, Waveform::Triangle];
+/// Potentiometer channel setting the volume of each voice.
+const CHANNELS: [Channel; 2] = [Channel::Ch0, Channel::Ch1];
+/// Pause before playing the song again, so that the last notes fade out.
+const PAUSE_MS: u32 = 1_000;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("AD9833 duo synthesizer example");
+    let mut board = Board::take().unwrap();
+    let spi1 = board.spi1(ad983x::MODE, 1.mhz()).unwrap();
+    let spi2 = board.spi2(mcp4x::MODE, 1.mhz()).unwrap();
+    let cs0 = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    let cs1 = board.gpioa.pa3.into_push_pull_output(&mut board.gpioa.crl);
+    let pot_cs = board.gpiob.pb12.into_push_pull_output(&mut board.gpiob.crh);
+    let Board {
+        clocks,
+        led,
+        heartbeat,
+        tim2,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut timer = Timer::tim2(tim2, &clocks).start_count_down(TICK_HZ.hz());
+
+    let mut synth0 = Ad983x::new_ad9833(spi1.acquire_spi(Compat::new(cs0)).unwrap());
+    let mut synth1 = Ad983x::new_ad9833(spi1.acquire_spi(Compat::new(cs1)).unwrap());
+    synth0.reset().unwrap();
+    synth1.reset().unwrap();
+    synth0.set_output_waveform(WAVEFORMS[0].into()).unwrap();
+    synth1.set_output_waveform(WAVEFORMS[1].into()).unwrap();
+    synth0.enable().unwrap();
+    synth1.enable().unwrap();
+    let mut digipot = Mcp4x::new_mcp42x(spi2.acquire_spi(Compat::new(pot_cs)).unwrap());
+    digipot.set_position(Channel::All, 0).unwrap();
+
+    let smf = Smf::parse(SONG).unwrap();
+    info!("Playing track {} of {}", TRACK, smf.track_count());
+    let mut players = [NotePlayer::default(), NotePlayer::default()];
+    let mut voices = Polyphony::<2>::new(ADSR, TICK_HZ);
+    let pause = Cue {
+        ticks: PAUSE_MS * TICK_HZ / 1_000,
+        kind: EventKind::EndOfTrack,
+    };
+    loop {
+        let cues = Performance::new(&smf, TRACK, TICK_HZ).unwrap();
+        for cue in cues.chain(iter::once(pause)) {
+            for _ in 0..cue.ticks {
+                block!(timer.wait()).unwrap();
+                heartbeat::feed(Status::Healthy);
+                for (channel, level) in CHANNELS.into_iter().zip(voices.tick()) {
+                    if let Some(level) = level {
+                        digipot.set_position(channel, level).unwrap();
+                    }
+                }
+            }
+            match cue.kind {
+                EventKind::NoteOn { key, velocity, .. } => match voices.note_on(key, velocity) {
+                    0 => players[0].play(&mut synth0, Some(key)).unwrap(),
+                    _ => players[1].play(&mut synth1, Some(key)).unwrap(),
+                },
+                EventKind::NoteOff { key, .. } => {
+                    voices.note_off(key);
+                }
+                _ => voices.release_all(),
+            }
+        }
+    }
+}
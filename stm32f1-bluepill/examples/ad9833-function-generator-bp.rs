@@ -0,0 +1,142 @@
+//! Bench function generator using an AD9833 waveform generator / direct
+//! digital synthesizer, an MCP41x digital potentiometer as amplitude control
+//! and an SSD1306 OLED display showing the settings.
+//!
+//! The generator is controlled per serial with SCPI-style commands, one per
+//! line, e.g. `FREQ 1000`, `FUNC SQU`, `PHAS 90`, `AMPL 50`,
+//! `SWEEP 100 10000 2s` or `SWEEP 20 20k 5s LOG`. Queries like `FREQ?` are
+//! answered and errors are reported like `-222,"Data out of range"`. See
+//! `driver_examples_common::generator` for all of them.
+//!
+//! TIM2 ticks every millisecond and advances the sweeps. Every new frequency
+//! and phase is written to the register which is not in use before switching
+//! to it, so that the output has no glitches.
+//!
+//! The MCP41x cannot share SPI1 with the AD9833 since it reads the data on
+//! the other clock edge, so it is on SPI2.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using SPI1, SPI2,
+//! I2C1 and USART1.
+//!
+//! ```
+//! BP   <-> AD9833  <-> Display
+//! GND  <-> VSS     <-> GND
+//! 3.3V <-> VDD     <-> VCC
+//! PA4  <-> FSYNC
+//! PA5  <-> CLK
+//! PA7  <-> DAT
+//! PB9              <-> SDA
+//! PB8              <-> SCL
+//!
+//! BP   <-> MCP41x
+//! GND  <-> VSS, PB0
+//! 3.3V <-> VDD
+//! PB13 <-> SCK
+//! PB15 <-> SI
+//! PB12 <-> CS
+//!
+//! AD9833 OUT <-> PA0 of MCP41x
+//! MCP41x PW0 <-> Output of the generator
+//!
+//! BP   <-> Serial device
+//! GND  <-> GND
+//! PA9  <-> RX
+//! PA10 <-> TX
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example ad9833-function-generator-bp --release`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use ad983x::Ad983x;
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    generator::{amplitude_position, Command, CommandLine, Generator, Output, Settings},
+    heartbeat::{self, Status},
+    info, logging, Board, Compat, TextPanel,
+};
+use mcp4x::{Channel, Mcp4x};
+use nb::block;
+use stm32f1xx_hal::{prelude::*, timer::Timer};
+
+/// Frequency of the timer which advances the sweeps.
+const TICK_HZ: u32 = 1_000;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("AD9833 function generator example");
+    let mut board = Board::take().unwrap();
+    let spi1 = board.spi1(ad983x::MODE, 1.mhz()).unwrap();
+    let spi2 = board.spi2(mcp4x::MODE, 1.mhz()).unwrap();
+    let (mut tx, mut rx) = board.usart1(115_200.bps()).unwrap().split();
+    let synth_cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    let pot_cs = board.gpiob.pb12.into_push_pull_output(&mut board.gpiob.crh);
+    let Board {
+        clocks,
+        led,
+        heartbeat,
+        tim2,
+        display,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut timer = Timer::tim2(tim2, &clocks).start_count_down(TICK_HZ.hz());
+
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let settings = Settings::default();
+    let mut synth = Ad983x::new_ad9833(spi1.acquire_spi(Compat::new(synth_cs)).unwrap());
+    let mut output = Output::default();
+    synth.reset().unwrap();
+    synth.set_output_waveform(settings.waveform.into()).unwrap();
+    output.set_phase(&mut synth, settings.phase_deg).unwrap();
+    synth.enable().unwrap();
+    let mut digipot = Mcp4x::new_mcp41x(spi2.acquire_spi(Compat::new(pot_cs)).unwrap());
+    let position = amplitude_position(settings.amplitude_percent);
+    digipot.set_position(Channel::Ch0, position).unwrap();
+
+    let mut generator = Generator::new(settings, TICK_HZ);
+    let mut command_line = CommandLine::default();
+    generator.settings().show(&mut panel);
+    panel.flush().unwrap();
+    loop {
+        block!(timer.wait()).unwrap();
+        heartbeat::feed(Status::Healthy);
+        if let Some(frequency_hz) = generator.tick() {
+            output.set_frequency(&mut synth, frequency_hz).unwrap();
+        }
+
+        let command = match rx.read().ok().and_then(|byte| command_line.push(byte)) {
+            Some(Ok(command)) => command,
+            Some(Err(error)) => {
+                write!(tx, "{}\r\n", error).unwrap();
+                continue;
+            }
+            None => continue,
+        };
+        info!("Command: {}", command);
+        generator.apply(command);
+        let settings = generator.settings();
+        match command {
+            Command::Function(waveform) => synth.set_output_waveform(waveform.into()).unwrap(),
+            Command::Phase(degrees) => output.set_phase(&mut synth, degrees).unwrap(),
+            Command::Amplitude(percent) => {
+                let position = amplitude_position(percent);
+                digipot.set_position(Channel::Ch0, position).unwrap();
+            }
+            Command::Query(query) => {
+                settings.answer(query, &mut tx).unwrap();
+                write!(tx, "\r\n").unwrap();
+            }
+            _ => {}
+        }
+        settings.show(&mut panel);
+        panel.flush().unwrap();
+    }
+}
@@ -1,5 +1,10 @@
-//! This plays the final part of Beethoven's ninth symphony given by
-//! its MIDI tones using an AD9833 waveform generator / direct digital synthesizer.
+//! This plays the final part of Beethoven's ninth symphony from a MIDI file
+//! using an AD9833 waveform generator / direct digital synthesizer.
+//!
+//! The file is included in the program and parsed with the `midi` module.
+//! TIM2 ticks every millisecond and times the notes following the tempo of
+//! the file, so any format 0 or 1 file can be played by changing `SONG`.
+//! When several notes of `TRACK` sound at once, the highest one is played.
 //!
 //! You can see a video of this running here:
 //! https://blog.eldruin.com/ad983x-waveform-generator-dds-driver-in-rust/
@@ -25,191 +30,58 @@
 #![no_std]
 #![no_main]
 
-use ad983x::{Ad983x, FrequencyRegister, MODE};
+use ad983x::{Ad983x, MODE};
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{delay::Delay, pac, prelude::*, spi::Spi};
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging,
+    midi::{Melody, Smf},
+    synth::{NotePlayer, ODE_TO_JOY},
+    Board, Compat,
+};
+use nb::block;
+use stm32f1xx_hal::{prelude::*, timer::Timer};
+
+/// MIDI file to play.
+const SONG: &[u8] = ODE_TO_JOY;
+/// Track with the melody. Track 0 of format 1 files usually only has the
+/// tempo.
+const TRACK: usize = 1;
+/// Frequency of the timer which times the notes.
+const TICK_HZ: u32 = 1_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("AD9833 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpioa = dp.GPIOA.split();
-
-    // SPI1
-    let sck = gpioa.pa5.into_alternate_push_pull(&mut gpioa.crl);
-    let miso = gpioa.pa6;
-    let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
-    let mut cs = gpioa.pa4.into_push_pull_output(&mut gpioa.crl);
-
-    let spi = Spi::spi1(
-        dp.SPI1,
-        (sck, miso, mosi),
-        &mut afio.mapr,
-        MODE,
-        1_u32.mhz(),
+    logging::init();
+    info!("AD9833 example");
+    let mut board = Board::take().unwrap();
+    let spi1 = board.spi1(MODE, 1.mhz()).unwrap();
+    let cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    let spi = spi1.acquire_spi(Compat::new(cs)).unwrap();
+    let Board {
         clocks,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-
-    cs.set_high();
-
-    let mut synth = Ad983x::new_ad9833(spi, cs);
+        led,
+        heartbeat,
+        tim2,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut timer = Timer::tim2(tim2, &clocks).start_count_down(TICK_HZ.hz());
+
+    let mut synth = Ad983x::new_ad9833(spi);
     synth.reset().unwrap();
     synth.enable().unwrap();
 
-    let mut current_register = FrequencyRegister::F0;
-    let mut table = MidiTable::default();
+    let smf = Smf::parse(SONG).unwrap();
+    info!("Playing track {} of {}", TRACK, smf.track_count());
+    let mut player = NotePlayer::default();
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 does not blink, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(25_u16);
-
-        let midi_number = table.next().unwrap_or(0);
-        let midi_number = f64::from(midi_number);
-        let frequency_hz = libm::pow(2.0, (midi_number - 69.0) / 12.0) * 440.0;
-        let mclk_hz = 25_000_000.0;
-        let synth_value = frequency_hz * f64::from(1 << 28) / mclk_hz;
-
-        // To ensure a smooth transition, set the frequency in the frequency
-        // register that is not currently in use, then switch to it.
-        let opposite = get_opposite(current_register);
-        synth.set_frequency(opposite, synth_value as u32).unwrap();
-        synth.select_frequency(opposite).unwrap();
-        current_register = opposite;
-    }
-}
-
-fn get_opposite(register: FrequencyRegister) -> FrequencyRegister {
-    match register {
-        FrequencyRegister::F0 => FrequencyRegister::F1,
-        FrequencyRegister::F1 => FrequencyRegister::F0,
-    }
-}
-
-#[derive(Debug, Default)]
-struct MidiTable {
-    position: usize,
-    duration_counter: usize,
-}
-
-impl Iterator for MidiTable {
-    type Item = u32;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut silence = None;
-        let (_, note_duration, silence_duration) = Self::NOTES[self.position];
-        let total_duration = note_duration + silence_duration;
-        let is_in_silence =
-            self.duration_counter >= note_duration && self.duration_counter < total_duration;
-        if is_in_silence {
-            self.duration_counter += 1;
-            silence = Some(0);
-        } else if self.duration_counter >= total_duration {
-            self.position = (self.position + 1) % Self::NOTES.len();
-            self.duration_counter = 1;
-        } else {
-            self.duration_counter += 1;
+        for step in Melody::new(&smf, TRACK, TICK_HZ).unwrap() {
+            for _ in 0..step.ticks {
+                block!(timer.wait()).unwrap();
+                heartbeat::feed(Status::Healthy);
+            }
+            player.play(&mut synth, step.note).unwrap();
         }
-        let tone = Some(Self::NOTES[self.position].0);
-        silence.or(tone)
     }
 }
-
-impl MidiTable {
-    const NOTES: [(u32, usize, usize); 62] = [
-        (76, 4, 1),
-        (76, 4, 1),
-        (77, 4, 1),
-        (79, 4, 1),
-        //
-        (79, 4, 1),
-        (77, 4, 1),
-        (76, 4, 1),
-        (74, 4, 1),
-        //
-        (72, 4, 1),
-        (72, 4, 1),
-        (74, 4, 1),
-        (76, 4, 1),
-        //
-        (76, 4, 4),
-        (74, 2, 1),
-        (74, 6, 4),
-        //
-        (76, 4, 1),
-        (76, 4, 1),
-        (77, 4, 1),
-        (79, 4, 1),
-        //
-        (79, 4, 1),
-        (77, 4, 1),
-        (76, 4, 1),
-        (74, 4, 1),
-        //
-        (72, 4, 1),
-        (72, 4, 1),
-        (74, 4, 1),
-        (76, 4, 1),
-        //
-        (74, 4, 4),
-        (72, 2, 1),
-        (72, 6, 4),
-        //
-        (74, 4, 1),
-        (74, 4, 1),
-        (76, 4, 1),
-        (72, 4, 1),
-        //
-        (74, 4, 1),
-        (76, 2, 1),
-        (77, 2, 1),
-        (76, 4, 1),
-        (72, 4, 1),
-        //
-        (74, 4, 1),
-        (76, 2, 1),
-        (77, 2, 1),
-        (76, 4, 1),
-        (74, 4, 1),
-        //
-        (72, 4, 1),
-        (74, 4, 1),
-        (67, 6, 2),
-        //
-        (76, 4, 1),
-        (76, 4, 1),
-        (77, 4, 1),
-        (79, 4, 1),
-        //
-        (79, 4, 1),
-        (77, 4, 1),
-        (76, 4, 1),
-        (74, 4, 1),
-        //
-        (72, 4, 1),
-        (72, 4, 1),
-        (74, 4, 1),
-        (76, 4, 1),
-        //
-        (74, 6, 2),
-        (72, 2, 1),
-        (72, 6, 10),
-    ];
-}
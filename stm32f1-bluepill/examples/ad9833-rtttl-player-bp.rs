@@ -0,0 +1,123 @@
+//! Play ringtones in the RTTTL (Nokia ringtone) format using an AD9833
+//! waveform generator / direct digital synthesizer.
+//!
+//! The built-in library has some short status jingles and a few melodies,
+//! see `driver_examples_common::rtttl`. Pressing the button plays the next
+//! tune. Per serial, send the number or the name of a tune followed by a new
+//! line to play it, `stop` to stop playing and `list` to show the tunes.
+//!
+//! TIM2 ticks every millisecond and times the notes.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using SPI1 and
+//! USART1.
+//!
+//! ```
+//! BP   <-> AD9833  <-> Amplifier
+//! GND  <-> VSS     <-> GND
+//! 3.3V <-> VDD     <-> VCC
+//! PA4  <-> FSYNC
+//! PA5  <-> CLK
+//! PA7  <-> DAT
+//!          OUT     <-> IN
+//!
+//! BP   <-> Serial device
+//! GND  <-> GND
+//! PA9  <-> RX
+//! PA10 <-> TX
+//!
+//! PA0  <-> Button  <-> +3.3V
+//! ```
+//!
+//! You will need an amplifier like the PAM8403 or similar and a speaker.
+//!
+//! Run with:
+//! `cargo embed --example ad9833-rtttl-player-bp --release`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use ad983x::{Ad983x, MODE};
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging,
+    rtttl::{Command, CommandLine, Jukebox, Rtttl, LIBRARY},
+    synth::NotePlayer,
+    Board, Compat,
+};
+use nb::block;
+use stm32f1xx_hal::{prelude::*, timer::Timer};
+
+/// Frequency of the timer which times the notes.
+const TICK_HZ: u32 = 1_000;
+/// The button is read every 20 ms, which is longer than it bounces.
+const BUTTON_TICKS: u32 = 20;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("AD9833 RTTTL example");
+    let mut board = Board::take().unwrap();
+    let spi1 = board.spi1(MODE, 1.mhz()).unwrap();
+    let (mut tx, mut rx) = board.usart1(115_200.bps()).unwrap().split();
+    let cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    let spi = spi1.acquire_spi(Compat::new(cs)).unwrap();
+    let button = board.gpioa.pa0.into_pull_down_input(&mut board.gpioa.crl);
+    let Board {
+        clocks,
+        led,
+        heartbeat,
+        tim2,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut timer = Timer::tim2(tim2, &clocks).start_count_down(TICK_HZ.hz());
+
+    let mut synth = Ad983x::new_ad9833(spi);
+    synth.reset().unwrap();
+    synth.enable().unwrap();
+
+    let mut player = NotePlayer::default();
+    let mut jukebox = Jukebox::new(TICK_HZ);
+    let mut command_line = CommandLine::default();
+    let mut was_pressed = false;
+    let mut ticks = 0;
+    info!("Playing {}", jukebox.play(0).unwrap());
+    loop {
+        block!(timer.wait()).unwrap();
+        heartbeat::feed(Status::Healthy);
+        ticks += 1;
+
+        let mut selected = None;
+        if let Ok(byte) = rx.read() {
+            match command_line.push(byte) {
+                Some(Command::Play(index)) => selected = Some(jukebox.play(index)),
+                Some(Command::Stop) => jukebox.stop(),
+                Some(Command::List) => {
+                    for (number, tune) in LIBRARY.iter().enumerate() {
+                        let name = Rtttl::parse(tune).unwrap().name();
+                        write!(tx, "{}: {}\r\n", number + 1, name).unwrap();
+                    }
+                }
+                Some(Command::Unknown) => write!(tx, "Unknown command\r\n").unwrap(),
+                None => {}
+            }
+        }
+        if ticks % BUTTON_TICKS == 0 {
+            let pressed = button.is_high();
+            if pressed && !was_pressed {
+                selected = Some(jukebox.play_next());
+            }
+            was_pressed = pressed;
+        }
+        if let Some(name) = selected {
+            info!("Playing {}", name.unwrap());
+        }
+
+        if let Some(note) = jukebox.tick() {
+            player.play(&mut synth, note).unwrap();
+        }
+    }
+}
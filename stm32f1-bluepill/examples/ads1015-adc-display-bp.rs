@@ -64,106 +64,68 @@
 #![no_std]
 #![no_main]
 
-use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, SlaveAddr};
+use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, TargetAddr};
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel,
 };
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("ADS1015 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    logging::init();
+    info!("ADS1015 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
 
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut adc = Ads1x1x::new_ads1015(manager.acquire_i2c(), SlaveAddr::default());
+    let mut adc = Ads1x1x::new_ads1015(i2c.acquire_i2c(), TargetAddr::default());
     // need to be able to measure [0-5V]
     adc.set_full_scale_range(FullScaleRange::Within6_144V)
         .unwrap();
+    // The ADC has to be configured again if the bus gets stuck.
+    i2c.lock(|i2c| {
+        i2c.on_recovery(|i2c| {
+            Ads1x1x::new_ads1015(i2c, TargetAddr::default())
+                .set_full_scale_range(FullScaleRange::Within6_144V)
+                .is_ok()
+        })
+    })
+    .unwrap();
 
+    let mut monitor = Monitor::new("ADS1015");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
         // Read voltage in all channels
-        let values = [
-            block!(adc.read(&mut AdcChannel::SingleA0)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA1)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA2)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA3)).unwrap_or(8091),
-        ];
-
-        let mut lines: [heapless::String<32>; 4] = [
-            heapless::String::new(),
-            heapless::String::new(),
-            heapless::String::new(),
-            heapless::String::new(),
-        ];
-
-        disp.clear();
-        for i in 0..values.len() {
-            write!(lines[i], "Channel {}: {}", i, values[i]).unwrap();
-            Text::new(&lines[i], Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+        let reading = monitor.read(&mut delay, || {
+            block!(adc.read(AdcChannel::SingleA0)).and_then(|a0| {
+                Ok([
+                    a0,
+                    block!(adc.read(AdcChannel::SingleA1))?,
+                    block!(adc.read(AdcChannel::SingleA2))?,
+                    block!(adc.read(AdcChannel::SingleA3))?,
+                ])
+            })
+        });
+        panel
+            .show_reading(&reading, |panel, values| {
+                for (i, value) in values.iter().enumerate() {
+                    write!(panel.row(i), "Channel {}: {}", i, value).unwrap();
+                }
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
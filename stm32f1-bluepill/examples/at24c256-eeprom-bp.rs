@@ -1,5 +1,5 @@
 //! Stores some data on an AT24C256C EEPROM.
-//! Then reads it again and if it matches, blinks LED 0.
+//! Then reads it again and shows on the heartbeat LED whether it matches.
 //!
 //! Introductory blog post here:
 //! https://blog.eldruin.com/24x-serial-eeprom-driver-in-rust/
@@ -22,67 +22,44 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use eeprom24x::{Eeprom24x, SlaveAddr};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board,
 };
+use eeprom24x::{Eeprom24x, SlaveAddr};
+use embedded_hal::delay::DelayNs;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("AT24C256 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    logging::init();
+    info!("AT24C256 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
 
-    let mut eeprom = Eeprom24x::new_24x256(i2c, SlaveAddr::Alternative(true, true, true));
+    let mut eeprom =
+        Eeprom24x::new_24x256(i2c.acquire_i2c(), SlaveAddr::Alternative(true, true, true));
     let memory_address = 0x01;
     eeprom
         .write_page(memory_address, &[0xAB, 0xCD, 0xEF, 0x12])
         .unwrap();
 
     // wait maximum time necessary for write
-    delay.delay_ms(5_u16);
+    delay.delay_ms(5);
     loop {
         let mut data = [0; 4];
         eeprom.read_data(memory_address, &mut data).unwrap();
+        // The LED blinks as healthy if the data was written correctly.
         if data == [0xAB, 0xCD, 0xEF, 0x12] {
-            led.set_high();
-            delay.delay_ms(250_u16);
-            led.set_low();
-            delay.delay_ms(250_u16);
+            heartbeat::feed(Status::Healthy);
+        } else {
+            heartbeat::feed(Status::SensorError);
         }
+        delay.delay_ms(500);
     }
 }
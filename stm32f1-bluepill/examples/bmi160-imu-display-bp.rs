@@ -18,116 +18,59 @@
 #![no_std]
 #![no_main]
 
-use bmi160::{
-    AccelerometerPowerMode, Bmi160, Data, GyroscopePowerMode, Sensor3DData, SensorSelector,
-    SlaveAddr,
-};
+use bmi160::{AccelerometerPowerMode, Bmi160, GyroscopePowerMode, SensorSelector, SlaveAddr};
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
-};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel,
 };
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("BMI160 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    logging::init();
+    info!("BMI160 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
 
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut imu = Bmi160::new_with_i2c(manager.acquire_i2c(), SlaveAddr::Alternative(true));
+    let mut imu = Bmi160::new_with_i2c(i2c.acquire_i2c(), SlaveAddr::Alternative(true));
     imu.set_accel_power_mode(AccelerometerPowerMode::Normal)
         .unwrap();
     imu.set_gyro_power_mode(GyroscopePowerMode::Normal).unwrap();
 
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
-    let default_3ddata = Sensor3DData {
-        x: -1,
-        y: -1,
-        z: -1,
-    };
-    let default_data = Data {
-        accel: Some(default_3ddata),
-        gyro: Some(default_3ddata),
-        magnet: None,
-        time: None,
-    };
-
+    let mut monitor = Monitor::new("BMI160");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let data = imu
-            .data(SensorSelector::new().accel().gyro())
-            .unwrap_or(default_data);
-        let accel = data.accel.unwrap();
-        let gyro = data.gyro.unwrap();
-
-        lines[0].clear();
-        lines[1].clear();
-        write!(lines[0], "acc: x {} y {} z {}", accel.x, accel.y, accel.z).unwrap();
-        write!(lines[1], "gyr: x {} y {} z {}", gyro.x, gyro.y, gyro.z).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || {
+            imu.data(SensorSelector::new().accel().gyro())
+        });
+        panel
+            .show_reading(&reading, |panel, data| {
+                // Both were selected so they are always there.
+                let accel = data.accel.unwrap();
+                let gyro = data.gyro.unwrap();
+                write!(
+                    panel.row(0),
+                    "acc: x {} y {} z {}",
+                    accel.x,
+                    accel.y,
+                    accel.z
+                )
                 .unwrap();
+                write!(panel.row(1), "gyr: x {} y {} z {}", gyro.x, gyro.y, gyro.z).unwrap();
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -4,6 +4,9 @@
 //! Introductory blog post with some pictures here:
 //! https://blog.eldruin.com/ccs811-indoor-air-quality-sensor-driver-in-rust/
 //!
+//! If a transfer hangs, the independent watchdog resets the board, which
+//! then shows "Recovered from watchdog reset" for two seconds.
+//!
 //! This example is runs on the STM32F103 "Bluepill" board using I2C1.
 //!
 //! ```
@@ -25,115 +28,71 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_ccs811::{prelude::*, AlgorithmResult, Ccs811Awake, MeasurementMode, SlaveAddr};
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board, Monitor, TextPanel,
 };
-use heapless::String;
+use embedded_ccs811::{prelude::*, Ccs811Awake, MeasurementMode, SlaveAddr};
+use embedded_hal::delay::DelayNs;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
+
+/// The board is reset if a task does not check in for this long.
+const WATCHDOG_TIMEOUT_MS: u32 = 5_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("CCS811 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-    let mut nwake = gpiob.pb7.into_push_pull_output(&mut gpiob.crl);
-    nwake.set_high();
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
+    logging::init();
+    info!("CCS811 example");
+    let mut board = Board::take().unwrap();
+    let mut watchdog = board.watchdog().unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        reset_cause,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    info!("Reset cause: {}", reset_cause.description());
+    if reset_cause.is_watchdog() {
+        panel.set_row(0, "Recovered from");
+        panel.set_row(1, "watchdog reset");
+        panel.flush().unwrap();
+        delay.delay_ms(2000);
+        panel.clear();
+    }
 
-    let mut ccs811 = Ccs811Awake::new(manager.acquire_i2c(), SlaveAddr::default());
+    let mut ccs811 = Ccs811Awake::new(i2c.acquire_i2c(), SlaveAddr::default());
     ccs811.software_reset().unwrap();
-    delay.delay_ms(10_u16);
-    let mut lines: [String<32>; 2] = [String::new(), String::new()];
+    delay.delay_ms(10);
 
     let mut ccs811 = ccs811.start_application().ok().unwrap();
     let temperature_c = 25.0;
     let humidity_perc = 60.0;
     ccs811
-        .set_environment(temperature_c, humidity_perc)
+        .set_environment(humidity_perc, temperature_c)
         .unwrap();
     ccs811.set_mode(MeasurementMode::ConstantPower1s).unwrap();
 
-    let default = AlgorithmResult {
-        eco2: 9999,
-        etvoc: 9999,
-        raw_current: 255,
-        raw_voltage: 9999,
-    };
+    let mut monitor = Monitor::new("CCS811");
+    let gas = watchdog.register("CCS811").unwrap();
+    let screen = watchdog.register("display").unwrap();
+    watchdog.start(WATCHDOG_TIMEOUT_MS);
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(100_u16);
-        led.set_low();
-        delay.delay_ms(100_u16);
-
-        let data = block!(ccs811.data()).unwrap_or(default);
-
-        for line in lines.iter_mut() {
-            line.clear();
-        }
-        write!(lines[0], "eCO2: {}", data.eco2).unwrap();
-        write!(lines[1], "eTVOC: {}", data.etvoc).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
-        }
-        disp.flush().unwrap();
+        let reading = monitor.read(&mut delay, || block!(ccs811.data()));
+        watchdog.check_in(gas);
+        panel
+            .show_reading(&reading, |panel, data| {
+                write!(panel.row(0), "eCO2: {}", data.eco2).unwrap();
+                write!(panel.row(1), "eTVOC: {}", data.etvoc).unwrap();
+            })
+            .unwrap();
+        watchdog.check_in(screen);
+        watchdog.feed();
+        heartbeat::feed(Status::of(&reading));
     }
 }
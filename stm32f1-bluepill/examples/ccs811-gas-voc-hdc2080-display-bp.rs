@@ -6,6 +6,9 @@
 //! Introductory blog post with some pictures here:
 //! https://blog.eldruin.com/ccs811-indoor-air-quality-sensor-driver-in-rust/
 //!
+//! If a transfer hangs, the independent watchdog resets the board, which
+//! then shows "Recovered from watchdog reset" for two seconds.
+//!
 //! This example is runs on the STM32F103 "Bluepill" board using I2C1.
 //!
 //! ```
@@ -27,132 +30,111 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_ccs811::{
-    prelude::*, AlgorithmResult, Ccs811Awake, MeasurementMode, SlaveAddr as Ccs811SlaveAddr,
-};
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    environment::Compensation,
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Compat, Monitor, TextPanel,
 };
+use embedded_ccs811::{prelude::*, Ccs811Awake, MeasurementMode, SlaveAddr as Ccs811SlaveAddr};
+use embedded_hal::delay::DelayNs;
 use hdc20xx::{Hdc20xx, SlaveAddr as Hdc20xxSlaveAddr};
-use heapless::String;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
+
+/// The board is reset if a task does not check in for this long.
+const WATCHDOG_TIMEOUT_MS: u32 = 5_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("CCS811/HDC2080 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-    let mut nwake = gpiob.pb7.into_push_pull_output(&mut gpiob.crl);
-    nwake.set_high();
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
+    logging::init();
+    info!("CCS811/HDC2080 example");
+    let mut board = Board::take().unwrap();
+    let mut watchdog = board.watchdog().unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        reset_cause,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    info!("Reset cause: {}", reset_cause.description());
+    if reset_cause.is_watchdog() {
+        panel.set_row(0, "Recovered from");
+        panel.set_row(1, "watchdog reset");
+        panel.flush().unwrap();
+        delay.delay_ms(2000);
+        panel.clear();
+    }
 
-    let mut hdc2080 = Hdc20xx::new(manager.acquire_i2c(), Hdc20xxSlaveAddr::default());
-    let mut ccs811 = Ccs811Awake::new(manager.acquire_i2c(), Ccs811SlaveAddr::default());
+    let mut hdc2080 = Hdc20xx::new(Compat::new(i2c.acquire_i2c()), Hdc20xxSlaveAddr::default());
+    let mut ccs811 = Ccs811Awake::new(i2c.acquire_i2c(), Ccs811SlaveAddr::default());
     ccs811.software_reset().unwrap();
-    delay.delay_ms(10_u16);
-    let mut lines: [String<32>; 4] = [String::new(), String::new(), String::new(), String::new()];
+    delay.delay_ms(10);
 
     let mut ccs811 = ccs811.start_application().ok().unwrap();
-    let mut env = block!(hdc2080.read()).unwrap();
-    ccs811
-        .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
-        .unwrap();
+    let mut env_monitor = Monitor::new("HDC2080");
+    let mut env = env_monitor.read(&mut delay, || block!(hdc2080.read()));
+    if let Ok(data) = &env {
+        ccs811
+            .set_environment(data.humidity.unwrap_or(0.0), data.temperature)
+            .unwrap();
+    }
     ccs811.set_mode(MeasurementMode::ConstantPower1s).unwrap();
 
-    let default = AlgorithmResult {
-        eco2: 9999,
-        etvoc: 9999,
-        raw_current: 255,
-        raw_voltage: 9999,
-    };
+    let mut compensation = Compensation::new(11);
 
-    let mut counter = 0;
+    let mut monitor = Monitor::new("CCS811");
+    let gas = watchdog.register("CCS811").unwrap();
+    let environment = watchdog.register("HDC2080").unwrap();
+    let screen = watchdog.register("display").unwrap();
+    watchdog.start(WATCHDOG_TIMEOUT_MS);
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(500_u16);
-        led.set_low();
-        delay.delay_ms(500_u16);
-
-        let data = block!(ccs811.data()).unwrap_or(default);
-
-        counter += 1;
-        if counter > 10 {
-            counter = 0;
-
-            env = block!(hdc2080.read()).unwrap();
-            ccs811
-                .set_environment(env.temperature, env.humidity.unwrap_or(0.0))
-                .unwrap();
-        }
-
-        for line in &mut lines {
-            line.clear();
+        let reading = monitor.read(&mut delay, || block!(ccs811.data()));
+        watchdog.check_in(gas);
+
+        // The temperature and humidity change slowly so only update them
+        // every 11 readings.
+        if compensation.is_due() {
+            env = env_monitor.read(&mut delay, || block!(hdc2080.read()));
+            match &env {
+                Ok(data) => {
+                    let humidity = data.humidity.unwrap_or(0.0);
+                    if let Err(error) = ccs811.set_environment(humidity, data.temperature) {
+                        warn!("CCS811 compensation failed: {}", logging::Dbg(&error));
+                    }
+                }
+                Err(failure) => warn!("{}", failure),
+            }
         }
-        write!(lines[0], "eCO2: {}", data.eco2).unwrap();
-        write!(lines[1], "eTVOC: {}", data.etvoc).unwrap();
-        write!(lines[2], "Temp: {:.2}ºC", env.temperature).unwrap();
-        write!(lines[3], "Humidity: {:.2}%", env.humidity.unwrap_or(0.0)).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+        watchdog.check_in(environment);
+
+        panel
+            .show_reading(&reading, |panel, data| {
+                write!(panel.row(0), "eCO2: {}", data.eco2).unwrap();
+                write!(panel.row(1), "eTVOC: {}", data.etvoc).unwrap();
+                match &env {
+                    Ok(env) => {
+                        write!(panel.row(2), "Temp: {:.2}ºC", env.temperature).unwrap();
+                        write!(
+                            panel.row(3),
+                            "Humidity: {:.2}%",
+                            env.humidity.unwrap_or(0.0)
+                        )
+                        .unwrap();
+                    }
+                    Err(failure) => write!(panel.row(2), "{} ERROR", failure.device).unwrap(),
+                }
+            })
+            .unwrap();
+        watchdog.check_in(screen);
+        watchdog.feed();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading).max(Status::of(&env)));
     }
 }
@@ -1,5 +1,5 @@
 //! Stores the date and time on a DS1307 real-time clock (RTC).
-//! Then reads the date and time repeatedly blink LED 0 for 30 seconds.
+//! Then reads the date and time repeatedly and prints them over RTT.
 //!
 //! Introductory blog post here:
 //! https://blog.eldruin.com/ds1307-real-time-clock-rtc-driver-in-rust/
@@ -22,52 +22,27 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use ds1307::{DateTimeAccess, Ds1307, NaiveDate};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board,
 };
+use ds1307::{DateTimeAccess, Ds1307, NaiveDate};
+use embedded_hal::delay::DelayNs;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("DS1307 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut rtc = Ds1307::new(i2c);
+    logging::init();
+    info!("DS1307 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+
+    let mut rtc = Ds1307::new(i2c.acquire_i2c());
     let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
         .unwrap()
         .and_hms_opt(10, 21, 34)
@@ -75,12 +50,8 @@ fn main() -> ! {
     rtc.set_datetime(&begin).unwrap();
     loop {
         let now = rtc.datetime().unwrap();
-        if (now - begin).num_seconds() < 30 {
-            // this will blink for 30 seconds
-            led.set_high();
-            delay.delay_ms(250_u16);
-            led.set_low();
-            delay.delay_ms(250_u16);
-        }
+        info!("Date/Time: {}", logging::Dbg(&now));
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(1000);
     }
 }
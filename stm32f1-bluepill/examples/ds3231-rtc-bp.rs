@@ -20,66 +20,36 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use ds323x::{DateTimeAccess, Ds323x, NaiveDate};
-
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board,
 };
+use ds323x::{DateTimeAccess, Ds323x, NaiveDate};
+use embedded_hal::delay::DelayNs;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("DS3231 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut rtc = Ds323x::new_ds3231(i2c);
+    logging::init();
+    info!("DS3231 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+
+    let mut rtc = Ds323x::new_ds3231(i2c.acquire_i2c());
     let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
         .unwrap()
         .and_hms_opt(10, 21, 34)
         .unwrap();
     rtc.set_datetime(&begin).unwrap();
     loop {
-        led.set_high();
-        delay.delay_ms(250_u16);
-        led.set_low();
-        delay.delay_ms(750_u16);
-
         let now = rtc.datetime().unwrap();
-        rprintln!("Date/Time: {:?}", now);
+        info!("Date/Time: {}", logging::Dbg(&now));
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(1000);
     }
 }
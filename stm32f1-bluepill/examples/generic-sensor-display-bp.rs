@@ -0,0 +1,91 @@
+//! Continuously measure any sensor through the generic `Sensor` interface,
+//! log every quantity and show them in an SSD1306 OLED display.
+//!
+//! The loop only knows about `Sensor`, so it works with every driver which
+//! has an adapter in `driver_examples_common::sensor`. Replace the LM75
+//! below with any other device, e.g. `Opt300x::new_opt3001(...)` in one-shot
+//! mode. Devices measuring more quantities than fit in the display are shown
+//! a page at a time.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using I2C1.
+//!
+//! ```
+//! BP   <-> LM75   <-> Display
+//! GND  <-> GND    <-> GND
+//! 3.3V <-> VCC    <-> VDD
+//! PB8  <-> SCL    <-> SCL
+//! PB9  <-> SDA    <-> SDA
+//! GND  <-> A0
+//! GND  <-> A1
+//! GND  <-> A2
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example generic-sensor-display-bp --release`
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging,
+    sensor::Sensor,
+    text_panel::ROWS,
+    warn, Board, Display, Monitor, TextPanel, I2C_FAST_MODE,
+};
+use embedded_hal::delay::DelayNs;
+use lm75::{Address, Lm75};
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("Generic sensor example");
+    let Board {
+        delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take_with_i2c_mode(I2C_FAST_MODE).unwrap();
+    heartbeat.start(led);
+
+    let lm75 = Lm75::new(i2c.acquire_i2c(), Address::default());
+    run(lm75, display, delay)
+}
+
+/// Measure the sensor, log and show the quantities forever.
+fn run<S: Sensor>(mut sensor: S, display: Display, mut delay: impl DelayNs) -> ! {
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    panel.page(sensor.name());
+
+    let mut monitor = Monitor::new(sensor.name());
+    let mut page = 0;
+    loop {
+        let reading = monitor.read(&mut delay, || sensor.measure());
+        panel
+            .show_reading(&reading, |panel, quantities| {
+                for quantity in quantities.iter() {
+                    info!("{}", quantity);
+                }
+                // Show the next rows if they do not fit at once.
+                let pages = quantities.len().div_ceil(ROWS).max(1);
+                page %= pages;
+                panel.clear_rows();
+                for (row, quantity) in quantities.iter().skip(page * ROWS).take(ROWS).enumerate() {
+                    write!(panel.row(row), "{}", quantity).unwrap();
+                }
+                page += 1;
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
+        }
+        heartbeat::feed(Status::of(&reading));
+        delay.delay_ms(1000);
+    }
+}
@@ -0,0 +1,92 @@
+//! Scan the I2C bus and identify the devices which are connected to it.
+//!
+//! Every address is probed and for each device which answers, the identity
+//! registers of the supported parts which can be at that address are read.
+//! The results are shown on an SSD1306 OLED display four at a time and
+//! printed through RTT and USART1 (or the USB port with the `usb-serial`
+//! feature), e.g.:
+//!
+//! ```text
+//! 0x3C: SSD1306/PCF8574A?
+//! 0x40: HDC2080 (device id 0x07D0)
+//! 0x5A: CCS811 (hw id 0x81)
+//! ```
+//!
+//! This is useful to check the wiring before running any other example.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using I2C1 and
+//! USART1 or USB.
+//!
+//! ```
+//! BP   <-> Devices <-> Display <-> Serial
+//! GND  <-> GND     <-> GND     <-> GND
+//! 3.3V <-> VCC     <-> VDD
+//! PB8  <-> SCL     <-> SCL
+//! PB9  <-> SDA     <-> SDA
+//! PA9                          <-> RX (only for USART1)
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example i2c-scanner-display-bp --release`,
+//! or over USB:
+//! `cargo embed --example i2c-scanner-display-bp --features usb-serial --release`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, scan, Board, TextPanel,
+};
+use embedded_hal::delay::DelayNs;
+use stm32f1xx_hal::prelude::*;
+
+const ROWS: usize = 4;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("I2C scanner example");
+    let mut board = Board::take().unwrap();
+    let mut tx = board.serial_output(115_200.bps()).unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut scanner_i2c = i2c.acquire_i2c();
+    loop {
+        panel.page("I2C scan");
+        let mut count = 0;
+        for found in scan::scan(&mut scanner_i2c) {
+            info!("{}", found);
+            writeln!(tx, "{}\r", found).unwrap();
+            if count > 0 && count % ROWS == 0 {
+                panel.flush().unwrap();
+                heartbeat::feed(Status::Healthy);
+                delay.delay_ms(2000);
+                panel.clear_rows();
+            }
+            write!(panel.row(count % ROWS), "{:#}", found).unwrap();
+            count += 1;
+        }
+        info!("{} devices found", count);
+        writeln!(tx, "{} devices found\r", count).unwrap();
+        if count == 0 {
+            panel.set_row(0, "No devices found");
+        }
+        panel.flush().unwrap();
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(2000);
+    }
+}
@@ -1,6 +1,9 @@
 //! Continuously measure the CO2 and TVOC equivalents in the air with an
 //! iAQ-Core-C module and print the values to an SSD1306 OLED display.
 //!
+//! If a transfer hangs, the independent watchdog resets the board, which
+//! then shows "Recovered from watchdog reset" for two seconds.
+//!
 //! This example is runs on the STM32F103 "Bluepill" board using I2C1.
 //!
 //! ```
@@ -20,98 +23,66 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Compat, Monitor, TextPanel,
 };
+use embedded_hal::delay::DelayNs;
 use iaq_core::IaqCore;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, Mode},
-    pac,
-    prelude::*,
-};
+
+/// The board is reset if a task does not check in for this long.
+const WATCHDOG_TIMEOUT_MS: u32 = 5_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("iAQ-Core-C example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
+    logging::init();
+    info!("iAQ-Core-C example");
 
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Standard {
-            frequency: 100_000.hz(),
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
+    let mut board = Board::take().unwrap();
+    let mut watchdog = board.watchdog().unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        reset_cause,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    info!("Reset cause: {}", reset_cause.description());
+    if reset_cause.is_watchdog() {
+        panel.set_row(0, "Recovered from");
+        panel.set_row(1, "watchdog reset");
+        panel.flush().unwrap();
+        delay.delay_ms(2000);
+        panel.clear();
+    }
 
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
+    let mut sensor = IaqCore::new(Compat::new(i2c.acquire_i2c()));
 
-    let mut sensor = IaqCore::new(manager.acquire_i2c());
-    let mut lines: [heapless::String<32>; 3] = [
-        heapless::String::new(),
-        heapless::String::new(),
-        heapless::String::new(),
-    ];
+    let mut monitor = Monitor::new("iAQ-Core");
+    let gas = watchdog.register("iAQ-Core").unwrap();
+    let screen = watchdog.register("display").unwrap();
+    watchdog.start(WATCHDOG_TIMEOUT_MS);
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let data = block!(sensor.data()).unwrap_or_default();
-
-        lines[0].clear();
-        lines[1].clear();
-        lines[2].clear();
-        write!(lines[0], "CO2: {} ppm  ", data.co2).unwrap();
-        write!(lines[1], "TVOC: {} ppb  ", data.tvoc).unwrap();
-        write!(lines[2], "Resistance: {} Ohm  ", data.resistance).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+        let reading = monitor.read(&mut delay, || block!(sensor.data()));
+        watchdog.check_in(gas);
+        panel
+            .show_reading(&reading, |panel, data| {
+                write!(panel.row(0), "CO2: {} ppm  ", data.co2).unwrap();
+                write!(panel.row(1), "TVOC: {} ppb  ", data.tvoc).unwrap();
+                write!(panel.row(2), "Resistance: {} Ohm  ", data.resistance).unwrap();
+            })
+            .unwrap();
+        watchdog.check_in(screen);
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
+        watchdog.feed();
     }
 }
@@ -1,6 +1,9 @@
 //! Continuously measure the charge and voltage of a battery with an MAX17043
 //! battery monitor and print it to an SSD1306 OLED display in lux.
 //!
+//! If a transfer hangs, the independent watchdog resets the board, which
+//! then shows "Recovered from watchdog reset" for two seconds.
+//!
 //! This example is runs on the STM32F103 "Bluepill" board using I2C1.
 //!
 //! ```
@@ -20,94 +23,68 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel,
 };
+use embedded_hal::delay::DelayNs;
 use max170xx::Max17043;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
+
+/// The board is reset if a task does not check in for this long.
+const WATCHDOG_TIMEOUT_MS: u32 = 5_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MAX17043 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
+    logging::init();
+    info!("MAX17043 example");
+    let mut board = Board::take().unwrap();
+    let mut watchdog = board.watchdog().unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        reset_cause,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    info!("Reset cause: {}", reset_cause.description());
+    if reset_cause.is_watchdog() {
+        panel.set_row(0, "Recovered from");
+        panel.set_row(1, "watchdog reset");
+        panel.flush().unwrap();
+        delay.delay_ms(2000);
+        panel.clear();
+    }
 
-    let mut sensor = Max17043::new(manager.acquire_i2c());
+    let mut sensor = Max17043::new(i2c.acquire_i2c());
 
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
+    let mut monitor = Monitor::new("MAX17043");
+    let battery = watchdog.register("MAX17043").unwrap();
+    let screen = watchdog.register("display").unwrap();
+    watchdog.start(WATCHDOG_TIMEOUT_MS);
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let soc = sensor.soc().unwrap();
-        let voltage = sensor.voltage().unwrap();
+        let reading = monitor.read(&mut delay, || {
+            sensor
+                .soc()
+                .and_then(|soc| sensor.voltage().map(|voltage| (soc, voltage)))
+        });
+        watchdog.check_in(battery);
 
-        lines[0].clear();
-        lines[1].clear();
-        write!(lines[0], "Charge: {:.2}%   ", soc).unwrap();
-        write!(lines[1], "Voltage: {:.2}V   ", voltage).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+        panel
+            .show_reading(&reading, |panel, (soc, voltage)| {
+                write!(panel.row(0), "Charge: {:.2}%   ", soc).unwrap();
+                write!(panel.row(1), "Voltage: {:.2}V   ", voltage).unwrap();
+            })
+            .unwrap();
+        watchdog.check_in(screen);
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
+        watchdog.feed();
     }
 }
@@ -1,91 +1,72 @@
-//! Continuously read the heart data and send it through USART.
+//! Continuously read the heart data and send it through USART1 or, with the
+//! `usb-serial` feature, through the USB port.
 //!
-//! This example is runs on the STM32F103 "Bluepill" board using I2C1 and USART1.
+//! Every infrared sample is sent as a binary telemetry frame tagged with the
+//! device, channel, unit and time, see `driver_examples_common::telemetry`.
+//! The samples read from the FIFO at once share the time they were read at.
+//! Decode them on the computer with the `telemetry` crate.
 //!
-//! To setup the serial communication, have a look at the discovery book:
+//! This example is runs on the STM32F103 "Bluepill" board using I2C1 and
+//! USART1 remapped to PB6/PB7 or USB.
+//!
+//! To setup the serial communication over USART1, have a look at the
+//! discovery book:
 //! https://rust-embedded.github.io/discovery/10-serial-communication/index.html
 //!
+//! Over USB, no adapter is needed: open the serial port of the board
+//! (e.g. `/dev/ttyACM0`) on the computer.
+//!
 //! ```
 //! BP   <-> MAX30102 <-> Serial
 //! GND  <-> GND      <-> GND
 //! 3.3V <-> VCC      <-> VDD
-//! PB6               <-> RX
+//! PB6               <-> RX (only for USART1)
 //! PB8  <-> SCL
 //! PB9  <-> SDA
 //! ```
 //!
 //! Run with:
 //! `cargo embed --example max30102-heart-usart-bp --release`,
+//! or over USB:
+//! `cargo embed --example max30102-heart-usart-bp --features usb-serial --release`,
 
 #![deny(unsafe_code)]
 #![no_std]
 #![no_main]
 
-use core::fmt::Write;
 use cortex_m_rt::entry;
-use max3010x::{Led, LedPulseWidth, Max3010x, SampleAveraging, SamplingRate};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-    serial,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging,
+    sensor::Unit,
+    telemetry::{self, Sample, MAX_FRAME_LEN},
+    warn, Board, Monitor, I2C_FAST_MODE,
 };
+use embedded_hal::delay::DelayNs;
+use max3010x::{Led, LedPulseWidth, Max3010x, SampleAveraging, SamplingRate};
+use nb::block;
+use stm32f1xx_hal::prelude::*;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MAX30102 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc
-        .cfgr
-        .use_hse(8.mhz())
-        .sysclk(72.mhz())
-        .pclk1(36.mhz())
-        .freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
+    logging::init();
+    info!("MAX30102 example");
 
-    let tx = gpiob.pb6.into_alternate_push_pull(&mut gpiob.crl);
-    let rx = gpiob.pb7;
-    let serial = serial::Serial::usart1(
-        dp.USART1,
-        (tx, rx),
-        &mut afio.mapr,
-        serial::Config::default().baudrate(9600.bps()),
-        clocks,
-    );
-    let (mut tx, _rx) = serial.split();
+    let mut board = Board::take_with_i2c_mode(I2C_FAST_MODE).unwrap();
+    let mut tx = board.serial_output_remapped(115_200.bps()).unwrap();
+    let Board {
+        mut delay,
+        mut clock,
+        led,
+        heartbeat,
+        i2c,
+        ..
+    } = board;
+    heartbeat.start(led);
 
-    let mut max30102 = Max3010x::new_max30102(i2c);
+    let mut max30102 = Max3010x::new_max30102(i2c.acquire_i2c());
     max30102.reset().unwrap();
-    delay.delay_ms(100_u8);
+    delay.delay_ms(100);
 
     let mut max30102 = max30102.into_heart_rate().unwrap();
 
@@ -97,13 +78,32 @@ fn main() -> ! {
 
     max30102.clear_fifo().unwrap();
 
-    writeln!(tx, "hr\r").unwrap();
+    let mut monitor = Monitor::new("MAX30102");
+    let mut frame = [0; MAX_FRAME_LEN];
     loop {
-        delay.delay_ms(100_u8);
+        delay.delay_ms(100);
         let mut data = [0; 16];
-        let read = max30102.read_fifo(&mut data).unwrap_or(0);
+        let read = match monitor.read(&mut delay, || max30102.read_fifo(&mut data)) {
+            Ok(read) => read,
+            Err(failure) => {
+                warn!("{}", failure);
+                heartbeat::feed(Status::of_failure(&failure));
+                continue;
+            }
+        };
+        heartbeat::feed(Status::Healthy);
+        let timestamp_ms = clock.now_ms();
         for v in data.iter().take(read.into()) {
-            writeln!(tx, "{}\r", v,).unwrap();
+            let sample = Sample {
+                timestamp_ms,
+                device: "MAX30102",
+                channel: "IR",
+                unit: Unit::Count,
+                value: *v as f32,
+            };
+            for byte in telemetry::encode(&sample, &mut frame).unwrap() {
+                block!(tx.write(*byte)).unwrap();
+            }
         }
     }
 }
@@ -25,61 +25,38 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use embedded_hal::blocking::delay::DelayMs;
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board, Compat,
+};
+use embedded_hal::delay::DelayNs;
 use mcp4x::{Channel, Mcp4x, MODE};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{delay::Delay, pac, prelude::*, spi::Spi};
+use stm32f1xx_hal::prelude::*;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MCP41010 example");
+    logging::init();
+    info!("MCP41010 example");
 
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
+    let mut board = Board::take().unwrap();
+    let spi1 = board.spi1(MODE, 1.mhz()).unwrap();
+    let cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    let spi = spi1.acquire_spi(Compat::new(cs)).unwrap();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        ..
+    } = board;
+    heartbeat.start(led);
 
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpioa = dp.GPIOA.split();
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    // SPI configuration
-    let sck = gpioa.pa5.into_alternate_push_pull(&mut gpioa.crl);
-    let miso = gpioa.pa6;
-    let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
-    let mut cs = gpioa.pa4.into_push_pull_output(&mut gpioa.crl);
-
-    let spi = Spi::spi1(
-        dp.SPI1,
-        (sck, miso, mosi),
-        &mut afio.mapr,
-        MODE,
-        1_u32.mhz(),
-        clocks,
-    );
-
-    cs.set_high();
-
-    let mut digipot = Mcp4x::new_mcp41x(spi, cs);
+    let mut digipot = Mcp4x::new_mcp41x(spi);
 
     let mut position = 0;
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 does not blink, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
         digipot.set_position(Channel::Ch0, position).unwrap();
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(100);
 
         if position == 255 {
             position = 0
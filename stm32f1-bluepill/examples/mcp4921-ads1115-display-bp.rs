@@ -27,130 +27,69 @@
 #![no_std]
 #![no_main]
 
-use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, SlaveAddr};
+use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, TargetAddr};
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    adc::scale_reading,
+    bus::NoChipSelect,
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Compat, Monitor, TextPanel, I2C_FAST_MODE,
 };
-use embedded_hal::adc::OneShot;
-use embedded_hal::blocking::delay::DelayMs;
-use mcp49xx::{Command as DacCommand, Mcp49xx, MODE_0};
+use embedded_hal::{delay::DelayNs, spi::MODE_0};
+use mcp49xx::{Command as DacCommand, Mcp49xx};
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-    spi::Spi,
-};
+use stm32f1xx_hal::prelude::*;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MCP4921 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpioa = dp.GPIOA.split();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    // SPI1
-    let sck = gpioa.pa5.into_alternate_push_pull(&mut gpioa.crl);
-    let miso = gpioa.pa6;
-    let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
-    let mut cs = gpioa.pa4.into_push_pull_output(&mut gpioa.crl);
-
-    let mut spi = Spi::spi1(
-        dp.SPI1,
-        (sck, miso, mosi),
-        &mut afio.mapr,
-        MODE_0,
-        1_u32.mhz(),
-        clocks,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut adc = Ads1x1x::new_ads1115(manager.acquire_i2c(), SlaveAddr::default());
+    logging::init();
+    info!("MCP4921 example");
+    let mut board = Board::take_with_i2c_mode(I2C_FAST_MODE).unwrap();
+    let spi1 = board.spi1(MODE_0, 1.mhz()).unwrap();
+    let cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    // The driver drives the chip select pin itself.
+    let mut spi = Compat::new(spi1.acquire_spi(NoChipSelect).unwrap());
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut adc = Ads1x1x::new_ads1115(i2c.acquire_i2c(), TargetAddr::default());
     // need to be able to measure [0-5V] since that is the reference voltage of the DAC (VREFA)
     adc.set_full_scale_range(FullScaleRange::Within6_144V)
         .unwrap();
 
-    cs.set_high();
-
     let mut dac = Mcp49xx::new_mcp4921(cs);
     let dac_cmd = DacCommand::default();
+    let mut monitor = Monitor::new("ADS1115");
     let mut position = 0;
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 does not blink, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-
         dac.send(&mut spi, dac_cmd.value(position)).unwrap();
 
         // Read voltage in channel 0
-        let value_ch0 = block!(adc.read(&mut AdcChannel::SingleA0)).unwrap();
-
-        // make the number smaller for reading ease
-        let value_ch0 = value_ch0 >> 5;
-
-        let mut buffer: heapless::String<64> = heapless::String::new();
-
-        // write some extra spaces after the number to clear up when the number get smaller
-        write!(buffer, "Channel 0: {}   ", value_ch0).unwrap();
-
-        // print
-        disp.clear();
-        Text::new(&buffer, Point::zero())
-            .into_styled(text_style)
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || block!(adc.read(AdcChannel::SingleA0)));
+        panel
+            .show_reading(&reading, |panel, value_ch0| {
+                // make the number smaller for reading ease
+                let value_ch0 = scale_reading(*value_ch0);
+
+                // write some extra spaces after the number to clear up when the number get smaller
+                write!(panel.row(0), "Channel 0: {}   ", value_ch0).unwrap();
+            })
             .unwrap();
-        disp.flush().unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
+        }
+        heartbeat::feed(Status::of(&reading));
+        delay.delay_ms(50);
 
         // Actually this gets only until 4080.
         // Then it would be too big so we set it to 0.
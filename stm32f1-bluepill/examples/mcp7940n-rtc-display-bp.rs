@@ -23,103 +23,56 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel,
 };
 use mcp794xx::{DateTimeAccess, Datelike, Mcp794xx, NaiveDate, Timelike};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MCP7940N example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
+    logging::init();
+    info!("MCP7940N example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
 
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut rtc = Mcp794xx::new_mcp7940n(manager.acquire_i2c());
+    let mut rtc = Mcp794xx::new_mcp7940n(i2c.acquire_i2c());
     let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
         .unwrap()
         .and_hms_opt(10, 21, 34)
         .unwrap();
     rtc.set_datetime(&begin).unwrap();
     rtc.enable().unwrap();
+    let mut monitor = Monitor::new("MCP7940N");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let now = rtc.datetime().unwrap();
-
-        let mut buffer: heapless::String<32> = heapless::String::new();
-        write!(
-            buffer,
-            "{}-{}-{} {}:{}:{}   ",
-            now.year(),
-            now.month(),
-            now.day(),
-            now.hour(),
-            now.minute(),
-            now.second()
-        )
-        .unwrap();
-        disp.clear();
-        Text::new(&buffer, Point::zero())
-            .into_styled(text_style)
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || rtc.datetime());
+        panel
+            .show_reading(&reading, |panel, now| {
+                write!(
+                    panel.row(0),
+                    "{}-{}-{} {}:{}:{}   ",
+                    now.year(),
+                    now.month(),
+                    now.day(),
+                    now.hour(),
+                    now.minute(),
+                    now.second()
+                )
+                .unwrap();
+            })
             .unwrap();
-
-        disp.flush().unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
+        }
+        heartbeat::feed(Status::of(&reading));
     }
 }
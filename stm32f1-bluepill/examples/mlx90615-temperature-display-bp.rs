@@ -20,97 +20,50 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel,
 };
+use embedded_hal::delay::DelayNs;
 use mlx9061x::{Mlx9061x, SlaveAddr};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MAX90615 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
+    logging::init();
+    info!("MAX90615 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
 
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    let mut sensor = Mlx9061x::new_mlx90615(i2c.acquire_i2c(), SlaveAddr::default(), 5).unwrap();
 
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    //disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut sensor =
-        Mlx9061x::new_mlx90615(manager.acquire_i2c(), SlaveAddr::default(), 5).unwrap();
-
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
+    let mut object_monitor = Monitor::new("MLX90615");
+    let mut ambient_monitor = Monitor::new("MLX90615");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let t_obj = sensor.object_temperature().unwrap_or(-1.0);
-        delay.delay_ms(50_u16); // a pause is necessary in between
-        let t_a = sensor.ambient_temperature().unwrap_or(-1.0);
-
-        lines[0].clear();
-        lines[1].clear();
-        write!(lines[0], "Object: {:.2}ºC", t_obj).unwrap();
-        write!(lines[1], "Ambient: {:.2}ºC", t_a).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+        let object = object_monitor.read(&mut delay, || sensor.object_temperature());
+        delay.delay_ms(50); // a pause is necessary in between
+        let reading = object.and_then(|t_obj| {
+            let t_a = ambient_monitor.read(&mut delay, || sensor.ambient_temperature())?;
+            Ok((t_obj, t_a))
+        });
+        delay.delay_ms(50); // a pause is necessary in between
+        panel
+            .show_reading(&reading, |panel, (t_obj, t_a)| {
+                write!(panel.row(0), "Object: {:.2}ºC", t_obj).unwrap();
+                write!(panel.row(1), "Ambient: {:.2}ºC", t_a).unwrap();
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        delay.delay_ms(50_u16); // a pause is necessary in between
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -20,95 +20,42 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
-};
-use mma8x5x::{Measurement, Mma8x5x, SlaveAddr};
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Compat, Monitor, TextPanel,
 };
+use mma8x5x::{Mma8x5x, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MMA8452 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut buffer: heapless::String<64> = heapless::String::new();
-    let sensor = Mma8x5x::new_mma8452(manager.acquire_i2c(), SlaveAddr::default());
+    logging::init();
+    info!("MMA8452 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let sensor = Mma8x5x::new_mma8452(Compat::new(i2c.acquire_i2c()), SlaveAddr::default());
     let mut sensor = sensor.into_active().ok().unwrap();
 
+    let mut monitor = Monitor::new("MMA8452");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        let def = Measurement {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        };
-        let m = sensor.read().unwrap_or(def);
-
-        buffer.clear();
-        write!(buffer, "{:.2}, {:.2}, {:.2}", m.x, m.y, m.z).unwrap();
-        disp.clear();
-        Text::new(&buffer, Point::zero())
-            .into_styled(text_style)
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || sensor.read());
+        panel
+            .show_reading(&reading, |panel, m| {
+                write!(panel.row(0), "{:.2}, {:.2}, {:.2}", m.x, m.y, m.z).unwrap();
+            })
             .unwrap();
-
-        disp.flush().unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
+        }
+        heartbeat::feed(Status::of(&reading));
     }
 }
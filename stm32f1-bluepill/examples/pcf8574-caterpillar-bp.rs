@@ -19,56 +19,34 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use pcf857x::{Pcf8574, SlaveAddr};
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, Board,
 };
+use embedded_hal::delay::DelayNs;
+use pcf857x::{Pcf8574, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("PCF8574 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 100_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut delay = Delay::new(cp.SYST, clocks);
-    let mut expander = Pcf8574::new(i2c, SlaveAddr::default());
+    logging::init();
+    info!("PCF8574 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        ..
+    } = Board::take().unwrap();
+    heartbeat.start(led);
+    let mut expander = Pcf8574::new(i2c.acquire_i2c(), SlaveAddr::default());
 
     let mut output_status = OutputStatus::new();
 
     loop {
         if let Some(status) = output_status.next() {
             expander.set(status).unwrap();
-            delay.delay_ms(100_u16);
+            heartbeat::feed(Status::Healthy);
+            delay.delay_ms(100);
         }
     }
 }
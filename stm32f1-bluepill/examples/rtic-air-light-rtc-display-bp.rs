@@ -0,0 +1,312 @@
+//! Air quality, ambient light and clock firmware built on RTIC.
+//!
+//! Instead of polling every device in a loop with fixed delays, TIM2
+//! schedules the sampling and the devices tell when they have something new
+//! through their interrupt lines. Every second, TIM2 raises an interrupt
+//! which starts a light measurement on the APDS9960, reads the time from the
+//! DS3231, shows everything on an SSD1306 OLED display and feeds the
+//! heartbeat LED with the status of the readings. Then:
+//! - The APDS9960 pulls INT low when the light measurement is done.
+//! - The CCS811 pulls nINT low when a new eCO2/eTVOC measurement is ready.
+//!   It measures once per second on its own.
+//!
+//! These lines raise EXTI interrupts which read the devices. The readings
+//! are shown on the next tick.
+//!
+//! All tasks share I2C1 through the bus of the board, where every
+//! transaction runs inside a critical section. A task can preempt another
+//! one between transactions but never in the middle of one. This takes the
+//! place of the Cortex-M mutex of `shared_bus`, which the board crate does
+//! not use. The SysTick delay for the retries is shared as well, but it is
+//! only locked while waiting so that a task which reads a device does not
+//! block the others.
+//!
+//! The Si4703 is left out, and so is its STC interrupt on GPIO2. It must
+//! see SDIO low when it comes out of reset to select the I2C interface, but
+//! the board sets up I2C1 on PB8/PB9 before the example can drive SDA as a
+//! GPIO.
+//!
+//! This example is runs on the STM32F103 "Bluepill" board using I2C1.
+//!
+//! ```
+//! BP   <-> CCS811 <-> APDS9960 <-> DS3231 <-> Display
+//! GND  <-> GND    <-> GND      <-> GND    <-> GND
+//! 3.3V <-> VCC    <-> VCC      <-> VCC    <-> VDD
+//! PB8  <-> SCL    <-> SCL      <-> SCL    <-> SCL
+//! PB9  <-> SDA    <-> SDA      <-> SDA    <-> SDA
+//! PB0  <-> nINT
+//! PB1             <-> INT
+//! GND  <-> nWAKE
+//! 3.3V <-> RST
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example rtic-air-light-rtc-display-bp --release`
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use apds9960::LightData;
+use driver_examples_bluepill::{reading::Reading, Compat};
+use embedded_ccs811::AlgorithmResult;
+use embedded_hal::delay::DelayNs;
+use rtic::Mutex;
+use stm32f1xx_hal::delay::Delay;
+
+/// Latest readings shown on the display.
+pub struct Readings {
+    gas: Option<Reading<AlgorithmResult>>,
+    light: Option<Reading<LightData>>,
+}
+
+/// Shared delay which is locked for each wait instead of for a whole
+/// reading.
+pub struct LockedDelay<M>(M);
+
+impl<M: Mutex<T = Compat<Delay>>> DelayNs for LockedDelay<M> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.lock(|delay| delay.delay_ns(ns));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.lock(|delay| delay.delay_us(us));
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.lock(|delay| delay.delay_ms(ms));
+    }
+}
+
+#[rtic::app(device = stm32f1xx_hal::pac, peripherals = true)]
+mod app {
+    use super::{LockedDelay, Readings};
+    use apds9960::Apds9960;
+    use core::fmt::Write;
+    use driver_examples_bluepill::{
+        error,
+        heartbeat::{self, Status},
+        info, logging,
+        reading::Failure,
+        warn, Board, Compat, I2cProxy, Monitor, Panel, TextPanel, I2C_FAST_MODE,
+    };
+    use ds323x::{ic::DS3231, interface::I2cInterface, DateTimeAccess, Ds323x};
+    use embedded_ccs811::{
+        mode::App, prelude::*, Ccs811Awake, InterruptMode, MeasurementMode, SlaveAddr,
+    };
+    use embedded_hal::delay::DelayNs;
+    use ssd1306::prelude::I2CInterface;
+    use stm32f1xx_hal::{
+        delay::Delay,
+        gpio::{
+            gpiob::{PB0, PB1},
+            Edge, ExtiPin, Input, PullUp,
+        },
+        pac::TIM2,
+        prelude::*,
+        timer::{CountDownTimer, Event, Timer},
+    };
+
+    #[shared]
+    struct Shared {
+        /// Delay for the retries of the monitors.
+        delay: Compat<Delay>,
+        readings: Readings,
+        apds9960: Apds9960<Compat<I2cProxy>>,
+    }
+
+    #[local]
+    struct Local {
+        timer: CountDownTimer<TIM2>,
+        panel: Panel<I2CInterface<I2cProxy>>,
+        rtc: Ds323x<I2cInterface<I2cProxy>, DS3231>,
+        rtc_monitor: Monitor,
+        ccs811: Ccs811Awake<I2cProxy, App>,
+        ccs811_int: PB0<Input<PullUp>>,
+        ccs811_monitor: Monitor,
+        apds9960_int: PB1<Input<PullUp>>,
+        apds9960_monitor: Monitor,
+    }
+
+    #[init]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        logging::init();
+        info!("RTIC air quality, light and clock example");
+
+        let Board {
+            clocks,
+            mut delay,
+            led,
+            heartbeat,
+            i2c,
+            display,
+            mut afio,
+            exti,
+            tim2,
+            mut gpiob,
+            ..
+        } = Board::new(cx.core, cx.device, I2C_FAST_MODE).unwrap();
+        heartbeat.start(led);
+        let mut panel = TextPanel::new(display);
+        panel.init().unwrap();
+        panel.page("Starting...");
+        panel.flush().unwrap();
+
+        let rtc = Ds323x::new_ds3231(i2c.acquire_i2c());
+
+        let mut ccs811 = Ccs811Awake::new(i2c.acquire_i2c(), SlaveAddr::default());
+        ccs811.software_reset().unwrap();
+        delay.delay_ms(10);
+        let mut ccs811 = ccs811.start_application().ok().unwrap();
+        ccs811.set_mode(MeasurementMode::ConstantPower1s).unwrap();
+        ccs811
+            .set_interrupt_mode(InterruptMode::OnDataReady)
+            .unwrap();
+        let mut ccs811_int = gpiob.pb0.into_pull_up_input(&mut gpiob.crl);
+        ccs811_int.make_interrupt_source(&mut afio);
+        ccs811_int.trigger_on_edge(&exti, Edge::Falling);
+        ccs811_int.enable_interrupt(&exti);
+
+        let mut apds9960 = Apds9960::new(Compat::new(i2c.acquire_i2c()));
+        apds9960.enable().unwrap();
+        // The persistence filter is off by default, so every light
+        // measurement interrupts. They are started by the timer.
+        apds9960.enable_light_interrupts().unwrap();
+        let mut apds9960_int = gpiob.pb1.into_pull_up_input(&mut gpiob.crl);
+        apds9960_int.make_interrupt_source(&mut afio);
+        apds9960_int.trigger_on_edge(&exti, Edge::Falling);
+        apds9960_int.enable_interrupt(&exti);
+
+        let mut timer = Timer::tim2(tim2, &clocks).start_count_down(1.hz());
+        timer.listen(Event::Update);
+
+        (
+            Shared {
+                delay,
+                readings: Readings {
+                    gas: None,
+                    light: None,
+                },
+                apds9960,
+            },
+            Local {
+                timer,
+                panel,
+                rtc,
+                rtc_monitor: Monitor::new("DS3231"),
+                ccs811,
+                ccs811_int,
+                ccs811_monitor: Monitor::new("CCS811"),
+                apds9960_int,
+                apds9960_monitor: Monitor::new("APDS9960"),
+            },
+        )
+    }
+
+    #[idle]
+    fn idle(_: idle::Context) -> ! {
+        loop {
+            cortex_m::asm::wfi();
+        }
+    }
+
+    /// Sample the light and the time and show everything once per second.
+    #[task(binds = TIM2, priority = 1, local = [timer, panel, rtc, rtc_monitor], shared = [delay, readings, apds9960])]
+    fn tick(mut cx: tick::Context) {
+        cx.local.timer.clear_update_interrupt_flag();
+
+        // The light is read once the APDS9960 interrupts.
+        let started = cx.shared.apds9960.lock(|apds9960| apds9960.enable_light());
+        if let Err(error) = started {
+            let failure = Failure::new("APDS9960", &error, 1);
+            warn!("{}", failure);
+            cx.shared
+                .readings
+                .lock(|readings| readings.light = Some(Err(failure)));
+        }
+
+        let rtc = cx.local.rtc;
+        let monitor = cx.local.rtc_monitor;
+        let now = monitor.read(&mut LockedDelay(cx.shared.delay), || rtc.datetime());
+
+        let mut status = Status::of(&now);
+        let panel = cx.local.panel;
+        panel.page("Air quality");
+        match now {
+            Ok(now) => write!(panel.row(0), "{} {}", now.date(), now.time()).unwrap(),
+            Err(failure) => write!(panel.row(0), "{} ERROR", failure.device).unwrap(),
+        }
+        cx.shared.readings.lock(|readings| {
+            if let Some(gas) = &readings.gas {
+                status = status.max(Status::of(gas));
+            }
+            if let Some(light) = &readings.light {
+                status = status.max(Status::of(light));
+            }
+            match &readings.gas {
+                Some(Ok(data)) => {
+                    write!(panel.row(1), "eCO2 {} TVOC {}", data.eco2, data.etvoc).unwrap()
+                }
+                Some(Err(failure)) => write!(panel.row(1), "{} ERROR", failure.device).unwrap(),
+                None => panel.set_row(1, "eCO2 ..."),
+            }
+            match &readings.light {
+                Some(Ok(light)) => {
+                    write!(panel.row(2), "Light {}", light.clear).unwrap();
+                    write!(
+                        panel.row(3),
+                        "R {} G {} B {}",
+                        light.red,
+                        light.green,
+                        light.blue
+                    )
+                    .unwrap();
+                }
+                Some(Err(failure)) => write!(panel.row(2), "{} ERROR", failure.device).unwrap(),
+                None => panel.set_row(2, "Light ..."),
+            }
+        });
+        if let Err(error) = panel.flush() {
+            error!("Display error: {}", logging::Dbg(&error));
+        }
+        heartbeat::feed(status);
+    }
+
+    /// The CCS811 has a new measurement.
+    #[task(binds = EXTI0, priority = 2, local = [ccs811, ccs811_int, ccs811_monitor], shared = [delay, readings])]
+    fn gas_ready(mut cx: gas_ready::Context) {
+        cx.local.ccs811_int.clear_interrupt_pending_bit();
+
+        let ccs811 = cx.local.ccs811;
+        let monitor = cx.local.ccs811_monitor;
+        // The data is ready, so this does not wait.
+        let reading = monitor.read(&mut LockedDelay(cx.shared.delay), || {
+            nb::block!(ccs811.data())
+        });
+        cx.shared
+            .readings
+            .lock(|readings| readings.gas = Some(reading));
+    }
+
+    /// The APDS9960 has measured the light.
+    #[task(binds = EXTI1, priority = 2, local = [apds9960_int, apds9960_monitor], shared = [delay, readings, apds9960])]
+    fn light_ready(mut cx: light_ready::Context) {
+        cx.local.apds9960_int.clear_interrupt_pending_bit();
+
+        let monitor = cx.local.apds9960_monitor;
+        let mut delay = LockedDelay(cx.shared.delay);
+        let reading = cx.shared.apds9960.lock(|apds9960| {
+            monitor.read(&mut delay, || {
+                nb::block!(apds9960.read_light()).and_then(|light| {
+                    // Stop until the next tick.
+                    apds9960.disable_light()?;
+                    apds9960.clear_light_interrupt()?;
+                    Ok(light)
+                })
+            })
+        });
+        cx.shared
+            .readings
+            .lock(|readings| readings.light = Some(reading));
+    }
+}
@@ -26,24 +26,22 @@
 #![no_main]
 
 use cortex_m_rt::entry;
+use driver_examples_bluepill::{
+    heartbeat::{self, HeartbeatTimer, Status},
+    info, logging, Compat, I2cBus, RecoverableI2c, I2C_FAST_MODE,
+};
+use embedded_hal::delay::DelayNs;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
 use si4703::{
     reset_and_select_i2c_method1 as reset_si4703, ChannelSpacing, DeEmphasis, SeekDirection,
     SeekMode, Si4703, Volume,
 };
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
+use stm32f1xx_hal::{delay::Delay, i2c::BlockingI2c, pac, prelude::*};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("Si4703 example");
+    logging::init();
+    info!("Si4703 example");
     let cp = cortex_m::Peripherals::take().unwrap();
     let dp = pac::Peripherals::take().unwrap();
 
@@ -61,44 +59,38 @@ fn main() -> ! {
     let seekdown = gpiob.pb11.into_pull_down_input(&mut gpiob.crh);
     let seekup = gpiob.pb10.into_pull_down_input(&mut gpiob.crh);
     let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    let led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    HeartbeatTimer::new(dp.TIM3, &clocks).start(led);
+    let mut delay = Compat::new(Delay::new(cp.SYST, clocks));
 
-    reset_si4703(&mut rst, &mut sda, &mut delay).unwrap();
+    reset_si4703(&mut rst, &mut sda, delay.inner_mut()).unwrap();
     let sda = sda.into_alternate_open_drain(&mut gpiob.crh);
     let i2c = BlockingI2c::i2c1(
         dp.I2C1,
         (scl, sda),
         &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
+        I2C_FAST_MODE,
         clocks,
         1000,
         10,
         1000,
         1000,
     );
-    let manager = shared_bus::BusManagerSimple::new(i2c);
+    let i2c = I2cBus::new(RecoverableI2c::new(i2c, clocks));
 
-    let mut radio = Si4703::new(manager.acquire_i2c());
+    let mut radio = Si4703::new(Compat::new(i2c.acquire_i2c()));
     radio.enable_oscillator().unwrap();
-    delay.delay_ms(500_u16);
+    delay.delay_ms(500);
     radio.enable().unwrap();
-    delay.delay_ms(110_u16);
+    delay.delay_ms(110);
 
     radio.set_volume(Volume::Dbfsm28).unwrap();
     radio.set_deemphasis(DeEmphasis::Us50).unwrap();
     radio.set_channel_spacing(ChannelSpacing::Khz100).unwrap();
     radio.unmute().unwrap();
     loop {
-        // Blink LED 0 every time a new seek is started
-        // to check that everything is actually running.
-        led.set_low();
-        delay.delay_ms(50_u16);
-        led.set_high();
-        delay.delay_ms(50_u16);
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(100);
         let should_seek_down = seekdown.is_high();
         let should_seek_up = seekup.is_high();
         if should_seek_down || should_seek_up {
@@ -28,30 +28,22 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, HeartbeatTimer, Status},
+    info, logging, new_display, Compat, I2cBus, Monitor, RecoverableI2c, TextPanel, I2C_FAST_MODE,
 };
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
+use embedded_hal::delay::DelayNs;
 use si4703::{
     reset_and_select_i2c_method1 as reset_si4703, ChannelSpacing, DeEmphasis, ErrorWithPin,
     SeekDirection, SeekMode, Si4703, Volume,
 };
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
+use ssd1306::I2CDisplayInterface;
+use stm32f1xx_hal::{delay::Delay, i2c::BlockingI2c, pac, prelude::*};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("Si4703 example");
+    logging::init();
+    info!("Si4703 example");
     let cp = cortex_m::Peripherals::take().unwrap();
     let dp = pac::Peripherals::take().unwrap();
 
@@ -69,99 +61,77 @@ fn main() -> ! {
     let seekdown = gpiob.pb11.into_pull_down_input(&mut gpiob.crh);
     let seekup = gpiob.pb10.into_pull_down_input(&mut gpiob.crh);
     let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
+    let led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    HeartbeatTimer::new(dp.TIM3, &clocks).start(led);
+    let mut delay = Compat::new(Delay::new(cp.SYST, clocks));
 
-    reset_si4703(&mut rst, &mut sda, &mut delay).unwrap();
+    reset_si4703(&mut rst, &mut sda, delay.inner_mut()).unwrap();
     let sda = sda.into_alternate_open_drain(&mut gpiob.crh);
     let i2c = BlockingI2c::i2c1(
         dp.I2C1,
         (scl, sda),
         &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
+        I2C_FAST_MODE,
         clocks,
         1000,
         10,
         1000,
         1000,
     );
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
+    let i2c = I2cBus::new(RecoverableI2c::new(i2c, clocks));
+    let interface = I2CDisplayInterface::new(i2c.acquire_i2c());
+    let mut panel = TextPanel::new(new_display(interface));
+    panel.init().unwrap();
 
-    let mut radio = Si4703::new(manager.acquire_i2c());
+    let mut radio = Si4703::new(Compat::new(i2c.acquire_i2c()));
     radio.enable_oscillator().unwrap();
-    delay.delay_ms(500_u16);
+    delay.delay_ms(500);
     radio.enable().unwrap();
-    delay.delay_ms(110_u16);
+    delay.delay_ms(110);
 
     radio.set_volume(Volume::Dbfsm28).unwrap();
     radio.set_deemphasis(DeEmphasis::Us50).unwrap();
     radio.set_channel_spacing(ChannelSpacing::Khz100).unwrap();
     radio.unmute().unwrap();
 
-    let mut buffer: heapless::String<64> = heapless::String::new();
+    let mut monitor = Monitor::new("Si4703");
     loop {
-        // Blink LED 0 every time a new seek is started
-        // to check that everything is actually running.
-        led.set_low();
-        delay.delay_ms(50_u16);
-        led.set_high();
-        delay.delay_ms(50_u16);
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(100);
         let should_seek_down = seekdown.is_high();
         let should_seek_up = seekup.is_high();
         if should_seek_down || should_seek_up {
-            buffer.clear();
-            write!(buffer, "Seeking...").unwrap();
-
-            disp.clear();
-            Text::new(&buffer, Point::zero())
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
-
-            disp.flush().unwrap();
+            panel.set_row(0, "Seeking...");
+            panel.flush().unwrap();
             let direction = if should_seek_down {
                 SeekDirection::Down
             } else {
                 SeekDirection::Up
             };
 
-            buffer.clear();
             loop {
                 match radio.seek_with_stc_int_pin(SeekMode::Wrap, direction, &stcint) {
                     Err(nb::Error::WouldBlock) => {}
                     Err(nb::Error::Other(ErrorWithPin::SeekFailed)) => {
-                        write!(buffer, "Seek Failed!  ").unwrap();
+                        write!(panel.row(0), "Seek Failed!  ").unwrap();
                         break;
                     }
                     Err(_) => {
-                        write!(buffer, "Error!     ").unwrap();
+                        write!(panel.row(0), "Error!     ").unwrap();
                         break;
                     }
                     Ok(_) => {
-                        let channel = radio.channel().unwrap_or(-1.0);
-                        write!(buffer, "Found {:1} MHz ", channel).unwrap();
+                        let reading = monitor.read(&mut delay, || radio.channel());
+                        panel
+                            .show_reading(&reading, |panel, channel| {
+                                write!(panel.row(0), "Found {:1} MHz ", channel).unwrap();
+                            })
+                            .unwrap();
                         break;
                     }
                 }
             }
-            disp.clear();
-            Text::new(&buffer, Point::zero())
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
-
-            disp.flush().unwrap();
+            panel.flush().unwrap();
         }
     }
 }
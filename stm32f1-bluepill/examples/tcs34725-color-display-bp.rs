@@ -20,113 +20,51 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    info, logging, warn, Board, Monitor, TextPanel, I2C_FAST_MODE,
 };
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
-use tcs3472::{AllChannelMeasurement, Tcs3472};
+use embedded_hal::delay::DelayNs;
+use tcs3472::Tcs3472;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("TCS34725 example");
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
+    logging::init();
+    info!("TCS34725 example");
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take_with_i2c_mode(I2C_FAST_MODE).unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
 
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut sensor = Tcs3472::new(manager.acquire_i2c());
+    let mut sensor = Tcs3472::new(i2c.acquire_i2c());
     sensor.enable().unwrap();
     sensor.enable_rgbc().unwrap();
     while !sensor.is_rgbc_status_valid().unwrap() {
         // wait for measurement to be available
-        delay.delay_ms(50_u8);
+        delay.delay_ms(50);
     }
-    let mut lines: [heapless::String<32>; 4] = [
-        heapless::String::new(),
-        heapless::String::new(),
-        heapless::String::new(),
-        heapless::String::new(),
-    ];
-    loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-
-        // If there was an error, it will print 0, 0, 0, 0
-        let measurement = sensor.read_all_channels().unwrap_or(AllChannelMeasurement {
-            red: 0,
-            green: 0,
-            blue: 0,
-            clear: 0,
-        });
 
-        lines[0].clear();
-        lines[1].clear();
-        lines[2].clear();
-        lines[3].clear();
-
-        write!(lines[0], "Red: {}", measurement.red).unwrap();
-        write!(lines[1], "Green: {}", measurement.green).unwrap();
-        write!(lines[2], "Blue: {}", measurement.blue).unwrap();
-        write!(lines[3], "Clear: {}", measurement.clear).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
-                .unwrap();
+    let mut monitor = Monitor::new("TCS34725");
+    loop {
+        let reading = monitor.read(&mut delay, || sensor.read_all_channels());
+        panel
+            .show_reading(&reading, |panel, measurement| {
+                write!(panel.row(0), "Red: {}", measurement.red).unwrap();
+                write!(panel.row(1), "Green: {}", measurement.green).unwrap();
+                write!(panel.row(2), "Blue: {}", measurement.blue).unwrap();
+                write!(panel.row(3), "Clear: {}", measurement.clear).unwrap();
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -23,104 +23,55 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    fonts::{Font6x8, Text},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    style::TextStyleBuilder,
+use driver_examples_bluepill::{
+    heartbeat::{self, Status},
+    logging, warn, Board, Monitor, TextPanel, I2C_FAST_MODE,
 };
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::rtt_init_print;
-use ssd1306::{prelude::*, Builder, I2CDIBuilder};
-use stm32f1xx_hal::{
-    delay::Delay,
-    i2c::{BlockingI2c, DutyCycle, Mode},
-    pac,
-    prelude::*,
-};
 use tmp006::{SlaveAddr, Tmp006};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let rcc = dp.RCC.constrain();
-
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut afio = dp.AFIO.constrain();
-    let mut gpiob = dp.GPIOB.split();
-
-    let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
-    let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
-
-    let i2c = BlockingI2c::i2c1(
-        dp.I2C1,
-        (scl, sda),
-        &mut afio.mapr,
-        Mode::Fast {
-            frequency: 400_000.hz(),
-            duty_cycle: DutyCycle::Ratio2to1,
-        },
-        clocks,
-        1000,
-        10,
-        1000,
-        1000,
-    );
-
-    let mut gpioc = dp.GPIOC.split();
-    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDIBuilder::new().init(manager.acquire_i2c());
-    let mut disp: GraphicsMode<_> = Builder::new().connect(interface).into();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = TextStyleBuilder::new(Font6x8)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut tmp006 = Tmp006::new(manager.acquire_i2c(), SlaveAddr::default());
-
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
+    logging::init();
+    let Board {
+        mut delay,
+        led,
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Board::take_with_i2c_mode(I2C_FAST_MODE).unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut tmp006 = Tmp006::new(i2c.acquire_i2c(), SlaveAddr::default());
+
+    let calibration_factor = 6e-14;
+    let mut monitor = Monitor::new("TMP006");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high();
-        delay.delay_ms(50_u16);
-        led.set_low();
-        delay.delay_ms(50_u16);
-        lines[0].clear();
-        lines[1].clear();
-
-        let calibration_factor = 6e-14;
-        let temp_k = block!(tmp006.read_object_temperature(calibration_factor)).unwrap();
-        let temp_c = temp_k - 273.15;
-        write!(lines[0], "Temperature: {:.2}ºC", temp_c).unwrap();
-
-        // Read data in raw format
-        let raw_data = block!(tmp006.read_sensor_data()).unwrap();
-        write!(
-            lines[1],
-            "OV: {}, AT: {}",
-            raw_data.object_voltage, raw_data.ambient_temperature
-        )
-        .unwrap();
-
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::new(line, Point::new(0, i as i32 * 16))
-                .into_styled(text_style)
-                .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || {
+            let temp_k = block!(tmp006.read_object_temperature(calibration_factor))?;
+            // Read data in raw format
+            let raw_data = block!(tmp006.read_sensor_data())?;
+            Ok::<_, tmp006::Error<_>>((temp_k, raw_data))
+        });
+        panel
+            .show_reading(&reading, |panel, (temp_k, raw_data)| {
+                let temp_c = temp_k - 273.15;
+                write!(panel.row(0), "Temperature: {:.2}ºC", temp_c).unwrap();
+                write!(
+                    panel.row(1),
+                    "OV: {}, AT: {}",
+                    raw_data.object_voltage,
+                    raw_data.ambient_temperature
+                )
                 .unwrap();
+            })
+            .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
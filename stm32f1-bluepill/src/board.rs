@@ -0,0 +1,505 @@
+//! Peripheral setup shared by all examples.
+
+use crate::heartbeat::HeartbeatTimer;
+use crate::recovery::RecoverableI2c;
+#[cfg(feature = "usb-serial")]
+use crate::usb_serial::UsbSerial;
+use crate::watchdog::{ResetCause, Watchdog};
+use cortex_m::peripheral::DWT;
+use driver_examples_common::{clock::Clock, compat::spi_mode, new_display, Compat};
+use embedded_hal::spi::Mode as SpiMode;
+use ssd1306::{prelude::*, I2CDisplayInterface};
+use stm32f1xx_hal::{
+    afio,
+    delay::Delay,
+    gpio::{
+        gpioa::{PA0, PA1, PA10, PA2, PA3, PA4, PA5, PA6, PA7, PA8, PA9},
+        gpiob::{PB0, PB1, PB10, PB11, PB12, PB13, PB14, PB15, PB5, PB6, PB7, PB8, PB9},
+        gpioc::PC13,
+        Alternate, Cr, Floating, Input, OpenDrain, Output, PushPull, CRH, CRL,
+    },
+    i2c::{BlockingI2c, DutyCycle, Mode},
+    pac::{self, EXTI, I2C1, IWDG, SPI1, SPI2, TIM2, USART1},
+    prelude::*,
+    rcc::Clocks,
+    serial::{self, Serial},
+    spi::{Spi, Spi1NoRemap, Spi2NoRemap},
+    time::{Bps, Hertz},
+    watchdog::IndependentWatchdog,
+};
+#[cfg(feature = "usb-serial")]
+use stm32f1xx_hal::{
+    gpio::gpioa::{PA11, PA12},
+    pac::USB,
+    usb::Peripheral,
+};
+
+/// I2C1 peripheral on PB8 (SCL) and PB9 (SDA).
+pub type I2c1 = BlockingI2c<I2C1, (PB8<Alternate<OpenDrain>>, PB9<Alternate<OpenDrain>>)>;
+
+/// Bus sharing I2C1 between the display and the devices.
+///
+/// It recovers from a stuck bus, see [`RecoverableI2c`].
+pub type I2cBus = driver_examples_common::I2cBus<RecoverableI2c>;
+
+/// Handle to the shared I2C1 bus which can be passed to any driver.
+pub type I2cProxy = driver_examples_common::I2cDevice<'static, RecoverableI2c>;
+
+/// SSD1306 OLED display connected to the shared I2C1 bus.
+pub type Display = driver_examples_common::Display<I2CInterface<I2cProxy>>;
+
+/// SPI1 peripheral on PA5 (SCK), PA6 (MISO) and PA7 (MOSI).
+pub type Spi1 = Spi<
+    SPI1,
+    Spi1NoRemap,
+    (
+        PA5<Alternate<PushPull>>,
+        PA6<Input<Floating>>,
+        PA7<Alternate<PushPull>>,
+    ),
+    u8,
+>;
+
+/// SPI1 shared between the devices on it, see [`Board::spi1`].
+pub type Spi1Bus = driver_examples_common::SpiBus<Compat<Spi1>>;
+
+/// Device on SPI1 with its own chip select pin, which can be passed to
+/// any driver.
+pub type Spi1Device<CS> = driver_examples_common::SpiDevice<'static, Compat<Spi1>, Compat<CS>>;
+
+/// SPI2 peripheral on PB13 (SCK), PB14 (MISO) and PB15 (MOSI).
+pub type Spi2 = Spi<
+    SPI2,
+    Spi2NoRemap,
+    (
+        PB13<Alternate<PushPull>>,
+        PB14<Input<Floating>>,
+        PB15<Alternate<PushPull>>,
+    ),
+    u8,
+>;
+
+/// SPI2 shared between the devices on it, see [`Board::spi2`].
+pub type Spi2Bus = driver_examples_common::SpiBus<Compat<Spi2>>;
+
+/// Device on SPI2 with its own chip select pin, which can be passed to
+/// any driver.
+pub type Spi2Device<CS> = driver_examples_common::SpiDevice<'static, Compat<Spi2>, Compat<CS>>;
+
+/// USART1 peripheral on PA9 (TX) and PA10 (RX).
+pub type Serial1 = Serial<USART1, (PA9<Alternate<PushPull>>, PA10<Input<Floating>>)>;
+
+/// USART1 peripheral remapped to PB6 (TX) and PB7 (RX).
+pub type Serial1Remapped = Serial<USART1, (PB6<Alternate<PushPull>>, PB7<Input<Floating>>)>;
+
+/// Output of the streaming examples: the USB serial port with the
+/// `usb-serial` feature, USART1 otherwise.
+#[cfg(feature = "usb-serial")]
+pub type SerialOutput = UsbSerial;
+
+/// Output of the streaming examples: the USB serial port with the
+/// `usb-serial` feature, USART1 otherwise.
+#[cfg(not(feature = "usb-serial"))]
+pub type SerialOutput = serial::Tx<USART1>;
+
+/// On-board LED on PC13. Note that it is lit when the pin is low.
+pub type Led = PC13<Output<PushPull>>;
+
+/// Standard mode I2C at 100 kHz. Most devices are fine with this.
+pub const I2C_STANDARD_MODE: Mode = Mode::Standard {
+    frequency: Hertz(100_000),
+};
+
+/// Fast mode I2C at 400 kHz.
+pub const I2C_FAST_MODE: Mode = Mode::Fast {
+    frequency: Hertz(400_000),
+    duty_cycle: DutyCycle::Ratio2to1,
+};
+
+/// GPIOA pins not used by the board setup.
+///
+/// PA5-PA7 are reserved for SPI1, PA9-PA10 for USART1 and PA11-PA12 for
+/// USB.
+pub struct GpioA {
+    pub crl: Cr<CRL, 'A'>,
+    pub crh: Cr<CRH, 'A'>,
+    pub pa0: PA0<Input<Floating>>,
+    pub pa1: PA1<Input<Floating>>,
+    pub pa2: PA2<Input<Floating>>,
+    pub pa3: PA3<Input<Floating>>,
+    pub pa4: PA4<Input<Floating>>,
+    pub pa8: PA8<Input<Floating>>,
+}
+
+/// GPIOB pins not used by the board setup.
+///
+/// PB6-PB7 are reserved for the remapped USART1, PB8-PB9 are used by I2C1
+/// and PB13-PB15 are reserved for SPI2.
+pub struct GpioB {
+    pub crl: Cr<CRL, 'B'>,
+    pub crh: Cr<CRH, 'B'>,
+    pub pb0: PB0<Input<Floating>>,
+    pub pb1: PB1<Input<Floating>>,
+    pub pb5: PB5<Input<Floating>>,
+    pub pb10: PB10<Input<Floating>>,
+    pub pb11: PB11<Input<Floating>>,
+    pub pb12: PB12<Input<Floating>>,
+}
+
+/// SPI1 and its pins until they are set up by [`Board::spi1`].
+type Spi1Parts = (
+    SPI1,
+    PA5<Input<Floating>>,
+    PA6<Input<Floating>>,
+    PA7<Input<Floating>>,
+);
+
+/// SPI2 and its pins until they are set up by [`Board::spi2`].
+type Spi2Parts = (
+    SPI2,
+    PB13<Input<Floating>>,
+    PB14<Input<Floating>>,
+    PB15<Input<Floating>>,
+);
+
+/// USART1 and its pins until they are set up by [`Board::usart1`] or
+/// [`Board::usart1_remapped`].
+type Usart1Parts = (
+    USART1,
+    PA9<Input<Floating>>,
+    PA10<Input<Floating>>,
+    PB6<Input<Floating>>,
+    PB7<Input<Floating>>,
+);
+
+/// USB peripheral and its pins until they are set up by
+/// [`Board::usb_serial`].
+#[cfg(feature = "usb-serial")]
+type UsbParts = (USB, PA11<Input<Floating>>, PA12<Input<Floating>>);
+
+/// STM32F103 "Blue pill" board as used in the examples.
+///
+/// ```
+/// BP   <-> Display
+/// GND  <-> GND
+/// 3.3V <-> VDD
+/// PB8  <-> SCL
+/// PB9  <-> SDA
+/// ```
+pub struct Board {
+    /// Clocks running from the 8 MHz external crystal at 72 MHz.
+    pub clocks: Clocks,
+    /// Why the chip was reset, e.g. to tell that the watchdog recovered
+    /// the board.
+    pub reset_cause: ResetCause,
+    /// SysTick-based delay.
+    pub delay: Compat<Delay>,
+    /// Milliseconds since the board was set up, from the cycle counter.
+    ///
+    /// The cycle counter wraps around every minute, so read it at least
+    /// that often.
+    pub clock: Clock,
+    /// Heartbeat LED.
+    pub led: Led,
+    /// TIM3, which blinks the heartbeat LED once started, see
+    /// [`heartbeat`](crate::heartbeat).
+    pub heartbeat: HeartbeatTimer,
+    /// Shared I2C1 bus. Use `i2c.acquire_i2c()` to get a handle for a driver.
+    ///
+    /// Register the configuration of the devices with
+    /// `i2c.lock(|i2c| i2c.on_recovery(...))` so that they are configured
+    /// again after a recovery from a stuck bus.
+    pub i2c: &'static I2cBus,
+    /// SSD1306 display on the I2C1 bus.
+    ///
+    /// It is not initialized since not all setups include a display.
+    /// Call `display.init()` before using it.
+    pub display: Display,
+    /// Alternate function I/O. Needed for remapping and external interrupts.
+    pub afio: afio::Parts,
+    /// External interrupt controller, to get interrupts from the interrupt
+    /// lines of the devices.
+    pub exti: EXTI,
+    /// Free general purpose timer, e.g. to schedule sampling.
+    pub tim2: TIM2,
+    /// Free GPIOA pins.
+    pub gpioa: GpioA,
+    /// Free GPIOB pins.
+    pub gpiob: GpioB,
+    spi1: Option<Spi1Parts>,
+    spi2: Option<Spi2Parts>,
+    usart1: Option<Usart1Parts>,
+    #[cfg(feature = "usb-serial")]
+    usb: Option<UsbParts>,
+    iwdg: Option<IWDG>,
+}
+
+impl Board {
+    /// Take the device peripherals and set up the board with I2C1 in
+    /// standard mode (100 kHz).
+    ///
+    /// Returns `None` if the peripherals have already been taken.
+    pub fn take() -> Option<Self> {
+        Self::take_with_i2c_mode(I2C_STANDARD_MODE)
+    }
+
+    /// Take the device peripherals and set up the board with I2C1 in the
+    /// given mode.
+    ///
+    /// Returns `None` if the peripherals have already been taken.
+    pub fn take_with_i2c_mode(mode: Mode) -> Option<Self> {
+        let cp = cortex_m::Peripherals::take()?;
+        let dp = pac::Peripherals::take()?;
+        Self::new(cp, dp, mode)
+    }
+
+    /// Set up the board on peripherals which have already been taken, e.g.
+    /// the ones an RTIC application gets in `init`, with I2C1 in the given
+    /// mode.
+    ///
+    /// Returns `None` if a board has already been set up.
+    pub fn new(mut cp: cortex_m::Peripherals, dp: pac::Peripherals, mode: Mode) -> Option<Self> {
+        let reset_cause = ResetCause::from_csr(dp.RCC.csr.read().bits());
+        dp.RCC.csr.modify(|_, w| w.rmvf().set_bit());
+
+        let mut flash = dp.FLASH.constrain();
+        let rcc = dp.RCC.constrain();
+        let clocks = rcc
+            .cfgr
+            .use_hse(8.mhz())
+            .sysclk(72.mhz())
+            .pclk1(36.mhz())
+            .freeze(&mut flash.acr);
+        #[cfg(feature = "panic-display")]
+        crate::panic_display::register(clocks);
+        let mut afio = dp.AFIO.constrain();
+        let gpioa = dp.GPIOA.split();
+        let mut gpiob = dp.GPIOB.split();
+        let mut gpioc = dp.GPIOC.split();
+
+        let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
+        let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
+        let i2c = BlockingI2c::i2c1(
+            dp.I2C1,
+            (scl, sda),
+            &mut afio.mapr,
+            mode,
+            clocks,
+            1000,
+            10,
+            1000,
+            1000,
+        );
+        let i2c = RecoverableI2c::new(i2c, clocks);
+        let i2c: &'static I2cBus = cortex_m::singleton!(: I2cBus = I2cBus::new(i2c))?;
+
+        let display = new_display(I2CDisplayInterface::new(i2c.acquire_i2c()));
+
+        let led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+        let delay = Compat::new(Delay::new(cp.SYST, clocks));
+        cp.DCB.enable_trace();
+        cp.DWT.enable_cycle_counter();
+        let clock = Clock::new(clocks.sysclk().0, DWT::cycle_count);
+        let heartbeat = HeartbeatTimer::new(dp.TIM3, &clocks);
+
+        Some(Board {
+            clocks,
+            reset_cause,
+            delay,
+            clock,
+            led,
+            heartbeat,
+            i2c,
+            display,
+            afio,
+            exti: dp.EXTI,
+            tim2: dp.TIM2,
+            gpioa: GpioA {
+                crl: gpioa.crl,
+                crh: gpioa.crh,
+                pa0: gpioa.pa0,
+                pa1: gpioa.pa1,
+                pa2: gpioa.pa2,
+                pa3: gpioa.pa3,
+                pa4: gpioa.pa4,
+                pa8: gpioa.pa8,
+            },
+            gpiob: GpioB {
+                crl: gpiob.crl,
+                crh: gpiob.crh,
+                pb0: gpiob.pb0,
+                pb1: gpiob.pb1,
+                pb5: gpiob.pb5,
+                pb10: gpiob.pb10,
+                pb11: gpiob.pb11,
+                pb12: gpiob.pb12,
+            },
+            spi1: Some((dp.SPI1, gpioa.pa5, gpioa.pa6, gpioa.pa7)),
+            spi2: Some((dp.SPI2, gpiob.pb13, gpiob.pb14, gpiob.pb15)),
+            usart1: Some((dp.USART1, gpioa.pa9, gpioa.pa10, gpiob.pb6, gpiob.pb7)),
+            #[cfg(feature = "usb-serial")]
+            usb: Some((dp.USB, gpioa.pa11, gpioa.pa12)),
+            iwdg: Some(dp.IWDG),
+        })
+    }
+
+    /// Set up SPI1 with the SPI mode and frequency required by the
+    /// devices on it.
+    ///
+    /// Every device gets its handle from the returned bus with its chip
+    /// select pin (e.g. PA4), configured as an output:
+    ///
+    /// ```ignore
+    /// let spi1 = board.spi1(MODE, 1.mhz()).unwrap();
+    /// let cs = board.gpioa.pa4.into_push_pull_output(&mut board.gpioa.crl);
+    /// let spi = spi1.acquire_spi(Compat::new(cs)).unwrap();
+    /// ```
+    ///
+    /// Set up the bus before taking the pins out of the board, since the
+    /// board cannot be borrowed anymore afterwards.
+    ///
+    /// Returns `None` if SPI1 has already been set up.
+    pub fn spi1<F: Into<Hertz>>(
+        &mut self,
+        mode: SpiMode,
+        frequency: F,
+    ) -> Option<&'static Spi1Bus> {
+        let (spi, sck, miso, mosi) = self.spi1.take()?;
+        let sck = sck.into_alternate_push_pull(&mut self.gpioa.crl);
+        let mosi = mosi.into_alternate_push_pull(&mut self.gpioa.crl);
+        let spi = Spi::spi1(
+            spi,
+            (sck, miso, mosi),
+            &mut self.afio.mapr,
+            spi_mode(mode),
+            frequency,
+            self.clocks,
+        );
+        let bus: &'static Spi1Bus =
+            cortex_m::singleton!(: Spi1Bus = Spi1Bus::new(Compat::new(spi)))?;
+        Some(bus)
+    }
+
+    /// Set up SPI2 like [`spi1`](Board::spi1), for devices which need
+    /// another SPI mode than the ones on SPI1.
+    ///
+    /// Returns `None` if SPI2 has already been set up.
+    pub fn spi2<F: Into<Hertz>>(
+        &mut self,
+        mode: SpiMode,
+        frequency: F,
+    ) -> Option<&'static Spi2Bus> {
+        let (spi, sck, miso, mosi) = self.spi2.take()?;
+        let sck = sck.into_alternate_push_pull(&mut self.gpiob.crh);
+        let mosi = mosi.into_alternate_push_pull(&mut self.gpiob.crh);
+        let spi = Spi::spi2(
+            spi,
+            (sck, miso, mosi),
+            spi_mode(mode),
+            frequency,
+            self.clocks,
+        );
+        let bus: &'static Spi2Bus =
+            cortex_m::singleton!(: Spi2Bus = Spi2Bus::new(Compat::new(spi)))?;
+        Some(bus)
+    }
+
+    /// Set up USART1 with the given baud rate.
+    ///
+    /// Returns `None` if USART1 has already been set up.
+    pub fn usart1(&mut self, baudrate: Bps) -> Option<Serial1> {
+        let (usart, tx, rx, _, _) = self.usart1.take()?;
+        let tx = tx.into_alternate_push_pull(&mut self.gpioa.crh);
+        Some(Serial::usart1(
+            usart,
+            (tx, rx),
+            &mut self.afio.mapr,
+            serial::Config::default().baudrate(baudrate),
+            self.clocks,
+        ))
+    }
+
+    /// Set up USART1 remapped to PB6 (TX) and PB7 (RX) with the given baud
+    /// rate, e.g. for setups where PA9 and PA10 are used by something else.
+    ///
+    /// Returns `None` if USART1 has already been set up.
+    pub fn usart1_remapped(&mut self, baudrate: Bps) -> Option<Serial1Remapped> {
+        let (usart, _, _, tx, rx) = self.usart1.take()?;
+        let tx = tx.into_alternate_push_pull(&mut self.gpiob.crl);
+        Some(Serial::usart1(
+            usart,
+            (tx, rx),
+            &mut self.afio.mapr,
+            serial::Config::default().baudrate(baudrate),
+            self.clocks,
+        ))
+    }
+
+    /// Set up the USB port as a CDC-ACM serial device, see
+    /// [`usb_serial`](crate::usb_serial).
+    ///
+    /// Returns `None` if the USB port has already been set up.
+    #[cfg(feature = "usb-serial")]
+    pub fn usb_serial(&mut self) -> Option<UsbSerial> {
+        let (usb, dm, dp) = self.usb.take()?;
+        // Pull D+ low for a moment so that the host enumerates the device
+        // again after a reset.
+        let mut dp = dp.into_push_pull_output(&mut self.gpioa.crh);
+        dp.set_low();
+        cortex_m::asm::delay(self.clocks.sysclk().0 / 100);
+        UsbSerial::new(Peripheral {
+            usb,
+            pin_dm: dm,
+            pin_dp: dp.into_floating_input(&mut self.gpioa.crh),
+        })
+    }
+
+    /// Set up the output of the streaming examples, chosen at build time:
+    /// the USB serial port with the `usb-serial` feature or USART1 with the
+    /// given baud rate otherwise.
+    ///
+    /// Returns `None` if the peripheral has already been set up.
+    #[cfg(feature = "usb-serial")]
+    pub fn serial_output(&mut self, _baudrate: Bps) -> Option<SerialOutput> {
+        self.usb_serial()
+    }
+
+    /// Set up the output of the streaming examples, chosen at build time:
+    /// the USB serial port with the `usb-serial` feature or USART1 with the
+    /// given baud rate otherwise.
+    ///
+    /// Returns `None` if the peripheral has already been set up.
+    #[cfg(not(feature = "usb-serial"))]
+    pub fn serial_output(&mut self, baudrate: Bps) -> Option<SerialOutput> {
+        self.usart1(baudrate).map(|serial| serial.split().0)
+    }
+
+    /// Same as [`serial_output`](Board::serial_output), but with USART1
+    /// remapped to PB6 (TX) and PB7 (RX).
+    ///
+    /// Returns `None` if the peripheral has already been set up.
+    #[cfg(feature = "usb-serial")]
+    pub fn serial_output_remapped(&mut self, _baudrate: Bps) -> Option<SerialOutput> {
+        self.usb_serial()
+    }
+
+    /// Same as [`serial_output`](Board::serial_output), but with USART1
+    /// remapped to PB6 (TX) and PB7 (RX).
+    ///
+    /// Returns `None` if the peripheral has already been set up.
+    #[cfg(not(feature = "usb-serial"))]
+    pub fn serial_output_remapped(&mut self, baudrate: Bps) -> Option<SerialOutput> {
+        self.usart1_remapped(baudrate)
+            .map(|serial| serial.split().0)
+    }
+
+    /// Set up the independent watchdog. Register the tasks which must
+    /// check in before it is fed and then start it, see
+    /// [`watchdog`](crate::watchdog).
+    ///
+    /// Returns `None` if the watchdog has already been set up.
+    pub fn watchdog(&mut self) -> Option<Watchdog> {
+        let iwdg = self.iwdg.take()?;
+        Some(Watchdog::new(IndependentWatchdog::new(iwdg)))
+    }
+}
@@ -0,0 +1,75 @@
+//! Board support for the STM32F103 "Blue pill" examples.
+//!
+//! All examples share the same setup: clocks, I2C1 on PB8/PB9 shared between
+//! the devices and an SSD1306 display, SPI1, USART1, USB, the PC13 LED and a
+//! delay. Instead of repeating it in every program, take a [`Board`]:
+//!
+//! ```no_run
+//! use driver_examples_bluepill::{Board, TextPanel};
+//!
+//! let board = Board::take().unwrap();
+//! let i2c = board.i2c.acquire_i2c();
+//! let mut panel = TextPanel::new(board.display);
+//! panel.init().unwrap();
+//! ```
+//!
+//! The delay, the I2C handles and the SPI devices implement the embedded-hal
+//! 1.0 traits so that they can be passed to any driver.
+//!
+//! Text can be shown on the display through a [`TextPanel`].
+//!
+//! The LED blinks from a timer interrupt with the status of the program, see
+//! [`heartbeat`].
+//!
+//! I2C1 recovers from a bus stuck by a glitching device and configures the
+//! devices again, see [`recovery`].
+//!
+//! With the `usb-serial` feature the USB port is used as a serial port, see
+//! `usb_serial`. The streaming examples write to [`SerialOutput`], which is
+//! either the USB serial port or USART1 depending on that feature. The USB
+//! stack is left out otherwise so that the larger examples fit into the
+//! flash.
+//!
+//! The independent watchdog resets the board if one of the tasks of the
+//! program hangs, see [`watchdog`].
+//!
+//! The examples log over RTT, either as formatted text or with `defmt`, see
+//! [`logging`].
+//!
+//! The crate also provides the panic handler of the examples. By default it
+//! is the one of `panic-rtt-target`, or `panic-probe` with the `defmt`
+//! feature. With the `panic-display` feature the
+//! panic is shown on the display as well and the LED blinks SOS.
+#![no_std]
+
+mod board;
+pub mod heartbeat;
+#[path = "../../common/board/logging.rs"]
+pub mod logging;
+#[cfg(feature = "panic-display")]
+mod panic_display;
+pub mod recovery;
+#[cfg(feature = "usb-serial")]
+pub mod usb_serial;
+pub mod watchdog;
+pub use crate::board::{
+    Board, Display, GpioA, GpioB, I2c1, I2cBus, I2cProxy, Led, Serial1, Serial1Remapped,
+    SerialOutput, Spi1, Spi1Bus, Spi1Device, Spi2, Spi2Bus, Spi2Device, I2C_FAST_MODE,
+    I2C_STANDARD_MODE,
+};
+pub use crate::heartbeat::HeartbeatTimer;
+pub use crate::recovery::{RecoverableI2c, Reinit};
+#[cfg(feature = "usb-serial")]
+pub use crate::usb_serial::UsbSerial;
+pub use crate::watchdog::{ResetCause, Watchdog};
+pub use driver_examples_common::{
+    adc, bus, environment, generator, midi, new_display, pwm, reading, rtttl, scan, sensor, synth,
+    telemetry, text_panel, Compat, InputCompat, Monitor, Panel, TextPanel,
+};
+
+#[cfg(feature = "defmt")]
+use defmt_rtt as _;
+#[cfg(all(feature = "defmt", not(feature = "panic-display")))]
+use panic_probe as _;
+#[cfg(not(any(feature = "defmt", feature = "panic-display")))]
+use panic_rtt_target as _;
@@ -0,0 +1,111 @@
+//! Panic handler which shows the panic on the display.
+//!
+//! It replaces `panic-rtt-target` when the `panic-display` feature is
+//! enabled:
+//!
+//! ```text
+//! cargo embed --example lm75-temp-display-bp --features panic-display --release
+//! ```
+//!
+//! Since the program is stopped anyway, it takes I2C1, USART1 and the LED
+//! over from whoever owned them. It prints the panic over RTT and over
+//! USART1 at 115200 bauds, releases I2C1 in case the panic happened in the
+//! middle of a transfer and shows the message, file and line on the display.
+//! Then it blinks SOS on the LED forever.
+
+use crate::board::I2C_STANDARD_MODE;
+use crate::recovery::RecoverableI2c;
+use core::cell::Cell;
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use cortex_m::interrupt::{self, Mutex};
+use driver_examples_common::{
+    crash::Report,
+    heartbeat::{Heartbeat, Status, TICK_HZ},
+    new_display, TextPanel,
+};
+use ssd1306::I2CDisplayInterface;
+use stm32f1xx_hal::{
+    i2c::BlockingI2c,
+    pac,
+    prelude::*,
+    rcc::Clocks,
+    serial::{self, Serial},
+};
+
+/// Baud rate of the panic report on USART1.
+const BAUD_RATE: u32 = 115_200;
+
+/// Frequency of the internal oscillator the chip runs on before the board
+/// is set up.
+const HSI_FREQUENCY: u32 = 8_000_000;
+
+static CLOCKS: Mutex<Cell<Option<Clocks>>> = Mutex::new(Cell::new(None));
+
+/// Remember the clocks so that the peripherals can be set up again when
+/// panicking.
+pub(crate) fn register(clocks: Clocks) {
+    interrupt::free(|cs| CLOCKS.borrow(cs).set(Some(clocks)));
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    interrupt::disable();
+    let report = Report::new(info);
+    crate::error!("{}", report);
+
+    // SAFETY: Interrupts are disabled and the main program does not run
+    // anymore, so nothing else uses the peripherals.
+    let dp = unsafe { pac::Peripherals::steal() };
+    let clocks = interrupt::free(|cs| CLOCKS.borrow(cs).get());
+    let mut afio = dp.AFIO.constrain();
+    if let Some(clocks) = clocks {
+        let mut gpioa = dp.GPIOA.split();
+        let tx = gpioa.pa9.into_alternate_push_pull(&mut gpioa.crh);
+        let serial = Serial::usart1(
+            dp.USART1,
+            (tx, gpioa.pa10),
+            &mut afio.mapr,
+            serial::Config::default().baudrate(BAUD_RATE.bps()),
+            clocks,
+        );
+        let (mut tx, _) = serial.split();
+        let _ = write!(tx, "{}\r\n", report);
+
+        let mut gpiob = dp.GPIOB.split();
+        let scl = gpiob.pb8.into_alternate_open_drain(&mut gpiob.crh);
+        let sda = gpiob.pb9.into_alternate_open_drain(&mut gpiob.crh);
+        let i2c = BlockingI2c::i2c1(
+            dp.I2C1,
+            (scl, sda),
+            &mut afio.mapr,
+            I2C_STANDARD_MODE,
+            clocks,
+            1000,
+            10,
+            1000,
+            1000,
+        );
+        let mut i2c = RecoverableI2c::new(i2c, clocks);
+        i2c.recover();
+        let mut panel = TextPanel::new(new_display(I2CDisplayInterface::new(i2c)));
+        if panel.init().is_ok() {
+            let _ = report.show(&mut panel);
+        }
+    }
+
+    let mut gpioc = dp.GPIOC.split();
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    let tick_cycles = clocks.map_or(HSI_FREQUENCY, |clocks| clocks.sysclk().0) / TICK_HZ;
+    let mut heartbeat = Heartbeat::new(0);
+    heartbeat.feed(Status::Panic);
+    loop {
+        // The LED is lit when the pin is low.
+        if heartbeat.tick() {
+            led.set_low();
+        } else {
+            led.set_high();
+        }
+        cortex_m::asm::delay(tick_cycles);
+    }
+}
@@ -0,0 +1,231 @@
+//! I2C1 which recovers from a stuck bus.
+//!
+//! A device which glitches in the middle of a transfer, e.g. because of
+//! the level problems of 5V devices, can keep SDA low forever. The
+//! peripheral then reports errors for every following transfer. A
+//! [`RecoverableI2c`] detects this, releases the bus by clocking SCL until
+//! the device lets go of SDA, resets the peripheral and configures the
+//! devices again before retrying the transfer.
+
+use crate::board::I2c1;
+use driver_examples_common::{
+    compat::{CompatError, ErrorKinds},
+    Compat,
+};
+use embedded_hal::i2c::{self, ErrorKind, I2c, NoAcknowledgeSource, Operation};
+use stm32f1xx_hal::{i2c::Error, pac, rcc::Clocks};
+
+/// Maximum number of functions which can be registered with
+/// [`RecoverableI2c::on_recovery`].
+pub const MAX_REINITS: usize = 8;
+
+/// Function which configures a device again after the bus was recovered,
+/// e.g. by enabling it or setting its measurement range.
+///
+/// Since the device was most likely reset as well, it gets the raw I2C1
+/// peripheral to create a temporary driver on:
+///
+/// ```no_run
+/// # use driver_examples_bluepill::{recovery::I2cErrorKinds, Compat, I2c1};
+/// # use veml6075::{Calibration, Veml6075};
+/// let reinit = |i2c: &mut Compat<I2c1, I2cErrorKinds>| {
+///     Veml6075::new(Compat::new(i2c), Calibration::default())
+///         .enable()
+///         .is_ok()
+/// };
+/// ```
+///
+/// It returns whether the device could be configured.
+pub type Reinit = fn(&mut Compat<I2c1, I2cErrorKinds>) -> bool;
+
+/// Classifies the errors of I2C1. The HAL reports a timeout as
+/// `WouldBlock`.
+#[derive(Debug)]
+pub struct I2cErrorKinds;
+
+impl ErrorKinds<nb::Error<Error>> for I2cErrorKinds {
+    fn i2c(error: &nb::Error<Error>) -> ErrorKind {
+        match error {
+            nb::Error::Other(Error::Bus) => ErrorKind::Bus,
+            nb::Error::Other(Error::Arbitration) => ErrorKind::ArbitrationLoss,
+            nb::Error::Other(Error::Acknowledge) => {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)
+            }
+            nb::Error::Other(Error::Overrun) => ErrorKind::Overrun,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Bits 0-7 of GPIOB_CRH configure PB8 (SCL) and PB9 (SDA).
+const PINS_CRH_MASK: u32 = 0xFF;
+/// PB8 and PB9 as open-drain outputs at 2 MHz.
+const PINS_CRH_OPEN_DRAIN: u32 = 0x66;
+const SCL: u32 = 1 << 8;
+const SDA: u32 = 1 << 9;
+
+/// I2C1 which recovers from bus errors and timeouts.
+///
+/// The board sets the bus up with it, so every handle returned by
+/// `i2c.acquire_i2c()` recovers transparently. A transfer which fails with
+/// a bus error, a lost arbitration or a timeout is retried once after the
+/// recovery. A missing acknowledge is only reported, since that is how a
+/// device which is not there answers.
+pub struct RecoverableI2c {
+    i2c: Compat<I2c1, I2cErrorKinds>,
+    half_period_cycles: u32,
+    reinits: heapless::Vec<Reinit, MAX_REINITS>,
+    recoveries: u32,
+}
+
+impl RecoverableI2c {
+    /// Wrap the I2C1 peripheral.
+    pub fn new(i2c: I2c1, clocks: Clocks) -> Self {
+        RecoverableI2c {
+            i2c: Compat::with_error_kinds(i2c),
+            // 5 µs, the SCL clocks run at 100 kHz.
+            half_period_cycles: clocks.sysclk().0 / 200_000,
+            reinits: heapless::Vec::new(),
+            recoveries: 0,
+        }
+    }
+
+    /// Register a function which configures a device again after every
+    /// recovery.
+    ///
+    /// Returns the function back if [`MAX_REINITS`] are already
+    /// registered.
+    ///
+    /// The bus is shared, so register it through the bus:
+    ///
+    /// ```no_run
+    /// # use driver_examples_bluepill::{Board, Compat};
+    /// # use veml6075::{Calibration, Veml6075};
+    /// # let board = Board::take().unwrap();
+    /// board.i2c.lock(|i2c| {
+    ///     i2c.on_recovery(|i2c| {
+    ///         Veml6075::new(Compat::new(i2c), Calibration::default())
+    ///             .enable()
+    ///             .is_ok()
+    ///     })
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn on_recovery(&mut self, reinit: Reinit) -> Result<(), Reinit> {
+        self.reinits.push(reinit)
+    }
+
+    /// Number of times the bus has been recovered.
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    /// Release the bus, reset the peripheral and configure the devices
+    /// again.
+    ///
+    /// Returns whether all devices could be configured.
+    pub fn recover(&mut self) -> bool {
+        self.release_bus();
+        reset_peripheral();
+        self.recoveries = self.recoveries.wrapping_add(1);
+        // Configure every device, even after one of them failed.
+        let mut configured = true;
+        for reinit in &self.reinits {
+            configured &= reinit(&mut self.i2c);
+        }
+        configured
+    }
+
+    /// Clock SCL up to nine times until the device holding SDA low lets
+    /// go of it, then generate a stop condition.
+    ///
+    /// The pins are driven directly while I2C1 is disabled and are given
+    /// back to it afterwards.
+    fn release_bus(&mut self) {
+        // SAFETY: I2C1 and PB8/PB9 are owned by `self.i2c`. The rest of
+        // GPIOB_CRH is written back unchanged and the whole recovery runs
+        // inside the critical section of the bus.
+        let (i2c1, gpiob) = unsafe { (&*pac::I2C1::ptr(), &*pac::GPIOB::ptr()) };
+        i2c1.cr1.modify(|_, w| w.pe().clear_bit());
+
+        let crh = gpiob.crh.read().bits();
+        gpiob.bsrr.write(|w| unsafe { w.bits(SCL | SDA) });
+        gpiob
+            .crh
+            .write(|w| unsafe { w.bits((crh & !PINS_CRH_MASK) | PINS_CRH_OPEN_DRAIN) });
+
+        for _ in 0..9 {
+            if gpiob.idr.read().bits() & SDA != 0 {
+                break;
+            }
+            gpiob.bsrr.write(|w| unsafe { w.bits(SCL << 16) });
+            self.half_period();
+            gpiob.bsrr.write(|w| unsafe { w.bits(SCL) });
+            self.half_period();
+        }
+
+        // Stop condition: SDA goes high while SCL is high.
+        gpiob.bsrr.write(|w| unsafe { w.bits(SCL << 16) });
+        self.half_period();
+        gpiob.bsrr.write(|w| unsafe { w.bits(SDA << 16) });
+        self.half_period();
+        gpiob.bsrr.write(|w| unsafe { w.bits(SCL) });
+        self.half_period();
+        gpiob.bsrr.write(|w| unsafe { w.bits(SDA) });
+        self.half_period();
+
+        let current = gpiob.crh.read().bits();
+        gpiob
+            .crh
+            .write(|w| unsafe { w.bits((current & !PINS_CRH_MASK) | (crh & PINS_CRH_MASK)) });
+    }
+
+    fn half_period(&self) {
+        cortex_m::asm::delay(self.half_period_cycles);
+    }
+}
+
+/// Reset I2C1 and configure it as it was.
+///
+/// The HAL configured the timing when creating the peripheral, so the
+/// configuration registers are saved and written back after the reset.
+fn reset_peripheral() {
+    // SAFETY: only called by `RecoverableI2c`, which owns I2C1.
+    let i2c1 = unsafe { &*pac::I2C1::ptr() };
+    let cr2 = i2c1.cr2.read().bits();
+    let ccr = i2c1.ccr.read().bits();
+    let trise = i2c1.trise.read().bits();
+    let oar1 = i2c1.oar1.read().bits();
+
+    i2c1.cr1.write(|w| w.swrst().set_bit());
+    i2c1.cr1.write(|w| unsafe { w.bits(0) });
+
+    i2c1.cr2.write(|w| unsafe { w.bits(cr2) });
+    i2c1.ccr.write(|w| unsafe { w.bits(ccr) });
+    i2c1.trise.write(|w| unsafe { w.bits(trise) });
+    i2c1.oar1.write(|w| unsafe { w.bits(oar1) });
+    i2c1.cr1.write(|w| w.pe().set_bit());
+}
+
+impl i2c::ErrorType for RecoverableI2c {
+    type Error = CompatError<nb::Error<Error>>;
+}
+
+impl I2c for RecoverableI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match self.i2c.transaction(address, operations) {
+            Err(CompatError::I2c(
+                nb::Error::WouldBlock | nb::Error::Other(Error::Bus | Error::Arbitration),
+                _,
+            )) => {
+                self.recover();
+                self.i2c.transaction(address, operations)
+            }
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! USB CDC-ACM serial port on the USB connector of the board.
+//!
+//! The streaming examples used to need a USB to serial adapter wired to
+//! USART1. The Blue Pill has a USB port, which shows up on the computer as
+//! a serial port (e.g. `/dev/ttyACM0`) with this module. The USB interrupts
+//! answer the requests of the host, so the program only writes to it:
+//!
+//! ```no_run
+//! use core::fmt::Write;
+//! use driver_examples_bluepill::Board;
+//!
+//! let mut board = Board::take().unwrap();
+//! let mut serial = board.usb_serial().unwrap();
+//! writeln!(serial, "hr\r").unwrap();
+//! ```
+//!
+//! The output is dropped until a terminal opens the port, so that the
+//! examples also run when the board is only powered over USB.
+//!
+//! Some Blue Pill boards have the wrong pull-up resistor on D+ (R10). If the
+//! computer does not recognize the device, replace it with a 1.5 kΩ one.
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+use core::fmt;
+use cortex_m::interrupt::{free, Mutex};
+use embedded_hal_02::serial;
+use stm32f1xx_hal::{
+    pac::{interrupt, Interrupt, NVIC},
+    usb::{Peripheral, UsbBus, UsbBusType},
+};
+use usb_device::{bus::UsbBusAllocator, prelude::*};
+use usbd_serial::{SerialPort, USB_CLASS_CDC};
+
+/// Vendor and product ID shared by open source serial devices.
+const VID_PID: UsbVidPid = UsbVidPid(0x16c0, 0x27dd);
+
+struct Port {
+    device: UsbDevice<'static, UsbBusType>,
+    serial: SerialPort<'static, UsbBusType>,
+}
+
+impl Port {
+    /// Whether a terminal on the computer has opened the port.
+    fn is_open(&self) -> bool {
+        self.device.state() == UsbDeviceState::Configured && self.serial.dtr()
+    }
+
+    fn poll(&mut self) {
+        if self.device.poll(&mut [&mut self.serial]) {
+            // The examples only stream, so whatever the host sends is
+            // discarded.
+            let mut buffer = [0; 64];
+            let _ = self.serial.read(&mut buffer);
+        }
+    }
+}
+
+static PORT: Mutex<RefCell<Option<Port>>> = Mutex::new(RefCell::new(None));
+
+/// Serial port on the USB connector.
+pub struct UsbSerial {
+    _private: (),
+}
+
+impl UsbSerial {
+    /// Set up the USB device and handle it from the USB interrupts from now
+    /// on.
+    ///
+    /// Returns `None` if it has already been set up.
+    pub(crate) fn new(usb: Peripheral) -> Option<Self> {
+        let bus: &'static UsbBusAllocator<UsbBusType> =
+            cortex_m::singleton!(: UsbBusAllocator<UsbBusType> = UsbBus::new(usb))?;
+        let serial = SerialPort::new(bus);
+        let device = UsbDeviceBuilder::new(bus, VID_PID)
+            .manufacturer("driver-examples")
+            .product("Blue Pill serial")
+            .serial_number("0001")
+            .device_class(USB_CLASS_CDC)
+            .build();
+        free(|cs| PORT.borrow(cs).replace(Some(Port { device, serial })));
+        // SAFETY: The handlers only access the port in a critical section.
+        unsafe {
+            NVIC::unmask(Interrupt::USB_HP_CAN_TX);
+            NVIC::unmask(Interrupt::USB_LP_CAN_RX0);
+        }
+        Some(UsbSerial { _private: () })
+    }
+
+    /// Whether a terminal on the computer has opened the port.
+    pub fn is_connected(&self) -> bool {
+        free(|cs| PORT.borrow(cs).borrow().as_ref().is_some_and(Port::is_open))
+    }
+
+    /// Write all the data, waiting while the host reads it.
+    ///
+    /// The data is dropped if no terminal has opened the port.
+    pub fn write_all(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The interrupts send the buffered data to the host between
+            // the attempts.
+            let written = free(|cs| match PORT.borrow(cs).borrow_mut().as_mut() {
+                Some(port) if port.is_open() => match port.serial.write(data) {
+                    Ok(count) => Some(count),
+                    Err(UsbError::WouldBlock) => Some(0),
+                    Err(_) => None,
+                },
+                _ => None,
+            });
+            match written {
+                Some(count) => data = &data[count..],
+                None => return,
+            }
+        }
+    }
+}
+
+impl fmt::Write for UsbSerial {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_all(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Same interface as USART1 to send binary data, e.g. telemetry frames.
+impl serial::Write<u8> for UsbSerial {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Infallible> {
+        self.write_all(&[word]);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+fn poll() {
+    free(|cs| {
+        if let Some(port) = PORT.borrow(cs).borrow_mut().as_mut() {
+            port.poll();
+        }
+    });
+}
+
+#[interrupt]
+fn USB_HP_CAN_TX() {
+    poll();
+}
+
+#[interrupt]
+fn USB_LP_CAN_RX0() {
+    poll();
+}
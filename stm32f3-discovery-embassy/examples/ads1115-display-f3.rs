@@ -0,0 +1,97 @@
+//! Continuously measure the voltage in all four channels of an ADS1115
+//! analog-to-digital converter (ADC) and print them to an SSD1306 OLED
+//! display.
+//!
+//! The ADC task starts a conversion on each channel in turn and waits for
+//! it to finish while the display and the heartbeat LED keep running.
+//! To check the readings, connect the channels to a voltage divider as in
+//! the `ads1015-display-f3` example.
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1.
+//!
+//! ```
+//! F3   <-> ADS1115 <-> Display
+//! GND  <-> GND     <-> GND
+//! +5V  <-> +5V     <-> +5V
+//! PB7  <-> SDA     <-> SDA
+//! PB6  <-> SCL     <-> SCL
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example ads1115-display-f3 --release`,
+
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use driver_examples_embassy::{
+    adc::scale_reading, heartbeat, info, logging, reading::Reading, wait, AsyncTextPanel,
+    Discovery, I2cBus, Monitor,
+};
+use embassy_executor::Spawner;
+use embassy_sync::{blocking_mutex::raw::ThreadModeRawMutex, signal::Signal};
+use embassy_time::{Delay, Duration, Ticker};
+
+use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, TargetAddr};
+
+/// How often to check whether a conversion is done. At the default data
+/// rate of 128 SPS a conversion takes about 8 ms.
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Latest reading of all channels.
+static VALUES: Signal<ThreadModeRawMutex, Reading<[i16; 4]>> = Signal::new();
+
+#[embassy_executor::task]
+async fn measure(i2c: &'static I2cBus) {
+    let mut adc = Ads1x1x::new_ads1115(i2c.acquire_blocking_i2c(), TargetAddr::default());
+    // need to be able to measure [0-5V]
+    i2c.blocking(|| adc.set_full_scale_range(FullScaleRange::Within6_144V))
+        .await
+        .unwrap();
+
+    let mut monitor = Monitor::new("ADS1115");
+    let mut ticker = Ticker::every(Duration::from_millis(200));
+    loop {
+        let reading = monitor
+            .read_async(&mut Delay, async || {
+                let a0 = wait::ready(i2c, POLL_INTERVAL, || adc.read(AdcChannel::SingleA0)).await;
+                let a1 = wait::ready(i2c, POLL_INTERVAL, || adc.read(AdcChannel::SingleA1)).await;
+                let a2 = wait::ready(i2c, POLL_INTERVAL, || adc.read(AdcChannel::SingleA2)).await;
+                let a3 = wait::ready(i2c, POLL_INTERVAL, || adc.read(AdcChannel::SingleA3)).await;
+                a0.and_then(|a0| Ok([a0, a1?, a2?, a3?]))
+            })
+            .await;
+        VALUES.signal(reading);
+        ticker.next().await;
+    }
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    logging::init();
+    info!("ADS1115 async example");
+
+    let Discovery {
+        leds: [led, ..],
+        i2c,
+        display,
+        ..
+    } = Discovery::init();
+    spawner.spawn(heartbeat(led)).unwrap();
+    spawner.spawn(measure(i2c)).unwrap();
+
+    let mut panel = AsyncTextPanel::new(display);
+    panel.init().await.unwrap();
+    loop {
+        let reading = VALUES.wait().await;
+        panel
+            .show_reading(&reading, |panel, values| {
+                for (i, value) in values.iter().enumerate() {
+                    // make the number smaller for reading ease
+                    write!(panel.row(i), "Channel {}: {}", i, scale_reading(*value)).unwrap();
+                }
+            })
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Continuously measure the eCO2 and eTVOC in the air and print it to an
+//! SSD1306 OLED display.
+//! In order to compensate for the ambient temperature and humidity, an HDC2080
+//! sensor is used.
+//!
+//! Each sensor is read in its own task. The HDC2080 task measures the
+//! environment every 10 seconds and passes it to the CCS811 task, which
+//! reads the gas every second. Both send their readings to the main task,
+//! which shows them on the display, while the heartbeat task blinks LED 0.
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1.
+//!
+//! ```
+//! F3   <-> CCS811 <-> HDC2080 <-> Display
+//! GND  <-> GND    <-> GND     <-> GND
+//! 3.3V <-> VCC    <-> VCC     <-> VDD
+//! PB6  <-> SCL    <-> SCL     <-> SCL
+//! PB7  <-> SDA    <-> SDA     <-> SDA
+//! GND  <-> nWAKE
+//! 3.3V <-> RST
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example ccs811-gas-voc-hdc2080-display-f3 --release`,
+
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use driver_examples_embassy::{
+    heartbeat, info, logging, reading::Reading, wait, warn, AsyncTextPanel, Compat, Discovery,
+    I2cBus, Monitor, I2C_FAST_FREQUENCY,
+};
+use embassy_executor::Spawner;
+use embassy_sync::{blocking_mutex::raw::ThreadModeRawMutex, channel::Channel, signal::Signal};
+use embassy_time::{Delay, Duration, Ticker, Timer};
+
+use embedded_ccs811::{
+    prelude::*, AlgorithmResult, Ccs811Awake, MeasurementMode, SlaveAddr as Ccs811SlaveAddr,
+};
+use hdc20xx::{Hdc20xx, Measurement, SlaveAddr as Hdc20xxSlaveAddr};
+
+/// How often to check whether a measurement is done.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+enum Update {
+    Gas(Reading<AlgorithmResult>),
+    Environment(Reading<Measurement>),
+}
+
+/// Readings to show on the display.
+static UPDATES: Channel<ThreadModeRawMutex, Update, 4> = Channel::new();
+
+/// Latest temperature in ºC and relative humidity in percent for the
+/// CCS811 compensation.
+static ENVIRONMENT: Signal<ThreadModeRawMutex, (f32, f32)> = Signal::new();
+
+#[embassy_executor::task]
+async fn environment(i2c: &'static I2cBus) {
+    let mut hdc2080 = Hdc20xx::new(
+        Compat::new(i2c.acquire_blocking_i2c()),
+        Hdc20xxSlaveAddr::default(),
+    );
+    let mut monitor = Monitor::new("HDC2080");
+    // The temperature and humidity change slowly so only measure them
+    // every 10 seconds.
+    let mut ticker = Ticker::every(Duration::from_secs(10));
+    loop {
+        let reading = monitor
+            .read_async(&mut Delay, async || {
+                wait::ready(i2c, POLL_INTERVAL, || hdc2080.read()).await
+            })
+            .await;
+        if let Ok(env) = &reading {
+            ENVIRONMENT.signal((env.temperature, env.humidity.unwrap_or(0.0)));
+        }
+        UPDATES.send(Update::Environment(reading)).await;
+        ticker.next().await;
+    }
+}
+
+#[embassy_executor::task]
+async fn gas(i2c: &'static I2cBus) {
+    let mut ccs811 = Ccs811Awake::new(i2c.acquire_blocking_i2c(), Ccs811SlaveAddr::default());
+    i2c.blocking(|| ccs811.software_reset()).await.unwrap();
+    Timer::after_millis(10).await;
+
+    let mut ccs811 = i2c
+        .blocking(|| ccs811.start_application())
+        .await
+        .ok()
+        .unwrap();
+    let (temperature, humidity) = ENVIRONMENT.wait().await;
+    i2c.blocking(|| {
+        ccs811.set_environment(humidity, temperature)?;
+        ccs811.set_mode(MeasurementMode::ConstantPower1s)
+    })
+    .await
+    .unwrap();
+
+    let mut monitor = Monitor::new("CCS811");
+    loop {
+        let reading = monitor
+            .read_async(&mut Delay, async || {
+                wait::ready(i2c, POLL_INTERVAL, || ccs811.data()).await
+            })
+            .await;
+        if let Some((temperature, humidity)) = ENVIRONMENT.try_take() {
+            let compensated = i2c
+                .blocking(|| ccs811.set_environment(humidity, temperature))
+                .await;
+            if let Err(error) = compensated {
+                warn!("Could not compensate: {}", logging::Dbg(&error));
+            }
+        }
+        UPDATES.send(Update::Gas(reading)).await;
+    }
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    logging::init();
+    info!("CCS811/HDC2080 async example");
+
+    let Discovery {
+        leds: [led, ..],
+        i2c,
+        display,
+        ..
+    } = Discovery::init_with_i2c_frequency(I2C_FAST_FREQUENCY);
+    spawner.spawn(heartbeat(led)).unwrap();
+    spawner.spawn(environment(i2c)).unwrap();
+    spawner.spawn(gas(i2c)).unwrap();
+
+    let mut panel = AsyncTextPanel::new(display);
+    panel.init().await.unwrap();
+    loop {
+        match UPDATES.receive().await {
+            Update::Gas(reading) => {
+                panel
+                    .show_reading(&reading, |panel, data| {
+                        write!(panel.row(0), "eCO2: {}", data.eco2).unwrap();
+                        write!(panel.row(1), "eTVOC: {}", data.etvoc).unwrap();
+                    })
+                    .await
+            }
+            Update::Environment(reading) => {
+                panel
+                    .show_reading(&reading, |panel, env| {
+                        write!(panel.row(2), "Temp: {:.2}ºC", env.temperature).unwrap();
+                        write!(
+                            panel.row(3),
+                            "Humidity: {:.2}%",
+                            env.humidity.unwrap_or(0.0)
+                        )
+                        .unwrap();
+                    })
+                    .await
+            }
+        }
+        .unwrap();
+    }
+}
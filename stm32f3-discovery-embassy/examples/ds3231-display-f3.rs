@@ -0,0 +1,89 @@
+//! Stores the date and time on a DS3231 real-time clock (RTC).
+//! Then reads the date and time every second and prints it to an SSD1306
+//! OLED display.
+//!
+//! The clock task reads the RTC on a one-second tick, so the time shown
+//! does not drift with the time it takes to refresh the display.
+//! LED 1 is lit for the first 30 seconds after setting the time.
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1.
+//!
+//! ```
+//! F3  <-> DS3231 <-> Display
+//! GND <-> GND    <-> GND
+//! +5V <-> +5V    <-> +5V
+//! PB7 <-> SDA    <-> SDA
+//! PB6 <-> SCL    <-> SCL
+//! ```
+//!
+//! Run with:
+//! `cargo embed --example ds3231-display-f3 --release`,
+
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use driver_examples_embassy::{
+    heartbeat, info, logging, reading::Reading, AsyncTextPanel, Discovery, I2cBus, Led, Monitor,
+};
+use embassy_executor::Spawner;
+use embassy_sync::{blocking_mutex::raw::ThreadModeRawMutex, signal::Signal};
+use embassy_time::{Delay, Duration, Ticker};
+
+use ds323x::{DateTimeAccess, Ds323x, NaiveDate, NaiveDateTime};
+
+static NOW: Signal<ThreadModeRawMutex, Reading<NaiveDateTime>> = Signal::new();
+
+#[embassy_executor::task]
+async fn clock(i2c: &'static I2cBus, mut led: Led) {
+    let mut rtc = Ds323x::new_ds3231(i2c.acquire_blocking_i2c());
+    let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
+        .unwrap()
+        .and_hms_opt(10, 21, 34)
+        .unwrap();
+    i2c.blocking(|| rtc.set_datetime(&begin)).await.unwrap();
+    led.set_high();
+
+    let mut monitor = Monitor::new("DS3231");
+    let mut ticker = Ticker::every(Duration::from_secs(1));
+    loop {
+        let reading = monitor
+            .read_async(&mut Delay, async || i2c.blocking(|| rtc.datetime()).await)
+            .await;
+        if let Ok(now) = &reading {
+            if (*now - begin).num_seconds() >= 30 {
+                led.set_low();
+            }
+        }
+        NOW.signal(reading);
+        ticker.next().await;
+    }
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    logging::init();
+    info!("DS3231 async example");
+
+    let Discovery {
+        leds: [heartbeat_led, led, ..],
+        i2c,
+        display,
+        ..
+    } = Discovery::init();
+    spawner.spawn(heartbeat(heartbeat_led)).unwrap();
+    spawner.spawn(clock(i2c, led)).unwrap();
+
+    let mut panel = AsyncTextPanel::new(display);
+    panel.init().await.unwrap();
+    loop {
+        let reading = NOW.wait().await;
+        panel
+            .show_reading(&reading, |panel, now| {
+                write!(panel.row(0), "{}", now.date()).unwrap();
+                write!(panel.row(1), "{}", now.time()).unwrap();
+            })
+            .await
+            .unwrap();
+    }
+}
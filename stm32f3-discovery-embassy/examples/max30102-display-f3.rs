@@ -0,0 +1,114 @@
+//! This example continuously reads the samples from a MAX30102 heart-rate
+//! and pulse oximeter (SpO2) sensor in heart-rate mode and displays the
+//! values in an SSD1306 OLED display.
+//!
+//! The sensor task empties the FIFO of the MAX30102 as it fills up. The
+//! display only shows the latest samples, at its own pace, so a slow
+//! display refresh does not make the FIFO overflow.
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1.
+//!
+//! ```
+//! F3    <-> MAX30102 <-> Display
+//! GND   <-> GND      <-> GND
+//! +3.3V <-> +3.3V
+//! +5V   <-> +5V      <-> +5V
+//! PB7   <-> SDA      <-> SDA
+//! PB6   <-> SCL      <-> SCL
+//! ```
+//! Run with:
+//! `cargo embed --example max30102-display-f3 --release`
+
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use driver_examples_embassy::{
+    heartbeat, info, logging, reading::Reading, AsyncTextPanel, Discovery, I2cBus, Monitor,
+    I2C_FAST_FREQUENCY,
+};
+use embassy_executor::Spawner;
+use embassy_sync::{blocking_mutex::raw::ThreadModeRawMutex, signal::Signal};
+use embassy_time::{Delay, Duration, Ticker, Timer};
+
+use max3010x::{Led as MaxLed, Max3010x};
+
+/// Number of samples the FIFO holds.
+const FIFO_DEPTH: usize = 32;
+
+/// Latest samples read from the FIFO.
+struct Samples {
+    /// Number of samples in the last read.
+    count: u8,
+    /// Last three samples.
+    latest: [u32; 3],
+}
+
+static SAMPLES: Signal<ThreadModeRawMutex, Reading<Samples>> = Signal::new();
+
+#[embassy_executor::task]
+async fn sensor(i2c: &'static I2cBus) {
+    let mut max30102 = Max3010x::new_max30102(i2c.acquire_blocking_i2c());
+    i2c.blocking(|| max30102.reset()).await.unwrap();
+    Timer::after_millis(50).await;
+
+    let mut max30102 = i2c
+        .blocking(|| {
+            let mut max30102 = max30102.into_heart_rate()?;
+            max30102.set_pulse_amplitude(MaxLed::All, 15)?;
+            max30102.enable_fifo_rollover()?;
+            Ok::<_, max3010x::Error<_>>(max30102)
+        })
+        .await
+        .unwrap();
+
+    let mut monitor = Monitor::new("MAX30102");
+    // The FIFO fills up in well over 100 ms at the default sample rate.
+    let mut ticker = Ticker::every(Duration::from_millis(100));
+    let mut data = [0; FIFO_DEPTH];
+    loop {
+        let count = monitor
+            .read_async(&mut Delay, async || {
+                i2c.blocking(|| max30102.read_fifo(&mut data)).await
+            })
+            .await;
+        let reading = count.map(|count| {
+            let end = usize::from(count).max(3);
+            let mut latest = [0; 3];
+            latest.copy_from_slice(&data[end - 3..end]);
+            Samples { count, latest }
+        });
+        SAMPLES.signal(reading);
+        ticker.next().await;
+    }
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    logging::init();
+    info!("MAX30102 async example");
+
+    let Discovery {
+        leds: [led, ..],
+        i2c,
+        display,
+        ..
+    } = Discovery::init_with_i2c_frequency(I2C_FAST_FREQUENCY);
+    spawner.spawn(heartbeat(led)).unwrap();
+    spawner.spawn(sensor(i2c)).unwrap();
+
+    let mut panel = AsyncTextPanel::new(display);
+    panel.init().await.unwrap();
+    loop {
+        let reading = SAMPLES.wait().await;
+        panel
+            .show_reading(&reading, |panel, samples| {
+                write!(panel.row(0), "Samples: {}", samples.count).unwrap();
+                for (i, sample) in samples.latest.iter().enumerate() {
+                    write!(panel.row(i + 1), "{}", sample).unwrap();
+                }
+            })
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+//! Peripheral setup shared by all examples.
+
+use driver_examples_common::{compat::spi_mode, new_async_display, AsyncDisplay};
+use embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice;
+use embassy_stm32::{
+    bind_interrupts,
+    gpio::{Level, Output, Speed},
+    i2c::{self, I2c},
+    mode::Async,
+    peripherals::{
+        self, DMA1_CH2, DMA1_CH3, PA0, PA1, PA2, PA3, PA4, PA5, PA6, PA7, PA8, PB0, PB1, PB10,
+        PB11, PB12, PB13, PB14, PB15, PB2, PB4, PB5, PB8, PB9, SPI1,
+    },
+    spi::{self, Spi},
+    time::Hertz,
+};
+use embassy_sync::{blocking_mutex::raw::NoopRawMutex, mutex::Mutex};
+use embedded_hal::{i2c::Operation, spi::Mode};
+use embedded_hal_bus::spi::{ExclusiveDevice, NoDelay};
+use ssd1306::{prelude::*, I2CDisplayInterface};
+use static_cell::StaticCell;
+
+bind_interrupts!(struct Irqs {
+    I2C1_EV => i2c::EventInterruptHandler<peripherals::I2C1>;
+    I2C1_ER => i2c::ErrorInterruptHandler<peripherals::I2C1>;
+});
+
+/// I2C1 peripheral on PB6 (SCL) and PB7 (SDA), transferring with DMA1
+/// channels 6 (TX) and 7 (RX).
+pub type I2c1 = I2c<'static, Async>;
+
+/// Async handle to the shared I2C1 bus for drivers implementing the
+/// `embedded-hal-async` traits, like the display.
+pub type I2cProxy = I2cDevice<'static, NoopRawMutex, I2c1>;
+
+/// SSD1306 OLED display connected to the shared I2C1 bus.
+pub type Display = AsyncDisplay<I2CInterface<I2cProxy>>;
+
+/// SPI1 peripheral on PA5 (SCK), PA6 (MISO) and PA7 (MOSI), transferring
+/// with DMA1 channels 3 (TX) and 2 (RX).
+pub type Spi1 = Spi<'static, Async>;
+
+/// Device on SPI1 with its own chip select pin. It implements both the
+/// blocking and the async `SpiDevice` traits.
+pub type Spi1Device = ExclusiveDevice<Spi1, Output<'static>, NoDelay>;
+
+/// One of the compass rose user LEDs on PE8-PE15.
+pub type Led = Output<'static>;
+
+/// Standard mode I2C at 100 kHz. Most devices are fine with this.
+pub const I2C_STANDARD_FREQUENCY: Hertz = Hertz(100_000);
+
+/// Fast mode I2C at 400 kHz.
+pub const I2C_FAST_FREQUENCY: Hertz = Hertz(400_000);
+
+/// Bus sharing I2C1 between the display and the devices.
+///
+/// The bus is shared through an async mutex. Async transfers, like the
+/// display flushes, use DMA and hold the mutex until they are done while
+/// the other tasks keep running.
+///
+/// The sensor drivers only implement the blocking `embedded-hal` traits,
+/// so their transactions stay blocking. Run them in
+/// [`blocking`](I2cBus::blocking), which waits for the async transfers to
+/// finish first.
+pub struct I2cBus(Mutex<NoopRawMutex, I2c1>);
+
+impl I2cBus {
+    /// Get an async handle to the bus for a driver.
+    pub fn acquire_i2c(&'static self) -> I2cProxy {
+        I2cDevice::new(&self.0)
+    }
+
+    /// Get a blocking handle to the bus for a driver which only implements
+    /// the blocking `embedded-hal` traits.
+    pub fn acquire_blocking_i2c(&'static self) -> BlockingI2cProxy {
+        BlockingI2cProxy(self)
+    }
+
+    /// Wait until no async transfer is using the bus and then run `f`,
+    /// which calls blocking drivers.
+    ///
+    /// All tasks run on the same executor and `f` cannot `.await`, so
+    /// nothing else can take the bus until `f` returns.
+    pub async fn blocking<R>(&self, f: impl FnOnce() -> R) -> R {
+        drop(self.0.lock().await);
+        f()
+    }
+}
+
+/// Blocking handle to the shared I2C1 bus.
+///
+/// Transactions panic if an async transfer is using the bus, so they must
+/// be done in [`I2cBus::blocking`].
+pub struct BlockingI2cProxy(&'static I2cBus);
+
+impl embedded_hal::i2c::ErrorType for BlockingI2cProxy {
+    type Error = i2c::Error;
+}
+
+impl embedded_hal::i2c::I2c for BlockingI2cProxy {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut i2c = (self.0).0.try_lock().expect(
+            "I2C1 is in use by an async transfer. Use blocking drivers in I2cBus::blocking",
+        );
+        i2c.blocking_transaction(address, operations)
+    }
+}
+
+/// GPIOA pins not used by the board setup.
+///
+/// PA0 is connected to the user button.
+/// PA5-PA7 are reserved for SPI1.
+pub struct GpioA {
+    pub pa0: PA0,
+    pub pa1: PA1,
+    pub pa2: PA2,
+    pub pa3: PA3,
+    pub pa4: PA4,
+    pub pa8: PA8,
+}
+
+/// GPIOB pins not used by the board setup.
+///
+/// PB6-PB7 are used by I2C1.
+pub struct GpioB {
+    pub pb0: PB0,
+    pub pb1: PB1,
+    pub pb2: PB2,
+    pub pb4: PB4,
+    pub pb5: PB5,
+    pub pb8: PB8,
+    pub pb9: PB9,
+    pub pb10: PB10,
+    pub pb11: PB11,
+    pub pb12: PB12,
+    pub pb13: PB13,
+    pub pb14: PB14,
+    pub pb15: PB15,
+}
+
+/// STM32F3 Discovery board as used in the async examples.
+///
+/// ```
+/// F3   <-> Display
+/// GND  <-> GND
+/// +5V  <-> VDD
+/// PB6  <-> SCL
+/// PB7  <-> SDA
+/// ```
+pub struct Discovery {
+    /// User LEDs going clockwise around the compass rose starting at
+    /// north: LD3 (PE9), LD5 (PE10), LD7 (PE11), LD9 (PE12), LD10 (PE13),
+    /// LD8 (PE14), LD6 (PE15) and LD4 (PE8).
+    ///
+    /// `leds[0]` is the heartbeat LED used by the examples.
+    pub leds: [Led; 8],
+    /// Shared I2C1 bus. Use `i2c.acquire_blocking_i2c()` to get a handle
+    /// for a blocking driver and `i2c.acquire_i2c()` for an async one.
+    pub i2c: &'static I2cBus,
+    /// SSD1306 display on the I2C1 bus.
+    ///
+    /// It is not initialized since not all setups include a display.
+    /// Call `display.init().await` before using it.
+    pub display: Display,
+    /// Free GPIOA pins.
+    pub gpioa: GpioA,
+    /// Free GPIOB pins.
+    pub gpiob: GpioB,
+    spi1: Option<(SPI1, PA5, PA6, PA7, DMA1_CH3, DMA1_CH2)>,
+}
+
+impl Discovery {
+    /// Initialize Embassy and set up the board with I2C1 in standard mode
+    /// (100 kHz).
+    ///
+    /// Panics if called more than once.
+    pub fn init() -> Self {
+        Self::init_with_i2c_frequency(I2C_STANDARD_FREQUENCY)
+    }
+
+    /// Initialize Embassy and set up the board with I2C1 running at the
+    /// given frequency.
+    ///
+    /// Panics if called more than once.
+    pub fn init_with_i2c_frequency(frequency: Hertz) -> Self {
+        static I2C_BUS: StaticCell<I2cBus> = StaticCell::new();
+
+        let p = embassy_stm32::init(Default::default());
+
+        let mut config = i2c::Config::default();
+        config.scl_pullup = true;
+        config.sda_pullup = true;
+        let i2c = I2c::new(
+            p.I2C1, p.PB6, p.PB7, Irqs, p.DMA1_CH6, p.DMA1_CH7, frequency, config,
+        );
+        let i2c: &'static I2cBus = I2C_BUS.init(I2cBus(Mutex::new(i2c)));
+
+        let display = new_async_display(I2CDisplayInterface::new(i2c.acquire_i2c()));
+
+        let leds = [
+            Output::new(p.PE9, Level::Low, Speed::Low),
+            Output::new(p.PE10, Level::Low, Speed::Low),
+            Output::new(p.PE11, Level::Low, Speed::Low),
+            Output::new(p.PE12, Level::Low, Speed::Low),
+            Output::new(p.PE13, Level::Low, Speed::Low),
+            Output::new(p.PE14, Level::Low, Speed::Low),
+            Output::new(p.PE15, Level::Low, Speed::Low),
+            Output::new(p.PE8, Level::Low, Speed::Low),
+        ];
+
+        Discovery {
+            leds,
+            i2c,
+            display,
+            gpioa: GpioA {
+                pa0: p.PA0,
+                pa1: p.PA1,
+                pa2: p.PA2,
+                pa3: p.PA3,
+                pa4: p.PA4,
+                pa8: p.PA8,
+            },
+            gpiob: GpioB {
+                pb0: p.PB0,
+                pb1: p.PB1,
+                pb2: p.PB2,
+                pb4: p.PB4,
+                pb5: p.PB5,
+                pb8: p.PB8,
+                pb9: p.PB9,
+                pb10: p.PB10,
+                pb11: p.PB11,
+                pb12: p.PB12,
+                pb13: p.PB13,
+                pb14: p.PB14,
+                pb15: p.PB15,
+            },
+            spi1: Some((p.SPI1, p.PA5, p.PA6, p.PA7, p.DMA1_CH3, p.DMA1_CH2)),
+        }
+    }
+
+    /// Set up SPI1 with the SPI mode and frequency required by a device
+    /// and its chip select pin (e.g. PB1 or PB5).
+    ///
+    /// Returns `None` if SPI1 has already been set up.
+    pub fn spi1(
+        &mut self,
+        mode: Mode,
+        frequency: Hertz,
+        chip_select: Output<'static>,
+    ) -> Option<Spi1Device> {
+        let (spi, sck, miso, mosi, tx_dma, rx_dma) = self.spi1.take()?;
+        let mut config = spi::Config::default();
+        config.mode = spi_mode(mode);
+        config.frequency = frequency;
+        let spi = Spi::new(spi, sck, mosi, miso, tx_dma, rx_dma, config);
+        ExclusiveDevice::new_no_delay(spi, chip_select).ok()
+    }
+}
@@ -0,0 +1,66 @@
+//! Board support for the async STM32F3 Discovery examples running on
+//! [Embassy](https://embassy.dev).
+//!
+//! The setup is the same as in the blocking examples: I2C1 on PB6/PB7
+//! shared between the devices and an SSD1306 display, SPI1 and the compass
+//! rose LEDs. Instead of serializing everything with delays, the sensors,
+//! the display and the heartbeat LED run as separate tasks:
+//!
+//! ```no_run
+//! use driver_examples_embassy::{heartbeat, AsyncTextPanel, Discovery};
+//! # async fn example(spawner: embassy_executor::Spawner) {
+//!
+//! let Discovery {
+//!     leds: [led, ..],
+//!     i2c,
+//!     display,
+//!     ..
+//! } = Discovery::init();
+//! spawner.spawn(heartbeat(led)).unwrap();
+//! let mut panel = AsyncTextPanel::new(display);
+//! panel.init().await.unwrap();
+//! # }
+//! ```
+//!
+//! The display uses the async SSD1306 interface, so its flushes transfer
+//! with DMA while the other tasks run. The sensor drivers only implement
+//! the blocking `embedded-hal` traits and stay blocking: each of their
+//! transactions runs in [`I2cBus::blocking`] once the bus is free, and
+//! only the time a device needs to measure is spent waiting
+//! asynchronously, see [`wait`].
+//!
+//! The examples log over RTT, either as formatted text or with `defmt`, see
+//! [`logging`]. The crate also provides their panic handler:
+//! `panic-rtt-target` by default or `panic-probe` with the `defmt` feature.
+#![no_std]
+
+mod board;
+pub use crate::board::{
+    BlockingI2cProxy, Discovery, Display, GpioA, GpioB, I2c1, I2cBus, I2cProxy, Led, Spi1,
+    Spi1Device, I2C_FAST_FREQUENCY, I2C_STANDARD_FREQUENCY,
+};
+#[path = "../../common/board/logging.rs"]
+pub mod logging;
+pub mod wait;
+pub use driver_examples_common::{adc, reading, AsyncPanel, AsyncTextPanel, Compat, Monitor};
+
+use embassy_time::{Duration, Ticker};
+
+#[cfg(feature = "defmt")]
+use defmt_rtt as _;
+#[cfg(feature = "defmt")]
+use panic_probe as _;
+#[cfg(not(feature = "defmt"))]
+use panic_rtt_target as _;
+
+/// Blink an LED to show that the executor is running.
+///
+/// If the LED stops blinking, a task is blocking the executor.
+#[embassy_executor::task]
+pub async fn heartbeat(mut led: Led) {
+    let mut ticker = Ticker::every(Duration::from_millis(500));
+    loop {
+        led.toggle();
+        ticker.next().await;
+    }
+}
@@ -0,0 +1,39 @@
+//! Waiting for devices which are polled through `nb`.
+//!
+//! Many drivers start a measurement on the first call and return
+//! `nb::Error::WouldBlock` until it is done. Instead of spinning with
+//! `nb::block!`, wait for them with [`ready`] so that the other tasks keep
+//! running in the meantime:
+//!
+//! ```no_run
+//! # use driver_examples_embassy::{wait, I2cBus};
+//! # use embassy_time::Duration;
+//! # async fn example(i2c: &'static I2cBus, mut read: impl FnMut() -> nb::Result<u16, ()>) {
+//! let value = wait::ready(i2c, Duration::from_millis(5), || read()).await;
+//! # }
+//! ```
+//!
+//! The drivers are blocking, so each poll is still a blocking I2C
+//! transaction. Only the time in between, while the device measures, is
+//! spent waiting asynchronously.
+
+use crate::I2cBus;
+use embassy_time::{Duration, Timer};
+
+/// Call `poll` every `interval` until it does not return
+/// `nb::Error::WouldBlock` any more.
+///
+/// Each call runs in [`I2cBus::blocking`], so `poll` can use blocking
+/// drivers on the shared bus.
+pub async fn ready<T, E, F>(i2c: &I2cBus, interval: Duration, mut poll: F) -> Result<T, E>
+where
+    F: FnMut() -> nb::Result<T, E>,
+{
+    loop {
+        match i2c.blocking(&mut poll).await {
+            Ok(value) => return Ok(value),
+            Err(nb::Error::Other(error)) => return Err(error),
+            Err(nb::Error::WouldBlock) => Timer::after(interval).await,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Play ringtones in the RTTTL (Nokia ringtone) format using an AD9833
+//! waveform generator / direct digital synthesizer.
+//!
+//! The built-in library has some short status jingles and a few melodies,
+//! see `driver_examples_common::rtttl`. Pressing the user button plays the
+//! next tune. Per serial, send the number or the name of a tune followed by
+//! a new line to play it, `stop` to stop playing and `list` to show the
+//! tunes.
+//!
+//! TIM2 ticks every millisecond and times the notes.
+//!
+//! This example is runs on the STM32F3 Discovery board using SPI1 and
+//! USART1.
+//!
+//! To setup the serial communication, have a look at the discovery book:
+//! https://rust-embedded.github.io/discovery/10-serial-communication/index.html
+//!
+//! ```
+//! F3   <-> AD9833  <-> Amplifier
+//! GND  <-> VSS     <-> GND
+//! 3.3V <-> VDD
+//! 5V               <-> VCC
+//! PA5  <-> CLK
+//! PA7  <-> DAT
+//! PB5  <-> FSYNC
+//!          OUT     <-> IN
+//!
+//! F3   <-> Serial device
+//! GND  <-> GND
+//! PA9  <-> TX
+//! PA10 <-> RX
+//! ```
+//!
+//! You will need an amplifier like the PAM8403 or similar and a speaker.
+//!
+//! Run with:
+//! `cargo run --example ad9833-rtttl-player-f3 --target thumbv7em-none-eabihf`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging,
+    rtttl::{Command, CommandLine, Jukebox, Rtttl, LIBRARY},
+    synth::NotePlayer,
+    Compat, Discovery,
+};
+use nb::block;
+use stm32f3xx_hal::{prelude::*, time::duration::Microseconds};
+
+use ad983x::{Ad983x, MODE};
+
+/// Frequency of the timer which times the notes.
+const TICK_HZ: u32 = 1_000;
+/// The button is read every 20 ms, which is longer than it bounces.
+const BUTTON_TICKS: u32 = 20;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("AD9833 RTTTL example");
+
+    let mut board = Discovery::take().unwrap();
+    let spi1 = board.spi1(MODE, 1.MHz()).unwrap();
+    let mut serial = board.usart1(115_200.Bd()).unwrap();
+    let chip_select = board
+        .gpiob
+        .pb5
+        .into_push_pull_output(&mut board.gpiob.moder, &mut board.gpiob.otyper);
+    let spi = spi1.acquire_spi(Compat::new(chip_select)).unwrap();
+    // The user button has a pull-down resistor on the board.
+    let button = board
+        .gpioa
+        .pa0
+        .into_floating_input(&mut board.gpioa.moder, &mut board.gpioa.pupdr);
+    let Discovery {
+        leds: [led, ..],
+        heartbeat,
+        mut tim2,
+        ..
+    } = board;
+    heartbeat.start(led);
+    tim2.start(Microseconds(1_000_000 / TICK_HZ));
+
+    let mut synth = Ad983x::new_ad9833(spi);
+    synth.reset().unwrap();
+    synth.enable().unwrap();
+
+    let mut player = NotePlayer::default();
+    let mut jukebox = Jukebox::new(TICK_HZ);
+    let mut command_line = CommandLine::default();
+    let mut was_pressed = false;
+    let mut ticks = 0;
+    info!("Playing {}", jukebox.play(0).unwrap());
+    loop {
+        block!(tim2.wait()).unwrap();
+        heartbeat::feed(Status::Healthy);
+        ticks += 1;
+
+        let mut selected = None;
+        if let Ok(byte) = serial.read() {
+            match command_line.push(byte) {
+                Some(Command::Play(index)) => selected = Some(jukebox.play(index)),
+                Some(Command::Stop) => jukebox.stop(),
+                Some(Command::List) => {
+                    for (number, tune) in LIBRARY.iter().enumerate() {
+                        let name = Rtttl::parse(tune).unwrap().name();
+                        write!(serial, "{}: {}\r\n", number + 1, name).unwrap();
+                    }
+                }
+                Some(Command::Unknown) => write!(serial, "Unknown command\r\n").unwrap(),
+                None => {}
+            }
+        }
+        if ticks % BUTTON_TICKS == 0 {
+            let pressed = button.is_high().unwrap();
+            if pressed && !was_pressed {
+                selected = Some(jukebox.play_next());
+            }
+            was_pressed = pressed;
+        }
+        if let Some(name) = selected {
+            info!("Playing {}", name.unwrap());
+        }
+
+        if let Some(note) = jukebox.tick() {
+            player.play(&mut synth, note).unwrap();
+        }
+    }
+}
@@ -66,115 +66,59 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, warn, Discovery, Monitor, TextPanel,
 };
-use embedded_hal::adc::OneShot;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
 
-use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, SlaveAddr};
+use ads1x1x::{channel as AdcChannel, Ads1x1x, FullScaleRange, TargetAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("ADS1015 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDisplayInterface::new(manager.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    led.set_high().unwrap();
-    delay.delay_ms(50_u16);
-    led.set_low().unwrap();
-    let mut adc = Ads1x1x::new_ads1015(manager.acquire_i2c(), SlaveAddr::default());
+    logging::init();
+    info!("ADS1015 example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut adc = Ads1x1x::new_ads1015(i2c.acquire_i2c(), TargetAddr::default());
     // need to be able to measure [0-5V]
     adc.set_full_scale_range(FullScaleRange::Within6_144V)
         .unwrap();
 
+    let mut monitor = Monitor::new("ADS1015");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 does not blink, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(50_u16);
-        led.set_low().unwrap();
-
         // Read voltage in all channels
-        let values = [
-            block!(adc.read(&mut AdcChannel::SingleA0)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA1)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA2)).unwrap_or(8091),
-            block!(adc.read(&mut AdcChannel::SingleA3)).unwrap_or(8091),
-        ];
-
-        let mut lines: [heapless::String<32>; 4] = [
-            heapless::String::new(),
-            heapless::String::new(),
-            heapless::String::new(),
-            heapless::String::new(),
-        ];
-
-        disp.clear();
-        for i in 0..values.len() {
-            write!(lines[i], "Channel {}: {}", i, values[i]).unwrap();
-            Text::with_baseline(
-                &lines[i],
-                Point::new(0, i as i32 * 16),
-                text_style,
-                Baseline::Top,
-            )
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || {
+            block!(adc.read(AdcChannel::SingleA0)).and_then(|a0| {
+                Ok([
+                    a0,
+                    block!(adc.read(AdcChannel::SingleA1))?,
+                    block!(adc.read(AdcChannel::SingleA2))?,
+                    block!(adc.read(AdcChannel::SingleA3))?,
+                ])
+            })
+        });
+        panel
+            .show_reading(&reading, |panel, values| {
+                for (i, value) in values.iter().enumerate() {
+                    write!(panel.row(i), "Channel {}: {}", i, value).unwrap();
+                }
+            })
             .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -1,5 +1,5 @@
 //! Stores some data on an AT24C256C EEPROM.
-//! Then reads it again and if it matches, blinks LED 0.
+//! Then reads it again and shows on the heartbeat LED whether it matches.
 //!
 //! Introductory blog post here:
 //! https://blog.eldruin.com/24x-serial-eeprom-driver-in-rust/
@@ -22,66 +22,46 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, Discovery,
+};
+use embedded_hal::delay::DelayNs;
 
 use eeprom24x::{Eeprom24x, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("AT24C256 example");
+    logging::init();
+    info!("AT24C256 example");
 
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
 
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let mut eeprom = Eeprom24x::new_24x256(i2c, SlaveAddr::Alternative(true, true, true));
+    let mut eeprom =
+        Eeprom24x::new_24x256(i2c.acquire_i2c(), SlaveAddr::Alternative(true, true, true));
     let memory_address = 0x01;
     eeprom
         .write_page(memory_address, &[0xAB, 0xCD, 0xEF, 0x12])
         .unwrap();
 
     // wait maximum time necessary for write
-    delay.delay_ms(5_u16);
+    delay.delay_ms(5);
     loop {
         let mut data = [0; 4];
         eeprom.read_data(memory_address, &mut data).unwrap();
+        // The LED blinks as healthy if the data was written correctly.
         if data == [0xAB, 0xCD, 0xEF, 0x12] {
-            led.set_high().unwrap();
-            delay.delay_ms(500_u16);
-            led.set_low().unwrap();
-            delay.delay_ms(500_u16);
+            heartbeat::feed(Status::Healthy);
+        } else {
+            heartbeat::feed(Status::SensorError);
         }
+        delay.delay_ms(500);
     }
 }
@@ -4,6 +4,9 @@
 //! Introductory blog post with some pictures here:
 //! https://blog.eldruin.com/ccs811-indoor-air-quality-sensor-driver-in-rust/
 //!
+//! If a transfer hangs, the independent watchdog resets the board, which
+//! then shows "Recovered from watchdog reset" for two seconds.
+//!
 //! This example is runs on the STM32F3 Discovery board using I2C1.
 //!
 //! ```
@@ -25,115 +28,77 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X12, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, warn, Discovery, Monitor, TextPanel,
 };
+use embedded_hal::delay::DelayNs;
 use nb::block;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
 
-use embedded_ccs811::{prelude::*, AlgorithmResult, Ccs811Awake, MeasurementMode, SlaveAddr};
+use embedded_ccs811::{prelude::*, Ccs811Awake, MeasurementMode, SlaveAddr};
+
+/// The board is reset if a task does not check in for this long.
+const WATCHDOG_TIMEOUT_MS: u32 = 5_000;
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("CCS811 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
+    logging::init();
+    info!("CCS811 example");
 
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDisplayInterface::new(manager.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X12)
-        .text_color(BinaryColor::On)
-        .build();
+    let mut board = Discovery::take().unwrap();
+    let mut watchdog = board.watchdog().unwrap();
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        reset_cause,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    info!("Reset cause: {}", reset_cause.description());
+    if reset_cause.is_watchdog() {
+        panel.set_row(0, "Recovered from");
+        panel.set_row(1, "watchdog reset");
+        panel.flush().unwrap();
+        delay.delay_ms(2000);
+        panel.clear();
+    }
 
     let address = SlaveAddr::default();
-    let mut sensor = Ccs811Awake::new(manager.acquire_i2c(), address);
+    let mut sensor = Ccs811Awake::new(i2c.acquire_i2c(), address);
     sensor.software_reset().unwrap();
-    delay.delay_ms(3_u16);
+    delay.delay_ms(3);
     let mut sensor = sensor.start_application().ok().unwrap();
-    delay.delay_ms(2_u8);
+    delay.delay_ms(2);
     let temperature_c = 25.0;
     let relative_humidity_perc = 60.0;
     sensor
-        .set_environment(temperature_c, relative_humidity_perc)
+        .set_environment(relative_humidity_perc, temperature_c)
         .unwrap();
     sensor.set_mode(MeasurementMode::ConstantPower1s).unwrap();
 
-    let default = AlgorithmResult {
-        eco2: 9999,
-        etvoc: 9999,
-        raw_current: 255,
-        raw_voltage: 9999,
-    };
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
+    let mut monitor = Monitor::new("CCS811");
+    let gas = watchdog.register("CCS811").unwrap();
+    let screen = watchdog.register("display").unwrap();
+    watchdog.start(WATCHDOG_TIMEOUT_MS);
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(50_u16);
-        led.set_low().unwrap();
-        delay.delay_ms(50_u16);
-
-        let data = block!(sensor.data()).unwrap_or(default);
-
-        lines[0].clear();
-        lines[1].clear();
-        write!(lines[0], "eCO2: {}", data.eco2).unwrap();
-        write!(lines[1], "eTVOC: {}", data.etvoc).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::with_baseline(
-                line,
-                Point::new(0, i as i32 * 16),
-                text_style,
-                Baseline::Top,
-            )
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || block!(sensor.data()));
+        watchdog.check_in(gas);
+        panel
+            .show_reading(&reading, |panel, data| {
+                write!(panel.row(0), "eCO2: {}", data.eco2).unwrap();
+                write!(panel.row(1), "eTVOC: {}", data.etvoc).unwrap();
+            })
             .unwrap();
+        watchdog.check_in(screen);
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
+        watchdog.feed();
     }
 }
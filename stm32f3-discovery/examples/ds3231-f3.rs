@@ -1,6 +1,6 @@
 //! Stores the date and time on a DS3231 real-time clock (RTC).
-//! Then reads the date and time repeatedly and blink LED 0
-//! for the first 30 seconds.
+//! Then reads the date and time roughly every second and
+//! prints it through RTT.
 //!
 //! This example is runs on the STM32F3 Discovery board using I2C1.
 //!
@@ -20,51 +20,29 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, Discovery,
+};
+use embedded_hal::delay::DelayNs;
 
 use ds323x::{DateTimeAccess, Ds323x, NaiveDate};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("DS3231 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let mut rtc = Ds323x::new_ds3231(i2c);
+    logging::init();
+    info!("DS3231 example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
+
+    let mut rtc = Ds323x::new_ds3231(i2c.acquire_i2c());
     let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
         .unwrap()
         .and_hms_opt(10, 21, 34)
@@ -72,12 +50,8 @@ fn main() -> ! {
     rtc.set_datetime(&begin).unwrap();
     loop {
         let now = rtc.datetime().unwrap();
-        if (now - begin).num_seconds() < 30 {
-            // this will blink for 30 seconds
-            led.set_high().unwrap();
-            delay.delay_ms(250_u16);
-            led.set_low().unwrap();
-            delay.delay_ms(250_u16);
-        }
+        info!("Date/Time: {}", logging::Dbg(&now));
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(1000);
     }
 }
@@ -1,6 +1,6 @@
 //! Stores the date and time on a DS3234 real-time clock (RTC).
-//! Then reads the date and time repeatedly and blink LED 0
-//! for 30 seconds.
+//! Then reads the date and time roughly every second and
+//! prints it through RTT.
 //!
 //! This example is runs on the STM32F3 Discovery board using SPI1.
 //!
@@ -22,68 +22,37 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use embedded_hal::blocking::delay::DelayMs;
-use embedded_hal::digital::v2::OutputPin;
-use embedded_hal::spi::MODE_1;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{
-    delay::Delay,
-    pac,
-    prelude::*,
-    spi::{config::Config, Spi},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, Compat, Discovery,
 };
+use embedded_hal::delay::DelayNs;
+use embedded_hal::spi::MODE_1;
+use stm32f3xx_hal::prelude::*;
 
 use ds323x::{DateTimeAccess, Ds323x, NaiveDate};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("DS3234 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
+    logging::init();
+    info!("DS3234 example");
 
-    let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-
-    // SPI configuration
-    let sck = gpioa
-        .pa5
-        .into_af5_push_pull(&mut gpioa.moder, &mut gpioa.otyper, &mut gpioa.afrl);
-    let miso = gpioa
-        .pa6
-        .into_af5_push_pull(&mut gpioa.moder, &mut gpioa.otyper, &mut gpioa.afrl);
-    let mosi = gpioa
-        .pa7
-        .into_af5_push_pull(&mut gpioa.moder, &mut gpioa.otyper, &mut gpioa.afrl);
-
-    let spi_config = Config::default().frequency(1.MHz()).mode(MODE_1);
-    let spi = Spi::new(
-        dp.SPI1,
-        (sck, miso, mosi),
-        spi_config,
-        clocks,
-        &mut rcc.apb2,
-    );
-
-    let mut chip_select = gpiob
+    let mut board = Discovery::take().unwrap();
+    let spi1 = board.spi1(MODE_1, 1.MHz()).unwrap();
+    let chip_select = board
+        .gpiob
         .pb1
-        .into_push_pull_output(&mut gpiob.moder, &mut gpiob.otyper);
-
-    chip_select.set_high().unwrap();
+        .into_push_pull_output(&mut board.gpiob.moder, &mut board.gpiob.otyper);
+    let spi = spi1.acquire_spi(Compat::new(chip_select)).unwrap();
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        ..
+    } = board;
+    heartbeat.start(led);
 
-    let mut rtc = Ds323x::new_ds3234(spi, chip_select);
+    let mut rtc = Ds323x::new_ds3234(spi);
     let begin = NaiveDate::from_ymd_opt(2022, 5, 2)
         .unwrap()
         .and_hms_opt(10, 21, 34)
@@ -91,12 +60,8 @@ fn main() -> ! {
     rtc.set_datetime(&begin).unwrap();
     loop {
         let now = rtc.datetime().unwrap();
-        if (now - begin).num_seconds() < 30 {
-            // this will blink for 30 seconds
-            led.set_high().unwrap();
-            delay.delay_ms(250_u16);
-            led.set_low().unwrap();
-            delay.delay_ms(250_u16);
-        }
+        info!("Date/Time: {}", logging::Dbg(&now));
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(1000);
     }
 }
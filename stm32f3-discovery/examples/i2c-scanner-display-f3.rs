@@ -0,0 +1,98 @@
+//! Scan the I2C bus and identify the devices which are connected to it.
+//!
+//! Every address is probed and for each device which answers, the identity
+//! registers of the supported parts which can be at that address are read.
+//! The results are shown on an SSD1306 OLED display four at a time and
+//! printed through RTT and USART1, e.g.:
+//!
+//! ```text
+//! 0x3C: SSD1306/PCF8574A?
+//! 0x40: HDC2080 (device id 0x07D0)
+//! 0x5A: CCS811 (hw id 0x81)
+//! ```
+//!
+//! This is useful to check the wiring before running any other example.
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1 and USART1.
+//!
+//! ```
+//! F3   <-> Devices <-> Display <-> Serial
+//! GND  <-> GND     <-> GND     <-> GND
+//! +5V  <-> VCC     <-> VDD
+//! PB6  <-> SCL     <-> SCL
+//! PB7  <-> SDA     <-> SDA
+//! PA9                          <-> RX
+//! ```
+//!
+//! Beware that PB6 and PB7 run on 5V level so put a logic level shifter in
+//! between if your devices run on 3.3V.
+//!
+//! Run with:
+//! `cargo run --example i2c-scanner-display-f3 --target thumbv7em-none-eabihf`
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use core::fmt::{self, Write};
+use cortex_m_rt::entry;
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, scan, Discovery, Serial1, TextPanel,
+};
+use embedded_hal::delay::DelayNs;
+use stm32f3xx_hal::prelude::*;
+
+const ROWS: usize = 4;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("I2C scanner example");
+    let mut board = Discovery::take().unwrap();
+    let mut serial = board.usart1(115_200.Bd()).unwrap();
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = board;
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut scanner_i2c = i2c.acquire_i2c();
+    loop {
+        panel.page("I2C scan");
+        let mut count = 0;
+        for found in scan::scan(&mut scanner_i2c) {
+            info!("{}", found);
+            send(&mut serial, format_args!("{}\r\n", found));
+            if count > 0 && count % ROWS == 0 {
+                panel.flush().unwrap();
+                heartbeat::feed(Status::Healthy);
+                delay.delay_ms(2000);
+                panel.clear_rows();
+            }
+            write!(panel.row(count % ROWS), "{:#}", found).unwrap();
+            count += 1;
+        }
+        info!("{} devices found", count);
+        send(&mut serial, format_args!("{} devices found\r\n", count));
+        if count == 0 {
+            panel.set_row(0, "No devices found");
+        }
+        panel.flush().unwrap();
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(2000);
+    }
+}
+
+fn send(serial: &mut Serial1, line: fmt::Arguments<'_>) {
+    let mut buffer: heapless::String<64> = heapless::String::new();
+    buffer.write_fmt(line).unwrap();
+    serial.bwrite_all(buffer.as_bytes()).unwrap();
+    serial.bflush().unwrap();
+}
@@ -23,101 +23,51 @@
 
 use core::fmt::Write;
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, warn, Discovery, Monitor, TextPanel,
 };
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use embedded_hal::delay::DelayNs;
 
 use mlx9061x::{Mlx9061x, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MLX90614 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDisplayInterface::new(manager.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut sensor =
-        Mlx9061x::new_mlx90614(manager.acquire_i2c(), SlaveAddr::default(), 5).unwrap();
-
-    let mut lines: [heapless::String<32>; 2] = [heapless::String::new(), heapless::String::new()];
-
+    logging::init();
+    info!("MLX90614 example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut sensor = Mlx9061x::new_mlx90614(i2c.acquire_i2c(), SlaveAddr::default(), 5).unwrap();
+
+    let mut object_monitor = Monitor::new("MLX90614");
+    let mut ambient_monitor = Monitor::new("MLX90614");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(50_u16);
-        led.set_low().unwrap();
-        delay.delay_ms(50_u16);
-
-        let t_obj = sensor.object1_temperature().unwrap_or(-1.0);
-        delay.delay_ms(50_u16); // a pause is necessary in between
-        let t_a = sensor.ambient_temperature().unwrap_or(-1.0);
-
-        lines[0].clear();
-        lines[1].clear();
-        write!(lines[0], "Object: {:.2}ºC", t_obj).unwrap();
-        write!(lines[1], "Ambient: {:.2}ºC", t_a).unwrap();
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::with_baseline(
-                line,
-                Point::new(0, i as i32 * 16),
-                text_style,
-                Baseline::Top,
-            )
-            .draw(&mut disp)
+        let object = object_monitor.read(&mut delay, || sensor.object1_temperature());
+        delay.delay_ms(50); // a pause is necessary in between
+        let reading = object.and_then(|t_obj| {
+            let t_a = ambient_monitor.read(&mut delay, || sensor.ambient_temperature())?;
+            Ok((t_obj, t_a))
+        });
+        panel
+            .show_reading(&reading, |panel, (t_obj, t_a)| {
+                write!(panel.row(0), "Object: {:.2}ºC", t_obj).unwrap();
+                write!(panel.row(1), "Ambient: {:.2}ºC", t_a).unwrap();
+            })
             .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -1,8 +1,9 @@
 //! Continuously read the acceleration with an MMA8452Q and
 //! transmit it per USART. (trivially adaptable to similar models).
 //!
-//! When running you should be able to see the acceleration readings in your
-//! serial communication program.
+//! Every value is sent as a binary telemetry frame tagged with the device,
+//! channel, unit and time, see `driver_examples_common::telemetry`. Decode
+//! them on the computer with the `telemetry` crate.
 //!
 //! This example is runs on the STM32F3 Discovery board using I2C1 and USART1.
 //!
@@ -31,68 +32,48 @@
 #![no_std]
 #![no_main]
 
-use core::fmt::Write;
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{self as hal, pac, prelude::*, serial::Serial};
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging,
+    sensor::Sensor,
+    telemetry::{self, Sample, MAX_FRAME_LEN},
+    Compat, Discovery,
+};
+use stm32f3xx_hal::prelude::*;
 
 use mma8x5x::{Mma8x5x, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("MMA8452 example");
+    logging::init();
+    info!("MMA8452 example");
 
-    let dp = pac::Peripherals::take().unwrap();
+    let mut board = Discovery::take().unwrap();
+    let mut serial = board.usart1(115_200.Bd()).unwrap();
+    let Discovery {
+        mut clock,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        ..
+    } = board;
+    heartbeat.start(led);
 
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
-    let pins = (
-        gpioa
-            .pa9
-            .into_af7_push_pull(&mut gpioa.moder, &mut gpioa.otyper, &mut gpioa.afrh),
-        gpioa
-            .pa10
-            .into_af7_push_pull(&mut gpioa.moder, &mut gpioa.otyper, &mut gpioa.afrh),
-    );
-    let mut serial = Serial::new(dp.USART1, pins, 115_200.Bd(), clocks, &mut rcc.apb2);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let accelerometer = Mma8x5x::new_mma8452(i2c, SlaveAddr::default());
+    let accelerometer = Mma8x5x::new_mma8452(Compat::new(i2c.acquire_i2c()), SlaveAddr::default());
     let mut accelerometer = accelerometer.into_active().ok().unwrap();
 
+    let mut frame = [0; MAX_FRAME_LEN];
     loop {
-        let accel = accelerometer.read().unwrap();
-
-        // transform numbers to string
-        let mut buffer: heapless::String<1616> = heapless::String::new();
-        write!(buffer, "{},{},{} ", accel.x, accel.y, accel.z).unwrap();
-
-        // send buffer
-        serial.bwrite_all(&buffer.into_bytes()).unwrap();
+        let quantities = accelerometer.measure().unwrap();
+        let timestamp_ms = clock.now_ms();
+        for quantity in &quantities {
+            let sample = Sample::of(timestamp_ms, accelerometer.name(), quantity);
+            serial
+                .bwrite_all(telemetry::encode(&sample, &mut frame).unwrap())
+                .unwrap();
+        }
         serial.bflush().unwrap();
+        heartbeat::feed(Status::Healthy);
     }
 }
@@ -24,51 +24,29 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, Discovery,
+};
+use embedded_hal::delay::DelayNs;
 
 use pwm_pca9685::{Address, Channel, Pca9685};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("PCA9685 example");
+    logging::init();
+    info!("PCA9685 example");
 
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
 
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let mut pwm = Pca9685::new(i2c, Address::default()).unwrap();
+    let mut pwm = Pca9685::new(i2c.acquire_i2c(), Address::default()).unwrap();
     // This results in about 60 Hz, which is the frequency at which servos operate.
     pwm.set_prescale(100).unwrap();
     pwm.enable().unwrap();
@@ -81,13 +59,8 @@ fn main() -> ! {
     let mut current = servo_min;
     let mut factor: i16 = 1;
     loop {
-        // Blink LED 0 (really fast, it will seem to be on the whole time)
-        // to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(1_u16);
-        led.set_low().unwrap();
-        delay.delay_ms(1_u16);
+        heartbeat::feed(Status::Healthy);
+        delay.delay_ms(2);
 
         pwm.set_channel_off(Channel::C0, current).unwrap();
         pwm.set_channel_off(Channel::C1, servo_min + (servo_max - current))
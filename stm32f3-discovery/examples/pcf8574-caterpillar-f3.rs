@@ -20,52 +20,36 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, Discovery, I2C_FAST_FREQUENCY,
+};
+use embedded_hal::delay::DelayNs;
 
 use pcf857x::{Pcf8574, SlaveAddr};
 
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("PCF8574 example");
+    logging::init();
+    info!("PCF8574 example");
 
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        400.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-    let mut expander = Pcf8574::new(i2c, SlaveAddr::default());
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        ..
+    } = Discovery::take_with_i2c_frequency(I2C_FAST_FREQUENCY).unwrap();
+    heartbeat.start(led);
+    let mut expander = Pcf8574::new(i2c.acquire_i2c(), SlaveAddr::default());
 
     let mut output_status = OutputStatus::new();
 
     loop {
         if let Some(status) = output_status.next() {
             expander.set(status).unwrap();
-            delay.delay_ms(100_u16);
+            heartbeat::feed(Status::Healthy);
+            delay.delay_ms(100);
         }
     }
 }
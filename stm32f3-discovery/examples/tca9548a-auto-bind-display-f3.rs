@@ -0,0 +1,214 @@
+//! Scan all eight channels of a TCA9548A, bind a driver to the sensor found
+//! on each of them and show their measurements on an SSD1306 OLED display,
+//! one channel per page.
+//!
+//! The supported sensors are the VEML6040 and TCS34725 color sensors and
+//! the OPT3001 ambient light sensor. Any combination of them can be
+//! connected to the channels, including several with the same address.
+//! The OPT3001 and TCS34725 are bound by their identity registers. The
+//! VEML6040 has none, so it is bound to an unidentified device at 0x10.
+//! The VEML6030 and VEML6075 there are identified and left out.
+//! The sensors are read round-robin and the bound channels are printed
+//! through RTT, e.g.:
+//!
+//! ```text
+//! Channel 0: VEML6040
+//! Channel 3: OPT3001
+//! Channel 5: TCS34725
+//! ```
+//!
+//! This example is runs on the STM32F3 Discovery board using I2C1.
+//!
+//! ```
+//! F3   <-> TCA9548A <-> Display <-> Sensor 0 <-> ... <-> Sensor 7
+//! GND  <-> GND      <-> GND     <-> GND      <-> ... <-> GND
+//! 3.3V <-> VCC      <-> VDD     <-> VCC      <-> ... <-> VCC
+//! PB7  <-> SDA      <-> SDA
+//! PB6  <-> SCL      <-> SCL
+//!          SDA0                 <-> SDA
+//!          SCL0                 <-> SCL
+//!          ...                                   <-> ...
+//!          SDA7                                      <-> SDA
+//!          SCL7                                      <-> SCL
+//! ```
+//!
+//! Run with:
+//! `cargo run --example tca9548a-auto-bind-display-f3 --target thumbv7em-none-eabihf`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use cortex_m_rt::entry;
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging,
+    mux::Channels,
+    scan, Compat, Discovery, Monitor, TextPanel,
+};
+use embedded_hal::delay::DelayNs;
+use nb::block;
+
+use opt300x::{ic, mode, Opt300x, SlaveAddr as Opt300xAddr};
+use tcs3472::Tcs3472;
+use veml6040::Veml6040;
+use xca9548a::{SlaveAddr, Xca9548a};
+
+use core::fmt::Write;
+
+/// Maximum number of devices on the upstream bus.
+const MAX_UPSTREAM: usize = 8;
+
+enum Device<I2C> {
+    Veml6040(Veml6040<Compat<I2C>>),
+    Opt3001(Opt300x<I2C, ic::Opt3001, mode::OneShot>),
+    Tcs3472(Tcs3472<I2C>),
+}
+
+struct Sensor<I2C> {
+    device: Device<I2C>,
+    monitor: Monitor,
+}
+
+impl<I2C> Sensor<I2C> {
+    fn new(device: Device<I2C>) -> Self {
+        let name = match device {
+            Device::Veml6040(_) => "VEML6040",
+            Device::Opt3001(_) => "OPT3001",
+            Device::Tcs3472(_) => "TCS34725",
+        };
+        Sensor {
+            device,
+            monitor: Monitor::new(name),
+        }
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("TCA9548A auto-bind example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+    panel.page("TCA9548A");
+    panel.set_row(0, "Scanning channels...");
+    panel.flush().unwrap();
+
+    // The devices on the upstream bus answer on every channel as well, so
+    // find them first with all channels disabled.
+    let mut i2c_switch = Xca9548a::new(i2c.acquire_i2c(), SlaveAddr::default());
+    i2c_switch.select_channels(0).unwrap();
+    let upstream: heapless::Vec<u8, MAX_UPSTREAM> = scan::scan(i2c.acquire_i2c())
+        .map(|found| found.address)
+        .take(MAX_UPSTREAM)
+        .collect();
+
+    let parts = i2c_switch.split();
+    let channels = [
+        parts.i2c0, parts.i2c1, parts.i2c2, parts.i2c3, parts.i2c4, parts.i2c5, parts.i2c6,
+        parts.i2c7,
+    ];
+    let mut sensors = Channels::bind(channels, &upstream, |channel, i2c, found| {
+        let part = found.identity.map(|identity| identity.part);
+        let device = match (found.address, part) {
+            // The VEML6040 has no identity register, the VEML6030 and
+            // VEML6075 at the same address do.
+            (0x10, None) => {
+                let mut sensor = Veml6040::new(Compat::new(i2c));
+                sensor.enable().ok()?;
+                Device::Veml6040(sensor)
+            }
+            (0x44, Some("OPT3001")) => {
+                Device::Opt3001(Opt300x::new_opt3001(i2c, Opt300xAddr::default()))
+            }
+            (0x29, Some("TCS34725")) => {
+                let mut sensor = Tcs3472::new(i2c);
+                sensor.enable().ok()?;
+                sensor.enable_rgbc().ok()?;
+                Device::Tcs3472(sensor)
+            }
+            _ => {
+                info!("Channel {}: {} not supported", channel, found);
+                return None;
+            }
+        };
+        Some(Sensor::new(device))
+    });
+
+    for (channel, sensor) in sensors.iter() {
+        info!("Channel {}: {}", channel, sensor.monitor.device());
+    }
+    if sensors.count() == 0 {
+        info!("No sensors found");
+        panel.set_row(0, "No sensors found");
+        panel.flush().unwrap();
+    }
+
+    let mut title = heapless::String::<32>::new();
+    loop {
+        let Some((channel, sensor)) = sensors.poll() else {
+            heartbeat::feed(Status::SensorError);
+            delay.delay_ms(1000);
+            continue;
+        };
+
+        title.clear();
+        write!(title, "Channel {}: {}", channel, sensor.monitor.device()).unwrap();
+        let monitor = &mut sensor.monitor;
+        let status = match &mut sensor.device {
+            Device::Veml6040(sensor) => {
+                let reading = monitor.read(&mut delay, || sensor.read_all_channels());
+                panel
+                    .show_reading(&reading, |panel, m| {
+                        panel.page(&title);
+                        write!(panel.row(0), "R {} G {}", m.red, m.green).unwrap();
+                        write!(panel.row(1), "B {} W {}", m.blue, m.white).unwrap();
+                    })
+                    .unwrap();
+                Status::of(&reading)
+            }
+            Device::Opt3001(sensor) => {
+                let reading = monitor.read(&mut delay, || block!(sensor.read_lux()));
+                panel
+                    .show_reading(&reading, |panel, m| {
+                        panel.page(&title);
+                        write!(panel.row(0), "lux {:.2}", m.result).unwrap();
+                    })
+                    .unwrap();
+                Status::of(&reading)
+            }
+            Device::Tcs3472(sensor) => {
+                let reading = monitor.read(&mut delay, || {
+                    sensor.read_clear_channel().and_then(|clear| {
+                        Ok([
+                            clear,
+                            sensor.read_red_channel()?,
+                            sensor.read_green_channel()?,
+                            sensor.read_blue_channel()?,
+                        ])
+                    })
+                });
+                panel
+                    .show_reading(&reading, |panel, [clear, red, green, blue]| {
+                        panel.page(&title);
+                        write!(panel.row(0), "R {} G {}", red, green).unwrap();
+                        write!(panel.row(1), "B {} C {}", blue, clear).unwrap();
+                    })
+                    .unwrap();
+                Status::of(&reading)
+            }
+        };
+        heartbeat::feed(status);
+        delay.delay_ms(1000);
+    }
+}
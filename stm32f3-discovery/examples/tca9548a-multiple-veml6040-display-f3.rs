@@ -24,16 +24,10 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, warn, Compat, Discovery, Monitor, TextPanel,
 };
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
 
 use veml6040::Veml6040;
 use xca9548a::{SlaveAddr, Xca9548a};
@@ -41,98 +35,62 @@ use xca9548a::{SlaveAddr, Xca9548a};
 use core::fmt::Write;
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("TCA9548A example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        100.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDisplayInterface::new(manager.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let i2c_switch = Xca9548a::new(manager.acquire_i2c(), SlaveAddr::default());
+    logging::init();
+    info!("TCA9548A example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Discovery::take().unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let i2c_switch = Xca9548a::new(i2c.acquire_i2c(), SlaveAddr::default());
     let parts = i2c_switch.split();
-    let mut sensor0 = Veml6040::new(parts.i2c0);
-    let mut sensor1 = Veml6040::new(parts.i2c1);
+    let mut sensor0 = Veml6040::new(Compat::new(parts.i2c0));
+    let mut sensor1 = Veml6040::new(Compat::new(parts.i2c1));
     sensor0.enable().unwrap();
     sensor1.enable().unwrap();
+    let mut monitor0 = Monitor::new("VEML6040 #0");
+    let mut monitor1 = Monitor::new("VEML6040 #1");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(50_u16);
-        led.set_low().unwrap();
-        delay.delay_ms(50_u16);
-
-        let mut lines: [heapless::String<32>; 2] =
-            [heapless::String::new(), heapless::String::new()];
-
-        let m0 = sensor0.read_all_channels().unwrap();
-        let m1 = sensor1.read_all_channels().unwrap();
-
-        write!(
-            lines[0],
-            "Sensor 0: R {} G {} B {} W {}",
-            m0.red, m0.green, m0.blue, m0.white
-        )
-        .unwrap();
-        write!(
-            lines[1],
-            "Sensor 1: R {} G {} B {} W {}     ",
-            m1.red, m1.green, m1.blue, m1.white
-        )
-        .unwrap();
-
-        disp.clear();
-        for (i, line) in lines.iter().enumerate() {
-            Text::with_baseline(
-                line,
-                Point::new(0, i as i32 * 16),
-                text_style,
-                Baseline::Top,
-            )
-            .draw(&mut disp)
+        let reading = monitor0
+            .read(&mut delay, || sensor0.read_all_channels())
+            .and_then(|m0| {
+                monitor1
+                    .read(&mut delay, || sensor1.read_all_channels())
+                    .map(|m1| (m0, m1))
+            });
+        panel
+            .show_reading(&reading, |panel, (m0, m1)| {
+                write!(
+                    panel.row(0),
+                    "Sensor 0: R {} G {} B {} W {}",
+                    m0.red,
+                    m0.green,
+                    m0.blue,
+                    m0.white
+                )
+                .unwrap();
+                write!(
+                    panel.row(1),
+                    "Sensor 1: R {} G {} B {} W {}     ",
+                    m1.red,
+                    m1.green,
+                    m1.blue,
+                    m1.white
+                )
+                .unwrap();
+            })
             .unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
         }
-        disp.flush().unwrap();
+        heartbeat::feed(Status::of(&reading));
     }
 }
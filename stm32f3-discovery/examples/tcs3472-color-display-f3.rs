@@ -22,99 +22,60 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
+use driver_examples::{
+    heartbeat::{self, Status},
+    info, logging, warn, Discovery, Monitor, TextPanel, I2C_FAST_FREQUENCY,
 };
-use panic_rtt_target as _;
-use rtt_target::{rprintln, rtt_init_print};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-use stm32f3xx_hal::{self as hal, delay::Delay, pac, prelude::*};
+use embedded_hal::delay::DelayNs;
 
 use tcs3472::Tcs3472;
 
 use core::fmt::Write;
 #[entry]
 fn main() -> ! {
-    rtt_init_print!();
-    rprintln!("TCS3472 example");
-
-    let cp = cortex_m::Peripherals::take().unwrap();
-    let dp = pac::Peripherals::take().unwrap();
-
-    let mut flash = dp.FLASH.constrain();
-    let mut rcc = dp.RCC.constrain();
-    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
-    let clocks = rcc.cfgr.freeze(&mut flash.acr);
-
-    let mut led = gpioe
-        .pe9
-        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
-    let mut delay = Delay::new(cp.SYST, clocks);
-
-    let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
-    let mut scl =
-        gpiob
-            .pb6
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    let mut sda =
-        gpiob
-            .pb7
-            .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
-    scl.internal_pull_up(&mut gpiob.pupdr, true);
-    sda.internal_pull_up(&mut gpiob.pupdr, true);
-
-    let i2c = hal::i2c::I2c::new(
-        dp.I2C1,
-        (scl, sda),
-        400.kHz().try_into().unwrap(),
-        clocks,
-        &mut rcc.apb1,
-    );
-
-    let manager = shared_bus::BusManagerSimple::new(i2c);
-    let interface = I2CDisplayInterface::new(manager.acquire_i2c());
-    let mut disp = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    disp.init().unwrap();
-    disp.flush().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut sensor = Tcs3472::new(manager.acquire_i2c());
+    logging::init();
+    info!("TCS3472 example");
+
+    let Discovery {
+        mut delay,
+        leds: [led, ..],
+        heartbeat,
+        i2c,
+        display,
+        ..
+    } = Discovery::take_with_i2c_frequency(I2C_FAST_FREQUENCY).unwrap();
+    heartbeat.start(led);
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut sensor = Tcs3472::new(i2c.acquire_i2c());
     sensor.enable().unwrap();
     sensor.enable_rgbc().unwrap();
     while !sensor.is_rgbc_status_valid().unwrap() {
         // wait for measurement to be available
-        delay.delay_ms(50_u8);
+        delay.delay_ms(50);
     }
 
+    let mut monitor = Monitor::new("TCS3472");
     loop {
-        // Blink LED 0 to check that everything is actually running.
-        // If the LED 0 is off, something went wrong.
-        led.set_high().unwrap();
-        delay.delay_ms(50_u8);
-        led.set_low().unwrap();
-        delay.delay_ms(50_u8);
-
-        let mut buffer: heapless::String<64> = heapless::String::new();
-
-        // if there was an error, it will print 0
-        let clear = sensor.read_clear_channel().unwrap_or(0);
-        let red = sensor.read_red_channel().unwrap_or(0);
-        let green = sensor.read_green_channel().unwrap_or(0);
-        let blue = sensor.read_blue_channel().unwrap_or(0);
-
-        write!(buffer, "C {} R {} G {} B {}", clear, red, green, blue).unwrap();
-        disp.clear();
-        Text::with_baseline(&buffer, Point::zero(), text_style, Baseline::Top)
-            .draw(&mut disp)
+        let reading = monitor.read(&mut delay, || {
+            sensor.read_clear_channel().and_then(|clear| {
+                Ok([
+                    clear,
+                    sensor.read_red_channel()?,
+                    sensor.read_green_channel()?,
+                    sensor.read_blue_channel()?,
+                ])
+            })
+        });
+        panel
+            .show_reading(&reading, |panel, [clear, red, green, blue]| {
+                write!(panel.row(0), "C {} R {} G {} B {}", clear, red, green, blue).unwrap();
+            })
             .unwrap();
-        disp.flush().unwrap();
+        if let Err(failure) = &reading {
+            warn!("{}", failure);
+        }
+        heartbeat::feed(Status::of(&reading));
     }
 }
@@ -0,0 +1,137 @@
+//! This plays a MIDI file stored in a W25Q64 flash memory using an AD9833
+//! waveform generator / direct digital synthesizer and shows the file on an
+//! SSD1306 OLED display.
+//!
+//! The file is read from `ADDRESS` on into a buffer with `midi::load` and
+//! parsed there. Store any format 0 or 1 file at that address beforehand,
+//! e.g. with a flash programmer. TIM2 ticks every millisecond and times the
+//! notes of `TRACK` like in the `ad9833-midi-player-f3` example.
+//!
+//! The W25Q64 and the AD9833 read the data on different clock edges, so the
+//! flash is on SPI2.
+//!
+//! This example is runs on the STM32F3 Discovery board using SPI1, SPI2 and
+//! I2C1.
+//!
+//! ```
+//! F3   <-> AD9833  <-> Amplifier <-> Display
+//! GND  <-> VSS     <-> GND       <-> GND
+//! 3.3V <-> VDD                   <-> VCC
+//! 5V               <-> VCC
+//! PA5  <-> CLK
+//! PA7  <-> DAT
+//! PB5  <-> FSYNC
+//!          OUT     <-> IN
+//! PB7                            <-> SDA
+//! PB6                            <-> SCL
+//!
+//! F3   <-> W25Q64
+//! GND  <-> GND
+//! +3V  <-> VCC
+//! PB13 <-> CLK
+//! PB14 <-> DO
+//! PB15 <-> DI
+//! PB12 <-> CS
+//! ```
+//!
+//! You will need an amplifier like the PAM8403 or similar and a speaker.
+//!
+//! Run with:
+//! `cargo run --example w25q64-midi-player-f3 --target thumbv7em-none-eabihf`,
+
+#![deny(unsafe_code)]
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use cortex_m_rt::entry;
+use driver_examples::{
+    heartbeat::{self, Status},
+    info,
+    logging::{self, Dbg},
+    midi::{self, Melody},
+    reading::Failure,
+    synth::NotePlayer,
+    warn, Compat, Discovery, TextPanel,
+};
+use nb::block;
+use stm32f3xx_hal::{prelude::*, time::duration::Microseconds};
+
+use ad983x::{Ad983x, MODE};
+use w25::{MODE_0, W25};
+
+/// Address of the MIDI file in the flash.
+const ADDRESS: u32 = 0;
+/// Size of the buffer the file is loaded into.
+const MAX_FILE_LEN: usize = 8 * 1024;
+/// Track with the melody. Track 0 of format 1 files usually only has the
+/// tempo.
+const TRACK: usize = 1;
+/// Frequency of the timer which times the notes.
+const TICK_HZ: u32 = 1_000;
+
+#[entry]
+fn main() -> ! {
+    logging::init();
+    info!("W25Q64 MIDI player example");
+
+    let mut board = Discovery::take().unwrap();
+    let spi1 = board.spi1(MODE, 1.MHz()).unwrap();
+    let spi2 = board.spi2(MODE_0, 1.MHz()).unwrap();
+    let moder = &mut board.gpiob.moder;
+    let otyper = &mut board.gpiob.otyper;
+    let synth_cs = board.gpiob.pb5.into_push_pull_output(moder, otyper);
+    let flash_cs = board.gpiob.pb12.into_push_pull_output(moder, otyper);
+    let Discovery {
+        leds: [led, ..],
+        heartbeat,
+        mut tim2,
+        display,
+        ..
+    } = board;
+    heartbeat.start(led);
+    tim2.start(Microseconds(1_000_000 / TICK_HZ));
+
+    let mut panel = TextPanel::new(display);
+    panel.init().unwrap();
+
+    let mut synth = Ad983x::new_ad9833(spi1.acquire_spi(Compat::new(synth_cs)).unwrap());
+    synth.reset().unwrap();
+    synth.enable().unwrap();
+
+    let mut flash = W25::new_w25q64(spi2.acquire_spi(Compat::new(flash_cs)).unwrap());
+    let mut buffer = [0; MAX_FILE_LEN];
+    let smf = match midi::load(&mut buffer, |offset, bytes| {
+        flash.read_data(ADDRESS + offset, bytes)
+    }) {
+        Ok(smf) => smf,
+        Err(error) => {
+            warn!("Could not load the MIDI file: {}", Dbg(&error));
+            let failure = Failure::new("W25Q64", &error, 1);
+            panel.show_failure(&failure);
+            panel.flush().unwrap();
+            loop {
+                block!(tim2.wait()).unwrap();
+                heartbeat::feed(Status::of_failure(&failure));
+            }
+        }
+    };
+
+    info!("Playing track {} of {}", TRACK, smf.track_count());
+    panel.page("MIDI from W25Q64");
+    write!(panel.row(0), "Format {}", smf.format()).unwrap();
+    write!(panel.row(1), "{} tracks", smf.track_count()).unwrap();
+    write!(panel.row(2), "Playing track {}", TRACK).unwrap();
+    panel.flush().unwrap();
+
+    let mut player = NotePlayer::default();
+    loop {
+        for step in Melody::new(&smf, TRACK, TICK_HZ).unwrap() {
+            for _ in 0..step.ticks {
+                block!(tim2.wait()).unwrap();
+                heartbeat::feed(Status::Healthy);
+            }
+            player.play(&mut synth, step.note).unwrap();
+        }
+    }
+}
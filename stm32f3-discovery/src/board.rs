@@ -0,0 +1,372 @@
+//! Peripheral setup shared by all examples.
+
+use crate::heartbeat::HeartbeatTimer;
+use crate::watchdog::{ResetCause, Watchdog};
+use cortex_m::peripheral::DWT;
+use driver_examples_common::{clock::Clock, compat::spi_mode, new_display, Compat};
+use embedded_hal::spi::Mode;
+use ssd1306::{prelude::*, I2CDisplayInterface};
+use stm32f3xx_hal::{
+    delay::Delay,
+    gpio::{
+        gpioa::{self, PA0, PA1, PA10, PA2, PA3, PA4, PA5, PA6, PA7, PA8, PA9},
+        gpiob::{
+            self, PB0, PB1, PB10, PB11, PB12, PB13, PB14, PB15, PB2, PB4, PB5, PB6, PB7, PB8, PB9,
+        },
+        gpioe::PEx,
+        Input, OpenDrain, Output, PushPull, AF0, AF4, AF5, AF7,
+    },
+    i2c::I2c,
+    pac::{self, I2C1, IWDG, SPI1, SPI2, TIM2, USART1},
+    prelude::*,
+    rcc::{Clocks, APB1, APB2},
+    serial::Serial,
+    spi::{config::Config, Spi},
+    time::rate::{Baud, Hertz, Megahertz},
+    timer::Timer,
+    watchdog::IndependentWatchDog,
+};
+
+/// I2C1 peripheral on PB6 (SCL) and PB7 (SDA).
+pub type I2c1 = I2c<I2C1, (PB6<AF4<OpenDrain>>, PB7<AF4<OpenDrain>>)>;
+
+/// Bus sharing I2C1 between the display and the devices.
+pub type I2cBus = driver_examples_common::I2cBus<Compat<I2c1>>;
+
+/// Handle to the shared I2C1 bus which can be passed to any driver.
+pub type I2cProxy = driver_examples_common::I2cDevice<'static, Compat<I2c1>>;
+
+/// SSD1306 OLED display connected to the shared I2C1 bus.
+pub type Display = driver_examples_common::Display<I2CInterface<I2cProxy>>;
+
+/// SPI1 peripheral on PA5 (SCK), PA6 (MISO) and PA7 (MOSI).
+pub type Spi1 = Spi<SPI1, (PA5<AF5<PushPull>>, PA6<AF5<PushPull>>, PA7<AF5<PushPull>>)>;
+
+/// SPI1 shared between the devices on it, see [`Discovery::spi1`].
+pub type Spi1Bus = driver_examples_common::SpiBus<Compat<Spi1>>;
+
+/// Device on SPI1 with its own chip select pin, which can be passed to
+/// any driver.
+pub type Spi1Device<CS> = driver_examples_common::SpiDevice<'static, Compat<Spi1>, Compat<CS>>;
+
+/// SPI2 peripheral on PB13 (SCK), PB14 (MISO) and PB15 (MOSI).
+pub type Spi2 = Spi<
+    SPI2,
+    (
+        PB13<AF5<PushPull>>,
+        PB14<AF5<PushPull>>,
+        PB15<AF5<PushPull>>,
+    ),
+>;
+
+/// SPI2 shared between the devices on it, see [`Discovery::spi2`].
+pub type Spi2Bus = driver_examples_common::SpiBus<Compat<Spi2>>;
+
+/// Device on SPI2 with its own chip select pin, which can be passed to
+/// any driver.
+pub type Spi2Device<CS> = driver_examples_common::SpiDevice<'static, Compat<Spi2>, Compat<CS>>;
+
+/// USART1 peripheral on PA9 (TX) and PA10 (RX).
+pub type Serial1 = Serial<USART1, (PA9<AF7<PushPull>>, PA10<AF7<PushPull>>)>;
+
+/// One of the compass rose user LEDs on PE8-PE15.
+pub type Led = PEx<Output<PushPull>>;
+
+/// Standard mode I2C at 100 kHz. Most devices are fine with this.
+pub const I2C_STANDARD_FREQUENCY: Hertz = Hertz(100_000);
+
+/// Fast mode I2C at 400 kHz.
+pub const I2C_FAST_FREQUENCY: Hertz = Hertz(400_000);
+
+/// GPIOA pins not used by the board setup.
+///
+/// PA0 is connected to the user button.
+/// PA5-PA7 are reserved for SPI1 and PA9-PA10 for USART1.
+pub struct GpioA {
+    pub moder: gpioa::MODER,
+    pub otyper: gpioa::OTYPER,
+    pub pupdr: gpioa::PUPDR,
+    pub afrl: gpioa::AFRL,
+    pub afrh: gpioa::AFRH,
+    pub pa0: PA0<Input>,
+    pub pa1: PA1<Input>,
+    pub pa2: PA2<Input>,
+    pub pa3: PA3<Input>,
+    pub pa4: PA4<Input>,
+    pub pa8: PA8<Input>,
+}
+
+/// GPIOB pins not used by the board setup.
+///
+/// PB6-PB7 are used by I2C1 and PB13-PB15 are reserved for SPI2.
+pub struct GpioB {
+    pub moder: gpiob::MODER,
+    pub otyper: gpiob::OTYPER,
+    pub pupdr: gpiob::PUPDR,
+    pub afrl: gpiob::AFRL,
+    pub afrh: gpiob::AFRH,
+    pub pb0: PB0<Input>,
+    pub pb1: PB1<Input>,
+    pub pb2: PB2<Input>,
+    /// Still the JTAG NJTRST pin after reset.
+    pub pb4: PB4<AF0<PushPull>>,
+    pub pb5: PB5<Input>,
+    pub pb8: PB8<Input>,
+    pub pb9: PB9<Input>,
+    pub pb10: PB10<Input>,
+    pub pb11: PB11<Input>,
+    pub pb12: PB12<Input>,
+}
+
+/// SPI1 and its pins until they are set up by [`Discovery::spi1`].
+type Spi1Parts = (SPI1, PA5<Input>, PA6<Input>, PA7<Input>);
+
+/// SPI2 and its pins until they are set up by [`Discovery::spi2`].
+type Spi2Parts = (SPI2, PB13<Input>, PB14<Input>, PB15<Input>);
+
+/// STM32F3 Discovery board as used in the examples.
+///
+/// ```
+/// F3   <-> Display
+/// GND  <-> GND
+/// +5V  <-> VDD
+/// PB6  <-> SCL
+/// PB7  <-> SDA
+/// ```
+pub struct Discovery {
+    /// Clocks running from the internal 8 MHz oscillator.
+    pub clocks: Clocks,
+    /// Why the chip was reset, e.g. to tell that the watchdog recovered
+    /// the board.
+    pub reset_cause: ResetCause,
+    /// SysTick-based delay.
+    pub delay: Compat<Delay>,
+    /// Milliseconds since the board was set up, from the cycle counter.
+    ///
+    /// The cycle counter wraps around every minute, so read it at least
+    /// that often.
+    pub clock: Clock,
+    /// User LEDs going clockwise around the compass rose starting at
+    /// north: LD3 (PE9), LD5 (PE10), LD7 (PE11), LD9 (PE12), LD10 (PE13),
+    /// LD8 (PE14), LD6 (PE15) and LD4 (PE8).
+    ///
+    /// `leds[0]` is the heartbeat LED used by the examples.
+    pub leds: [Led; 8],
+    /// TIM3, which blinks the heartbeat LED once started, see
+    /// [`heartbeat`](crate::heartbeat).
+    pub heartbeat: HeartbeatTimer,
+    /// Free general purpose timer, e.g. to time the notes of a melody.
+    pub tim2: Timer<TIM2>,
+    /// Shared I2C1 bus. Use `i2c.acquire_i2c()` to get a handle for a driver.
+    pub i2c: &'static I2cBus,
+    /// SSD1306 display on the I2C1 bus.
+    ///
+    /// It is not initialized since not all setups include a display.
+    /// Call `display.init()` before using it.
+    pub display: Display,
+    /// Free GPIOA pins.
+    pub gpioa: GpioA,
+    /// Free GPIOB pins.
+    pub gpiob: GpioB,
+    apb1: APB1,
+    apb2: APB2,
+    spi1: Option<Spi1Parts>,
+    spi2: Option<Spi2Parts>,
+    usart1: Option<(USART1, PA9<Input>, PA10<Input>)>,
+    iwdg: Option<IWDG>,
+}
+
+impl Discovery {
+    /// Take the device peripherals and set up the board with I2C1 in
+    /// standard mode (100 kHz).
+    ///
+    /// Returns `None` if the peripherals have already been taken.
+    pub fn take() -> Option<Self> {
+        Self::take_with_i2c_frequency(I2C_STANDARD_FREQUENCY)
+    }
+
+    /// Take the device peripherals and set up the board with I2C1 running
+    /// at the given frequency.
+    ///
+    /// Returns `None` if the peripherals have already been taken.
+    pub fn take_with_i2c_frequency(frequency: Hertz) -> Option<Self> {
+        let mut cp = cortex_m::Peripherals::take()?;
+        let dp = pac::Peripherals::take()?;
+
+        let reset_cause = ResetCause::from_csr(dp.RCC.csr.read().bits());
+        dp.RCC.csr.modify(|_, w| w.rmvf().set_bit());
+
+        let mut flash = dp.FLASH.constrain();
+        let mut rcc = dp.RCC.constrain();
+        let clocks = rcc.cfgr.freeze(&mut flash.acr);
+        #[cfg(feature = "panic-display")]
+        crate::panic_display::register(clocks);
+        let gpioa = dp.GPIOA.split(&mut rcc.ahb);
+        let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
+        let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
+
+        let mut scl =
+            gpiob
+                .pb6
+                .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
+        let mut sda =
+            gpiob
+                .pb7
+                .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
+        scl.internal_pull_up(&mut gpiob.pupdr, true);
+        sda.internal_pull_up(&mut gpiob.pupdr, true);
+        let i2c = I2c::new(dp.I2C1, (scl, sda), frequency, clocks, &mut rcc.apb1);
+        let i2c: &'static I2cBus = cortex_m::singleton!(: I2cBus = I2cBus::new(Compat::new(i2c)))?;
+
+        let display = new_display(I2CDisplayInterface::new(i2c.acquire_i2c()));
+
+        let moder = &mut gpioe.moder;
+        let otyper = &mut gpioe.otyper;
+        let leds = [
+            gpioe.pe9.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe10.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe11.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe12.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe13.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe14.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe15.into_push_pull_output(moder, otyper).downgrade(),
+            gpioe.pe8.into_push_pull_output(moder, otyper).downgrade(),
+        ];
+        let delay = Compat::new(Delay::new(cp.SYST, clocks));
+        cp.DCB.enable_trace();
+        cp.DWT.enable_cycle_counter();
+        let clock = Clock::new(clocks.sysclk().0, DWT::cycle_count);
+        let heartbeat = HeartbeatTimer::new(Timer::new(dp.TIM3, clocks, &mut rcc.apb1));
+        let tim2 = Timer::new(dp.TIM2, clocks, &mut rcc.apb1);
+
+        Some(Discovery {
+            clocks,
+            reset_cause,
+            delay,
+            clock,
+            leds,
+            heartbeat,
+            tim2,
+            i2c,
+            display,
+            gpioa: GpioA {
+                moder: gpioa.moder,
+                otyper: gpioa.otyper,
+                pupdr: gpioa.pupdr,
+                afrl: gpioa.afrl,
+                afrh: gpioa.afrh,
+                pa0: gpioa.pa0,
+                pa1: gpioa.pa1,
+                pa2: gpioa.pa2,
+                pa3: gpioa.pa3,
+                pa4: gpioa.pa4,
+                pa8: gpioa.pa8,
+            },
+            gpiob: GpioB {
+                moder: gpiob.moder,
+                otyper: gpiob.otyper,
+                pupdr: gpiob.pupdr,
+                afrl: gpiob.afrl,
+                afrh: gpiob.afrh,
+                pb0: gpiob.pb0,
+                pb1: gpiob.pb1,
+                pb2: gpiob.pb2,
+                pb4: gpiob.pb4,
+                pb5: gpiob.pb5,
+                pb8: gpiob.pb8,
+                pb9: gpiob.pb9,
+                pb10: gpiob.pb10,
+                pb11: gpiob.pb11,
+                pb12: gpiob.pb12,
+            },
+            apb1: rcc.apb1,
+            apb2: rcc.apb2,
+            spi1: Some((dp.SPI1, gpioa.pa5, gpioa.pa6, gpioa.pa7)),
+            spi2: Some((dp.SPI2, gpiob.pb13, gpiob.pb14, gpiob.pb15)),
+            usart1: Some((dp.USART1, gpioa.pa9, gpioa.pa10)),
+            iwdg: Some(dp.IWDG),
+        })
+    }
+
+    /// Set up SPI1 with the SPI mode and frequency required by the
+    /// devices on it.
+    ///
+    /// Every device gets its handle from the returned bus with its chip
+    /// select pin (e.g. PB1 or PB5), configured as an output:
+    ///
+    /// ```ignore
+    /// let spi1 = board.spi1(MODE, 1.MHz()).unwrap();
+    /// let cs = board
+    ///     .gpiob
+    ///     .pb5
+    ///     .into_push_pull_output(&mut board.gpiob.moder, &mut board.gpiob.otyper);
+    /// let spi = spi1.acquire_spi(Compat::new(cs)).unwrap();
+    /// ```
+    ///
+    /// Set up the bus before taking the pins out of the board, since the
+    /// board cannot be borrowed anymore afterwards.
+    ///
+    /// Returns `None` if SPI1 has already been set up.
+    pub fn spi1(&mut self, mode: Mode, frequency: Megahertz) -> Option<&'static Spi1Bus> {
+        let (spi, sck, miso, mosi) = self.spi1.take()?;
+        let moder = &mut self.gpioa.moder;
+        let otyper = &mut self.gpioa.otyper;
+        let afrl = &mut self.gpioa.afrl;
+        let sck = sck.into_af5_push_pull(moder, otyper, afrl);
+        let miso = miso.into_af5_push_pull(moder, otyper, afrl);
+        let mosi = mosi.into_af5_push_pull(moder, otyper, afrl);
+        let config = Config::default().frequency(frequency).mode(spi_mode(mode));
+        let spi = Spi::new(spi, (sck, miso, mosi), config, self.clocks, &mut self.apb2);
+        let bus: &'static Spi1Bus =
+            cortex_m::singleton!(: Spi1Bus = Spi1Bus::new(Compat::new(spi)))?;
+        Some(bus)
+    }
+
+    /// Set up SPI2 like [`spi1`](Discovery::spi1), for devices which need
+    /// another SPI mode than the ones on SPI1.
+    ///
+    /// Returns `None` if SPI2 has already been set up.
+    pub fn spi2(&mut self, mode: Mode, frequency: Megahertz) -> Option<&'static Spi2Bus> {
+        let (spi, sck, miso, mosi) = self.spi2.take()?;
+        let moder = &mut self.gpiob.moder;
+        let otyper = &mut self.gpiob.otyper;
+        let afrh = &mut self.gpiob.afrh;
+        let sck = sck.into_af5_push_pull(moder, otyper, afrh);
+        let miso = miso.into_af5_push_pull(moder, otyper, afrh);
+        let mosi = mosi.into_af5_push_pull(moder, otyper, afrh);
+        let config = Config::default().frequency(frequency).mode(spi_mode(mode));
+        let spi = Spi::new(spi, (sck, miso, mosi), config, self.clocks, &mut self.apb1);
+        let bus: &'static Spi2Bus =
+            cortex_m::singleton!(: Spi2Bus = Spi2Bus::new(Compat::new(spi)))?;
+        Some(bus)
+    }
+
+    /// Set up USART1 with the given baud rate.
+    ///
+    /// Returns `None` if USART1 has already been set up.
+    pub fn usart1(&mut self, baud_rate: Baud) -> Option<Serial1> {
+        let (usart, tx, rx) = self.usart1.take()?;
+        let moder = &mut self.gpioa.moder;
+        let otyper = &mut self.gpioa.otyper;
+        let afrh = &mut self.gpioa.afrh;
+        let tx = tx.into_af7_push_pull(moder, otyper, afrh);
+        let rx = rx.into_af7_push_pull(moder, otyper, afrh);
+        Some(Serial::new(
+            usart,
+            (tx, rx),
+            baud_rate,
+            self.clocks,
+            &mut self.apb2,
+        ))
+    }
+
+    /// Set up the independent watchdog. Register the tasks which must
+    /// check in before it is fed and then start it, see
+    /// [`watchdog`](crate::watchdog).
+    ///
+    /// Returns `None` if the watchdog has already been set up.
+    pub fn watchdog(&mut self) -> Option<Watchdog> {
+        let iwdg = self.iwdg.take()?;
+        Some(Watchdog::new(IndependentWatchDog::new(iwdg)))
+    }
+}
@@ -0,0 +1,104 @@
+//! Heartbeat LED driven by the TIM3 interrupt.
+//!
+//! The main loop does not blink the LED anymore, which delayed every
+//! measurement by 100 ms. Instead, it hands the LED over to the heartbeat
+//! and feeds it with the status of its readings:
+//!
+//! ```no_run
+//! use driver_examples::{heartbeat::{self, Status}, Discovery};
+//!
+//! let Discovery {
+//!     leds: [led, ..],
+//!     heartbeat,
+//!     ..
+//! } = Discovery::take().unwrap();
+//! heartbeat.start(led);
+//! loop {
+//!     // Read the sensors...
+//!     heartbeat::feed(Status::Healthy);
+//! }
+//! ```
+//!
+//! The LED blinks once per second while everything works and two or three
+//! times in a row on sensor or bus errors. See
+//! [`Status`] for all patterns. If the main loop does not feed the
+//! heartbeat for [`TIMEOUT_TICKS`], the LED stays off.
+//!
+//! On a panic, the LED only blinks SOS with the `panic-display` feature.
+//! The default panic handlers stop the program and leave the LED as it
+//! was.
+
+use crate::board::Led;
+use core::cell::RefCell;
+use cortex_m::interrupt::{free, Mutex};
+use driver_examples_common::heartbeat::Heartbeat;
+pub use driver_examples_common::heartbeat::{Status, TICK_HZ};
+use stm32f3xx_hal::{
+    pac::{interrupt, Interrupt, NVIC, TIM3},
+    prelude::*,
+    time::duration::Milliseconds,
+    timer::{Event, Timer},
+};
+
+/// Number of ticks without being fed after which the heartbeat stops (5 s).
+pub const TIMEOUT_TICKS: u32 = 5 * TICK_HZ;
+
+struct Service {
+    led: Led,
+    timer: Timer<TIM3>,
+    heartbeat: Heartbeat,
+}
+
+static SERVICE: Mutex<RefCell<Option<Service>>> = Mutex::new(RefCell::new(None));
+
+/// TIM3, ready to drive the heartbeat LED at [`TICK_HZ`].
+pub struct HeartbeatTimer {
+    timer: Timer<TIM3>,
+}
+
+impl HeartbeatTimer {
+    pub(crate) fn new(timer: Timer<TIM3>) -> Self {
+        HeartbeatTimer { timer }
+    }
+
+    /// Blink the LED from the TIM3 interrupt from now on.
+    pub fn start(self, led: Led) {
+        let mut timer = self.timer;
+        timer.enable_interrupt(Event::Update);
+        timer.start(Milliseconds(1000 / TICK_HZ));
+        free(|cs| {
+            SERVICE.borrow(cs).replace(Some(Service {
+                led,
+                timer,
+                heartbeat: Heartbeat::new(TIMEOUT_TICKS),
+            }))
+        });
+        // SAFETY: The handler only accesses the service in a critical section.
+        unsafe { NVIC::unmask(Interrupt::TIM3) };
+    }
+}
+
+/// Tell the heartbeat that the main loop is alive and its status.
+///
+/// It does nothing if the heartbeat has not been started.
+pub fn feed(status: Status) {
+    free(|cs| {
+        if let Some(service) = SERVICE.borrow(cs).borrow_mut().as_mut() {
+            service.heartbeat.feed(status);
+        }
+    });
+}
+
+#[interrupt]
+fn TIM3() {
+    free(|cs| {
+        if let Some(service) = SERVICE.borrow(cs).borrow_mut().as_mut() {
+            service.timer.clear_event(Event::Update);
+            let _ = if service.heartbeat.tick() {
+                service.led.set_high()
+            } else {
+                service.led.set_low()
+            };
+        }
+    });
+}
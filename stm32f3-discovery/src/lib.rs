@@ -0,0 +1,65 @@
+//! Board support for the STM32F3 Discovery examples.
+//!
+//! All examples share the same setup: clocks, I2C1 on PB6/PB7 shared between
+//! the devices and an SSD1306 display, SPI1, SPI2, USART1, the compass rose
+//! LEDs and a delay. Instead of repeating it in every program, take a [`Discovery`]:
+//!
+//! ```no_run
+//! use driver_examples::{Compat, Discovery, TextPanel};
+//! use mcp4x::MODE;
+//! use stm32f3xx_hal::prelude::*;
+//!
+//! let mut board = Discovery::take().unwrap();
+//! let spi1 = board.spi1(MODE, 1.MHz()).unwrap();
+//! let chip_select = board
+//!     .gpiob
+//!     .pb1
+//!     .into_push_pull_output(&mut board.gpiob.moder, &mut board.gpiob.otyper);
+//! let spi = spi1.acquire_spi(Compat::new(chip_select)).unwrap();
+//! let i2c = board.i2c.acquire_i2c();
+//! let mut panel = TextPanel::new(board.display);
+//! panel.init().unwrap();
+//! ```
+//!
+//! The delay, the I2C handles and the SPI devices implement the embedded-hal
+//! 1.0 traits so that they can be passed to any driver.
+//!
+//! The heartbeat LED blinks from a timer interrupt with the status of the
+//! program, see [`heartbeat`].
+//!
+//! The independent watchdog resets the board if one of the tasks of the
+//! program hangs, see [`watchdog`].
+//!
+//! The examples log over RTT, either as formatted text or with `defmt`, see
+//! [`logging`].
+//!
+//! The crate also provides the panic handler of the examples. By default it
+//! is the one of `panic-rtt-target`, or `panic-probe` with the `defmt`
+//! feature. With the `panic-display` feature the
+//! panic is shown on the display as well and the heartbeat LED blinks SOS.
+#![no_std]
+
+mod board;
+pub mod heartbeat;
+#[path = "../../common/board/logging.rs"]
+pub mod logging;
+#[cfg(feature = "panic-display")]
+mod panic_display;
+pub mod watchdog;
+pub use crate::board::{
+    Discovery, Display, GpioA, GpioB, I2c1, I2cBus, I2cProxy, Led, Serial1, Spi1, Spi1Bus,
+    Spi1Device, Spi2, Spi2Bus, Spi2Device, I2C_FAST_FREQUENCY, I2C_STANDARD_FREQUENCY,
+};
+pub use crate::heartbeat::HeartbeatTimer;
+pub use crate::watchdog::{ResetCause, Watchdog};
+pub use driver_examples_common::{
+    adc, bus, generator, midi, mux, new_display, reading, rtttl, scan, sensor, synth, telemetry,
+    Compat, Monitor, Panel, TextPanel,
+};
+
+#[cfg(feature = "defmt")]
+use defmt_rtt as _;
+#[cfg(all(feature = "defmt", not(feature = "panic-display")))]
+use panic_probe as _;
+#[cfg(not(any(feature = "defmt", feature = "panic-display")))]
+use panic_rtt_target as _;
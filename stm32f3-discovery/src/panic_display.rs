@@ -0,0 +1,105 @@
+//! Panic handler which shows the panic on the display.
+//!
+//! It replaces `panic-rtt-target` when the `panic-display` feature is
+//! enabled:
+//!
+//! ```text
+//! cargo embed --example lm75-display-f3 --features panic-display --release
+//! ```
+//!
+//! Since the program is stopped anyway, it takes I2C1, USART1 and the
+//! heartbeat LED (LD3) over from whoever owned them. It prints the panic
+//! over RTT and over USART1 at 115200 bauds and shows the message, file and
+//! line on the display. Then it blinks SOS on the LED forever.
+
+use crate::board::I2C_STANDARD_FREQUENCY;
+use core::cell::Cell;
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use cortex_m::interrupt::{self, Mutex};
+use driver_examples_common::{
+    crash::Report,
+    heartbeat::{Heartbeat, Status, TICK_HZ},
+    new_display, Compat, TextPanel,
+};
+use ssd1306::I2CDisplayInterface;
+use stm32f3xx_hal::{i2c::I2c, pac, prelude::*, rcc::Clocks, serial::Serial};
+
+/// Baud rate of the panic report on USART1.
+const BAUD_RATE: u32 = 115_200;
+
+/// Frequency of the internal oscillator the chip runs on.
+const HSI_FREQUENCY: u32 = 8_000_000;
+
+static CLOCKS: Mutex<Cell<Option<Clocks>>> = Mutex::new(Cell::new(None));
+
+/// Remember the clocks so that the peripherals can be set up again when
+/// panicking.
+pub(crate) fn register(clocks: Clocks) {
+    interrupt::free(|cs| CLOCKS.borrow(cs).set(Some(clocks)));
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    interrupt::disable();
+    let report = Report::new(info);
+    crate::error!("{}", report);
+
+    // SAFETY: Interrupts are disabled and the main program does not run
+    // anymore, so nothing else uses the peripherals.
+    let dp = unsafe { pac::Peripherals::steal() };
+    let clocks = interrupt::free(|cs| CLOCKS.borrow(cs).get());
+    let mut rcc = dp.RCC.constrain();
+    if let Some(clocks) = clocks {
+        let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
+        let moder = &mut gpioa.moder;
+        let otyper = &mut gpioa.otyper;
+        let tx = gpioa.pa9.into_af7_push_pull(moder, otyper, &mut gpioa.afrh);
+        let rx = gpioa
+            .pa10
+            .into_af7_push_pull(moder, otyper, &mut gpioa.afrh);
+        let mut serial = Serial::new(dp.USART1, (tx, rx), BAUD_RATE.Bd(), clocks, &mut rcc.apb2);
+        let mut text = heapless::String::<128>::new();
+        let _ = write!(text, "{}\r\n", report);
+        let _ = serial.bwrite_all(text.as_bytes());
+
+        let mut gpiob = dp.GPIOB.split(&mut rcc.ahb);
+        let mut scl =
+            gpiob
+                .pb6
+                .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
+        let mut sda =
+            gpiob
+                .pb7
+                .into_af4_open_drain(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrl);
+        scl.internal_pull_up(&mut gpiob.pupdr, true);
+        sda.internal_pull_up(&mut gpiob.pupdr, true);
+        let i2c = I2c::new(
+            dp.I2C1,
+            (scl, sda),
+            I2C_STANDARD_FREQUENCY,
+            clocks,
+            &mut rcc.apb1,
+        );
+        let mut panel = TextPanel::new(new_display(I2CDisplayInterface::new(Compat::new(i2c))));
+        if panel.init().is_ok() {
+            let _ = report.show(&mut panel);
+        }
+    }
+
+    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
+    let mut led = gpioe
+        .pe9
+        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
+    let tick_cycles = clocks.map_or(HSI_FREQUENCY, |clocks| clocks.sysclk().0) / TICK_HZ;
+    let mut heartbeat = Heartbeat::new(0);
+    heartbeat.feed(Status::Panic);
+    loop {
+        let _ = if heartbeat.tick() {
+            led.set_high()
+        } else {
+            led.set_low()
+        };
+        cortex_m::asm::delay(tick_cycles);
+    }
+}
@@ -0,0 +1,84 @@
+//! Independent watchdog which is only fed once every task has checked in.
+//!
+//! A transfer which hangs, e.g. in `nb::block!` waiting for a measurement
+//! which never comes, stops the whole program. The independent watchdog
+//! (IWDG) runs from its own oscillator and resets the chip if it is not fed
+//! in time. Each subsystem of the program registers a [`Task`] and checks in
+//! when it has done its job:
+//!
+//! ```no_run
+//! use driver_examples::Discovery;
+//!
+//! let mut board = Discovery::take().unwrap();
+//! let mut watchdog = board.watchdog().unwrap();
+//! let sensor = watchdog.register("CCS811").unwrap();
+//! let display = watchdog.register("display").unwrap();
+//! watchdog.start(5_000);
+//! loop {
+//!     // Read the sensor...
+//!     watchdog.check_in(sensor);
+//!     // Refresh the display...
+//!     watchdog.check_in(display);
+//!     watchdog.feed();
+//! }
+//! ```
+//!
+//! After the reset,
+//! [`Discovery::reset_cause`](crate::Discovery::reset_cause) tells
+//! that the watchdog recovered the board.
+
+use driver_examples_common::watchdog::Supervisor;
+pub use driver_examples_common::watchdog::{ResetCause, Task};
+use stm32f3xx_hal::{prelude::*, time::duration::Milliseconds, watchdog::IndependentWatchDog};
+
+/// Independent watchdog fed on behalf of several tasks.
+pub struct Watchdog {
+    iwdg: IndependentWatchDog,
+    supervisor: Supervisor,
+}
+
+impl Watchdog {
+    pub(crate) fn new(iwdg: IndependentWatchDog) -> Self {
+        Watchdog {
+            iwdg,
+            supervisor: Supervisor::new(),
+        }
+    }
+
+    /// Register a task which must check in before every feed.
+    ///
+    /// Returns `None` if too many tasks are already registered.
+    pub fn register(&mut self, name: &'static str) -> Option<Task> {
+        self.supervisor.register(name)
+    }
+
+    /// Start the watchdog. The chip is reset if it is not fed within the
+    /// given time in milliseconds (up to 26 seconds).
+    ///
+    /// Once started, the watchdog cannot be stopped.
+    pub fn start(&mut self, timeout_ms: u32) {
+        self.iwdg.start(Milliseconds(timeout_ms));
+    }
+
+    /// Tell that the task is alive.
+    pub fn check_in(&mut self, task: Task) {
+        self.supervisor.check_in(task);
+    }
+
+    /// Feed the watchdog if every registered task has checked in since the
+    /// last feed.
+    ///
+    /// Returns whether the watchdog was fed. The tasks which did not check
+    /// in are printed over RTT, once per stall.
+    pub fn feed(&mut self) -> bool {
+        if !self.supervisor.all_checked_in() {
+            for name in self.supervisor.newly_missing() {
+                crate::warn!("Watchdog: waiting for {}", name);
+            }
+            return false;
+        }
+        self.iwdg.feed();
+        self.supervisor.restart();
+        true
+    }
+}
@@ -0,0 +1,170 @@
+//! Recent values of every channel, ready to be plotted.
+
+use crate::{Record, Unit};
+use std::collections::BTreeMap;
+
+/// Values of one channel of a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    /// Unit of the values.
+    pub unit: Unit,
+    /// Time in seconds and value of every sample, oldest first.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Series {
+    /// Last value received.
+    pub fn last(&self) -> Option<f64> {
+        self.points.last().map(|(_, value)| *value)
+    }
+}
+
+/// Samples received in the last seconds, grouped by device and channel.
+///
+/// If the time of a device goes backwards, the board was reset and its old
+/// samples are dropped.
+#[derive(Debug)]
+pub struct History {
+    window: f64,
+    devices: BTreeMap<String, BTreeMap<String, Series>>,
+}
+
+impl History {
+    /// Keep the samples of the last `window` seconds.
+    pub fn new(window: f64) -> Self {
+        History {
+            window,
+            devices: BTreeMap::new(),
+        }
+    }
+
+    /// Add a record.
+    pub fn push(&mut self, record: &Record) {
+        let time = f64::from(record.timestamp_ms) / 1000.0;
+        let channels = self.devices.entry(record.device.clone()).or_default();
+        if channels.values().any(|series| latest(series) > time) {
+            channels.clear();
+        }
+        let series = channels
+            .entry(record.channel.clone())
+            .or_insert_with(|| Series {
+                unit: record.unit,
+                points: Vec::new(),
+            });
+        series.unit = record.unit;
+        series.points.push((time, f64::from(record.value)));
+        for series in channels.values_mut() {
+            let old = series
+                .points
+                .partition_point(|(t, _)| *t < time - self.window);
+            series.points.drain(..old);
+        }
+    }
+
+    /// Devices with their channels, sorted by name.
+    pub fn devices(&self) -> impl Iterator<Item = (&str, &BTreeMap<String, Series>)> {
+        self.devices
+            .iter()
+            .map(|(device, channels)| (device.as_str(), channels))
+    }
+
+    /// Time range shown for a device: the last `window` seconds it sent.
+    pub fn time_bounds(&self, device: &str) -> [f64; 2] {
+        let end = self.devices.get(device).map_or(0.0, |channels| {
+            channels.values().map(latest).fold(0.0, f64::max)
+        });
+        [end - self.window, end]
+    }
+}
+
+fn latest(series: &Series) -> f64 {
+    series.points.last().map_or(f64::MIN, |(time, _)| *time)
+}
+
+/// Range of the values of some channels with a margin of 5% on each side, so
+/// that the lines do not touch the borders of the plot.
+pub fn value_bounds<'a>(series: impl IntoIterator<Item = &'a Series>) -> [f64; 2] {
+    let (min, max) = series
+        .into_iter()
+        .flat_map(|series| series.points.iter().map(|(_, value)| *value))
+        .fold((f64::MAX, f64::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    if min > max {
+        return [0.0, 1.0];
+    }
+    let margin = ((max - min) * 0.05).max(0.5);
+    [min - margin, max + margin]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp_ms: u32, device: &str, channel: &str, value: f32) -> Record {
+        Record {
+            timestamp_ms,
+            device: device.to_owned(),
+            channel: channel.to_owned(),
+            unit: Unit::MilliG,
+            value,
+        }
+    }
+
+    fn points<'a>(history: &'a History, device: &str, channel: &str) -> &'a [(f64, f64)] {
+        let (_, channels) = history.devices().find(|(d, _)| *d == device).unwrap();
+        &channels[channel].points
+    }
+
+    #[test]
+    fn groups_channels_by_device() {
+        let mut history = History::new(10.0);
+        history.push(&record(1_000, "MMA8452", "X", 1.0));
+        history.push(&record(1_000, "MMA8452", "Y", 2.0));
+        history.push(&record(1_500, "LM75", "Temp", 21.5));
+        let devices: Vec<_> = history
+            .devices()
+            .map(|(device, channels)| (device, channels.keys().cloned().collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("LM75", vec!["Temp".to_owned()]),
+                ("MMA8452", vec!["X".to_owned(), "Y".to_owned()])
+            ],
+            devices
+        );
+    }
+
+    #[test]
+    fn drops_samples_out_of_the_window() {
+        let mut history = History::new(2.0);
+        for timestamp_ms in [0, 1_000, 2_000, 3_000] {
+            history.push(&record(timestamp_ms, "LM75", "Temp", 1.0));
+        }
+        assert_eq!(
+            &[(1.0, 1.0), (2.0, 1.0), (3.0, 1.0)],
+            points(&history, "LM75", "Temp")
+        );
+        assert_eq!([1.0, 3.0], history.time_bounds("LM75"));
+    }
+
+    #[test]
+    fn restarts_device_after_reset() {
+        let mut history = History::new(10.0);
+        history.push(&record(5_000, "LM75", "Temp", 1.0));
+        history.push(&record(5_000, "MMA8452", "X", 1.0));
+        history.push(&record(20, "LM75", "Temp", 2.0));
+        assert_eq!(&[(0.02, 2.0)], points(&history, "LM75", "Temp"));
+        assert_eq!(1, points(&history, "MMA8452", "X").len());
+    }
+
+    #[test]
+    fn value_bounds_with_margin() {
+        let mut history = History::new(10.0);
+        history.push(&record(0, "MMA8452", "X", -100.0));
+        history.push(&record(0, "MMA8452", "Y", 100.0));
+        let (_, channels) = history.devices().next().unwrap();
+        assert_eq!([-110.0, 110.0], value_bounds(channels.values()));
+        assert_eq!([0.0, 1.0], value_bounds([]));
+    }
+}
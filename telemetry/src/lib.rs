@@ -0,0 +1,257 @@
+//! Decoder of the telemetry sent by the streaming examples.
+//!
+//! The examples send every sample as a COBS frame ending in a zero byte, see
+//! `driver_examples_common::telemetry`. This crate splits the byte stream
+//! coming from a serial port or a file into frames and decodes them into
+//! [`Record`]s:
+//!
+//! ```no_run
+//! use driver_examples_telemetry::Records;
+//! use std::fs::File;
+//!
+//! let port = File::open("/dev/ttyACM0").unwrap();
+//! for record in Records::new(port) {
+//!     match record.unwrap() {
+//!         Ok(record) => println!("{record}"),
+//!         Err(error) => eprintln!("Dropped frame: {error}"),
+//!     }
+//! }
+//! ```
+//!
+//! A frame which was cut off when the stream was opened or corrupted on the
+//! way is reported as an error and the decoder continues with the next one.
+
+pub mod history;
+
+use driver_examples_common::telemetry::{self, DELIMITER, MAX_FRAME_LEN};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Read};
+
+pub use driver_examples_common::sensor::Unit;
+pub use driver_examples_common::telemetry::FrameError;
+
+/// Decoded sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Milliseconds since the board started.
+    pub timestamp_ms: u32,
+    /// Name of the device, e.g. `"LM75"`.
+    pub device: String,
+    /// Name of the channel of the device, e.g. `"Temp"` or `"X"`.
+    pub channel: String,
+    /// Unit of the value.
+    pub unit: Unit,
+    /// Measured value.
+    pub value: f32,
+}
+
+impl From<telemetry::Sample<'_>> for Record {
+    fn from(sample: telemetry::Sample<'_>) -> Self {
+        Record {
+            timestamp_ms: sample.timestamp_ms,
+            device: sample.device.to_owned(),
+            channel: sample.channel.to_owned(),
+            unit: sample.unit,
+            value: sample.value,
+        }
+    }
+}
+
+/// Shows e.g. `12.345 s LM75 Temp: 21.50ºC`.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quantity = driver_examples_common::sensor::Quantity::new("", self.unit, self.value);
+        write!(
+            f,
+            "{}.{:03} s {} {}{}",
+            self.timestamp_ms / 1000,
+            self.timestamp_ms % 1000,
+            self.device,
+            self.channel,
+            quantity
+        )
+    }
+}
+
+/// Splits a byte stream into frames and decodes them.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    frame: Vec<u8>,
+    overflow: bool,
+}
+
+impl Decoder {
+    /// Create a decoder waiting for the start of a frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a byte of the stream.
+    ///
+    /// Returns the decoded record or the error once a frame is complete.
+    /// Empty frames are skipped, so that a sender can start with a zero to
+    /// end any partial frame.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Record, FrameError>> {
+        if byte != DELIMITER {
+            if self.frame.len() < MAX_FRAME_LEN {
+                self.frame.push(byte);
+            } else {
+                self.overflow = true;
+            }
+            return None;
+        }
+        let overflow = core::mem::take(&mut self.overflow);
+        if self.frame.is_empty() && !overflow {
+            return None;
+        }
+        let result = if overflow {
+            Err(FrameError::TooLong)
+        } else {
+            telemetry::decode(&mut self.frame).map(Record::from)
+        };
+        self.frame.clear();
+        Some(result)
+    }
+
+    /// Add several bytes of the stream and return the frames completed by
+    /// them.
+    pub fn push_all(&mut self, bytes: &[u8]) -> Vec<Result<Record, FrameError>> {
+        bytes.iter().filter_map(|byte| self.push(*byte)).collect()
+    }
+}
+
+/// Iterator over the records of a reader, e.g. a serial port or a file with
+/// a recording.
+///
+/// Returns an I/O error if reading fails and stops at the end of the input.
+#[derive(Debug)]
+pub struct Records<R> {
+    reader: R,
+    decoder: Decoder,
+    buffer: Box<[u8]>,
+    pending: std::collections::VecDeque<Result<Record, FrameError>>,
+}
+
+impl<R: Read> Records<R> {
+    /// Read the records from `reader`.
+    pub fn new(reader: R) -> Self {
+        Records {
+            reader,
+            decoder: Decoder::new(),
+            buffer: vec![0; 256].into_boxed_slice(),
+            pending: Default::default(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Records<R> {
+    type Item = io::Result<Result<Record, FrameError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Some(Ok(record));
+            }
+            let read = match self.reader.read(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Some(Err(error)),
+            };
+            self.pending
+                .extend(self.decoder.push_all(&self.buffer[..read]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use driver_examples_common::telemetry::Sample;
+
+    fn frame(timestamp_ms: u32, channel: &str, value: f32) -> Vec<u8> {
+        let sample = Sample {
+            timestamp_ms,
+            device: "MMA8452",
+            channel,
+            unit: Unit::MilliG,
+            value,
+        };
+        let mut buffer = [0; MAX_FRAME_LEN];
+        telemetry::encode(&sample, &mut buffer).unwrap().to_vec()
+    }
+
+    fn record(timestamp_ms: u32, channel: &str, value: f32) -> Record {
+        Record {
+            timestamp_ms,
+            device: "MMA8452".to_owned(),
+            channel: channel.to_owned(),
+            unit: Unit::MilliG,
+            value,
+        }
+    }
+
+    #[test]
+    fn decodes_stream_in_chunks() {
+        let stream = [frame(1_000, "X", 12.0), frame(1_000, "Y", -981.0)].concat();
+        let mut decoder = Decoder::new();
+        let (first, second) = stream.split_at(5);
+        assert!(decoder.push_all(first).is_empty());
+        assert_eq!(
+            vec![Ok(record(1_000, "X", 12.0)), Ok(record(1_000, "Y", -981.0))],
+            decoder.push_all(second)
+        );
+    }
+
+    #[test]
+    fn resynchronizes_after_partial_frame() {
+        let first = frame(1_000, "X", 12.0);
+        let stream = [&first[3..], &frame(1_020, "X", 15.0)].concat();
+        let records = Decoder::new().push_all(&stream);
+        assert_eq!(2, records.len());
+        assert!(records[0].is_err());
+        assert_eq!(Ok(record(1_020, "X", 15.0)), records[1]);
+    }
+
+    #[test]
+    fn skips_empty_frames() {
+        let stream = [&[0, 0][..], &frame(5, "Z", 1.0)].concat();
+        assert_eq!(
+            vec![Ok(record(5, "Z", 1.0))],
+            Decoder::new().push_all(&stream)
+        );
+    }
+
+    #[test]
+    fn reports_overlong_frames() {
+        let mut stream = vec![1; MAX_FRAME_LEN + 10];
+        stream.push(DELIMITER);
+        stream.extend(frame(5, "Z", 1.0));
+        assert_eq!(
+            vec![Err(FrameError::TooLong), Ok(record(5, "Z", 1.0))],
+            Decoder::new().push_all(&stream)
+        );
+    }
+
+    #[test]
+    fn reads_records_until_the_end() {
+        let stream = [frame(1, "X", 1.0), frame(2, "X", 2.0)].concat();
+        let records: Vec<_> = Records::new(&stream[..])
+            .map(|record| record.unwrap().unwrap().timestamp_ms)
+            .collect();
+        assert_eq!(vec![1, 2], records);
+    }
+
+    #[test]
+    fn shows_time_device_and_quantity() {
+        let record = Record {
+            timestamp_ms: 12_345,
+            device: "LM75".to_owned(),
+            channel: "Temp".to_owned(),
+            unit: Unit::Celsius,
+            value: 21.5,
+        };
+        assert_eq!("12.345 s LM75 Temp: 21.50ºC", record.to_string());
+    }
+}
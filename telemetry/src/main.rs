@@ -0,0 +1,288 @@
+//! Shows the telemetry of the streaming examples as live plots in the
+//! terminal and records it as JSON lines.
+//!
+//! ```text
+//! telemetry --port /dev/ttyACM0 --record lm75.jsonl
+//! telemetry --input capture.bin --print
+//! ```
+//!
+//! Every device gets a plot with all its channels, e.g. the three axes of an
+//! accelerometer. Press `q` or `Esc` to quit.
+
+use clap::{ArgGroup, Parser};
+use driver_examples_telemetry::history::{value_bounds, History};
+use driver_examples_telemetry::{Record, Records};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+const COLORS: [Color; 6] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+/// Live plots and recordings of the telemetry of the driver examples.
+#[derive(Debug, Parser)]
+#[command(version, group(ArgGroup::new("source").required(true)))]
+struct Args {
+    /// Serial port the board is connected to, e.g. /dev/ttyACM0 or COM3.
+    #[arg(short, long, group = "source")]
+    port: Option<String>,
+
+    /// Baud rate of the serial port.
+    #[arg(short, long, default_value_t = 115_200)]
+    baud: u32,
+
+    /// File or pipe with the raw bytes sent by a board, `-` for stdin.
+    #[arg(short, long, group = "source")]
+    input: Option<PathBuf>,
+
+    /// Append every record to this file as a JSON line.
+    #[arg(short, long)]
+    record: Option<PathBuf>,
+
+    /// Print the records as text instead of plotting them.
+    #[arg(long)]
+    print: bool,
+
+    /// Seconds of history shown in the plots.
+    #[arg(short, long, default_value_t = 10.0)]
+    window: f64,
+}
+
+/// What the reader thread sends to the display.
+enum Message {
+    Record(Record),
+    Dropped,
+    Error(io::Error),
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let source = open(&args)?;
+    let recording = match &args.record {
+        Some(path) => Some(BufWriter::new(
+            File::options().create(true).append(true).open(path)?,
+        )),
+        None => None,
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || read(source, recording, |message| sender.send(message).is_ok()));
+
+    if args.print {
+        return print(receiver);
+    }
+    let terminal = ratatui::init();
+    let result = View::new(History::new(args.window), receiver).run(terminal);
+    ratatui::restore();
+    result
+}
+
+fn open(args: &Args) -> io::Result<Box<dyn Read + Send>> {
+    if let Some(port) = &args.port {
+        let port = serialport::new(port, args.baud)
+            .timeout(Duration::from_millis(100))
+            .open()?;
+        return Ok(Box::new(port));
+    }
+    match args.input.as_deref() {
+        Some(path) if path.as_os_str() == "-" => Ok(Box::new(io::stdin())),
+        Some(path) => Ok(Box::new(File::open(path)?)),
+        None => unreachable!("clap requires a source"),
+    }
+}
+
+/// Decode the records of `source`, record them and pass them on until
+/// `send` fails or the source ends.
+fn read(source: impl Read, mut recording: Option<impl Write>, send: impl Fn(Message) -> bool) {
+    for record in Records::new(source) {
+        let message = match record {
+            Ok(Ok(record)) => {
+                if let Some(recording) = &mut recording {
+                    if let Err(error) = write_line(recording, &record) {
+                        send(Message::Error(error));
+                        return;
+                    }
+                }
+                Message::Record(record)
+            }
+            Ok(Err(_)) => Message::Dropped,
+            // The serial port times out when the board sends nothing.
+            Err(error) if error.kind() == io::ErrorKind::TimedOut => continue,
+            Err(error) => Message::Error(error),
+        };
+        if !send(message) {
+            return;
+        }
+    }
+}
+
+fn write_line(recording: &mut impl Write, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *recording, record)?;
+    writeln!(recording)?;
+    recording.flush()
+}
+
+fn print(receiver: Receiver<Message>) -> io::Result<()> {
+    for message in receiver {
+        match message {
+            Message::Record(record) => println!("{record}"),
+            Message::Dropped => eprintln!("Dropped a corrupted frame"),
+            Message::Error(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+/// Plots of the history in the terminal.
+struct View {
+    history: History,
+    receiver: Receiver<Message>,
+    dropped: u32,
+    status: String,
+}
+
+impl View {
+    fn new(history: History, receiver: Receiver<Message>) -> Self {
+        View {
+            history,
+            receiver,
+            dropped: 0,
+            status: "Waiting for data...".to_owned(),
+        }
+    }
+
+    fn run(mut self, mut terminal: DefaultTerminal) -> io::Result<()> {
+        loop {
+            self.receive();
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(Duration::from_millis(50))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press
+                        && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn receive(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Record(record)) => {
+                    self.status = format!("Last: {record}");
+                    self.history.push(&record);
+                }
+                Ok(Message::Dropped) => self.dropped += 1,
+                Ok(Message::Error(error)) => self.status = format!("Error: {error}"),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    if !self.status.starts_with("Error") {
+                        self.status = "End of the input".to_owned();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let devices: Vec<_> = self.history.devices().collect();
+        let [plots, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let areas = Layout::vertical(vec![Constraint::Fill(1); devices.len()]).split(plots);
+        for ((device, channels), area) in devices.iter().zip(areas.iter()) {
+            let datasets = channels
+                .iter()
+                .zip(COLORS.iter().cycle())
+                .map(|((channel, series), color)| {
+                    let last = series.last().unwrap_or_default();
+                    let decimals = series.unit.decimals();
+                    Dataset::default()
+                        .name(format!(
+                            "{channel}: {last:.decimals$}{}",
+                            series.unit.symbol()
+                        ))
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::default().fg(*color))
+                        .data(&series.points)
+                })
+                .collect();
+            let [start, end] = self.history.time_bounds(device);
+            let [min, max] = value_bounds(channels.values());
+            let chart = Chart::new(datasets)
+                .block(Block::bordered().title(*device))
+                .x_axis(
+                    Axis::default()
+                        .bounds([start, end])
+                        .labels([format!("{start:.1} s"), format!("{end:.1} s")]),
+                )
+                .y_axis(
+                    Axis::default()
+                        .bounds([min, max])
+                        .labels([format!("{min:.1}"), format!("{max:.1}")]),
+                );
+            frame.render_widget(chart, *area);
+        }
+        let status_line = format!(
+            "{} | dropped frames: {} | q: quit",
+            self.status, self.dropped
+        );
+        frame.render_widget(Paragraph::new(Line::from(status_line)), status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use driver_examples_common::telemetry::{encode, Sample, MAX_FRAME_LEN};
+    use driver_examples_telemetry::Unit;
+    use std::cell::RefCell;
+
+    #[test]
+    fn records_json_lines() {
+        let sample = Sample {
+            timestamp_ms: 1_500,
+            device: "LM75",
+            channel: "Temp",
+            unit: Unit::Celsius,
+            value: 21.5,
+        };
+        let mut buffer = [0; MAX_FRAME_LEN];
+        let frame = encode(&sample, &mut buffer).unwrap().to_vec();
+        let source = [&frame[2..], &frame, &frame].concat();
+
+        let mut recording = Vec::new();
+        let messages = RefCell::new(Vec::new());
+        read(&source[..], Some(&mut recording), |message| {
+            messages
+                .borrow_mut()
+                .push(matches!(message, Message::Record(_)));
+            true
+        });
+
+        assert_eq!(vec![false, true, true], messages.into_inner());
+        let line = r#"{"timestamp_ms":1500,"device":"LM75","channel":"Temp","unit":"Celsius","value":21.5}"#;
+        assert_eq!(
+            format!("{line}\n{line}\n"),
+            String::from_utf8(recording).unwrap()
+        );
+    }
+}